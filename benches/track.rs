@@ -0,0 +1,56 @@
+//! Measures the cost of creating an error and tracking it a few times.
+//!
+//! Run with `cargo bench --bench track`,
+//! and with `cargo bench --bench track --features smallvec` to measure the inline history storage.
+#[macro_use]
+extern crate trackable;
+
+use std::hint::black_box;
+use std::time::Instant;
+use trackable::error::{ErrorKindExt, Failed, Failure};
+use trackable::{History, Location};
+
+const ITERATIONS: u32 = 1_000_000;
+
+fn fail(depth: usize) -> Result<(), Failure> {
+    if depth == 0 {
+        track_panic!(Failed);
+    }
+    track!(fail(depth - 1))
+}
+
+fn bench<F: FnMut()>(name: &str, mut f: F) {
+    for _ in 0..ITERATIONS / 10 {
+        f();
+    }
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "{:<24} {:>8.1} ns/iter",
+        name,
+        elapsed.as_nanos() as f64 / f64::from(ITERATIONS)
+    );
+}
+
+fn main() {
+    bench("new_error", || {
+        black_box(Failure::from(Failed.error()));
+    });
+    for &len in &[1, 3, 8] {
+        bench(&format!("history_add_{}", len), || {
+            let mut history = History::new();
+            for i in 0..len {
+                history.add(Location::new(module_path!(), file!(), i, ""));
+            }
+            black_box(history);
+        });
+    }
+    for &depth in &[1, 3, 8] {
+        bench(&format!("track_depth_{}", depth), || {
+            black_box(fail(black_box(depth))).ok();
+        });
+    }
+}
@@ -0,0 +1,150 @@
+//! Compact binary encoding of trackable errors and histories.
+//!
+//! This module provides [MessagePack] (`msgpack` feature) and [CBOR] (`cbor` feature) codecs
+//! for the serializable types of this crate (e.g., `TrackableError`, `Failure` and `History`).
+//!
+//! # Wire shape
+//!
+//! The encoded values have the same shape as the `serde` representation of the types
+//! (e.g., the JSON produced by `serde_json`): structs are encoded as maps keyed by the field names.
+//! The only exception is `History`: human-readable formats encode a history without extra state
+//! (e.g., omitted events, cycles or branches) as the bare sequence of its events,
+//! but these codecs are not human-readable and always encode it as a map with
+//! the `events`, `state` (`nil` if none) and `branches` fields.
+//! The shape is stable within the same major version of this crate:
+//! new fields are only added as optional ones, and unknown fields are ignored when decoding.
+//! So values encoded by an older version can be decoded by a newer version and vice versa.
+//!
+//! Note that the fields which are not serializable (e.g., the extensions and the linked errors of an error,
+//! and the annotations of a location) are not encoded.
+//!
+//! # Examples
+//!
+//! ```
+//! # #[macro_use]
+//! # extern crate trackable;
+//! #
+//! # fn main() {
+//! use trackable::binary;
+//! use trackable::error::{Failed, Failure, ErrorKindExt};
+//!
+//! let e: Failure = track!(Failed.cause("oops")).into();
+//!
+//! # #[cfg(feature = "msgpack")]
+//! # {
+//! let bytes = binary::to_msgpack(&e).unwrap();
+//! let decoded: Failure = binary::from_msgpack(&bytes).unwrap();
+//! assert_eq!(decoded.to_string(), e.to_string());
+//! # }
+//!
+//! # #[cfg(feature = "cbor")]
+//! # {
+//! let bytes = binary::to_cbor(&e).unwrap();
+//! let decoded: Failure = binary::from_cbor(&bytes).unwrap();
+//! assert_eq!(decoded.to_string(), e.to_string());
+//! # }
+//! # }
+//! ```
+//!
+//! [MessagePack]: https://msgpack.org/
+//! [CBOR]: https://cbor.io/
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use error::Failure;
+
+/// Encodes `value` into MessagePack bytes.
+#[cfg(feature = "msgpack")]
+pub fn to_msgpack<T: Serialize>(value: &T) -> Result<Vec<u8>, Failure> {
+    track!(::rmp_serde::to_vec_named(value).map_err(Failure::from_error))
+}
+
+/// Decodes a value from MessagePack bytes.
+#[cfg(feature = "msgpack")]
+pub fn from_msgpack<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, Failure> {
+    track!(::rmp_serde::from_slice(bytes).map_err(Failure::from_error))
+}
+
+/// Encodes `value` into CBOR bytes.
+#[cfg(feature = "cbor")]
+pub fn to_cbor<T: Serialize>(value: &T) -> Result<Vec<u8>, Failure> {
+    let mut bytes = Vec::new();
+    track!(::ciborium::into_writer(value, &mut bytes).map_err(Failure::from_error))?;
+    Ok(bytes)
+}
+
+/// Decodes a value from CBOR bytes.
+#[cfg(feature = "cbor")]
+pub fn from_cbor<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, Failure> {
+    track!(::ciborium::from_reader(bytes).map_err(Failure::from_error))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use error::Failure;
+    use {History, Location};
+
+    fn history() -> History<Location> {
+        let mut history = History::new();
+        history.add(Location::new("foo", "src/foo.rs", 10, "hello").with_column(5));
+        history
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn msgpack_wire_shape_is_stable() {
+        let bytes = to_msgpack(&history()).unwrap();
+        let decoded: History<Location> = from_msgpack(&bytes).unwrap();
+        assert_eq!(decoded.to_string(), history().to_string());
+
+        // {"events": [1, 2], "state": nil, "branches": []}
+        let mut plain = History::new();
+        plain.add(1u32);
+        plain.add(2);
+        let bytes = b"\x83\xa6events\x92\x01\x02\xa5state\xc0\xa8branches\x90";
+        assert_eq!(to_msgpack(&plain).unwrap(), &bytes[..]);
+        let decoded: History<u32> = from_msgpack(bytes).unwrap();
+        assert_eq!(decoded.events(), [1, 2]);
+
+        // The omitted fields are optional:
+        // {"events": [{"module_path": "foo", "file": "src/foo.rs", "line": 10, "message": "hello"}]}
+        let bytes = b"\x81\xa6events\x91\x84\xabmodule_path\xa3foo\xa4file\xaasrc/foo.rs\
+                      \xa4line\x0a\xa7message\xa5hello";
+        let decoded: History<Location> = from_msgpack(bytes).unwrap();
+        assert_eq!(decoded.events()[0].to_string(), "at src/foo.rs:10 -- hello");
+
+        let e = from_msgpack::<Failure>(b"\xc1");
+        assert!(e.is_err());
+    }
+
+    #[cfg(feature = "cbor")]
+    #[test]
+    fn cbor_wire_shape_is_stable() {
+        use error::{ErrorKindExt, Failed};
+
+        let bytes = to_cbor(&history()).unwrap();
+        let decoded: History<Location> = from_cbor(&bytes).unwrap();
+        assert_eq!(decoded.to_string(), history().to_string());
+
+        // {"events": [1, 2], "state": nil, "branches": []}
+        let mut plain = History::new();
+        plain.add(1u32);
+        plain.add(2);
+        let bytes = b"\xa3\x66events\x82\x01\x02\x65state\xf6\x68branches\x80";
+        assert_eq!(to_cbor(&plain).unwrap(), &bytes[..]);
+        let decoded: History<u32> = from_cbor(bytes).unwrap();
+        assert_eq!(decoded.events(), [1, 2]);
+
+        // The omitted fields are optional:
+        // {"events": [{"module_path": "foo", "file": "src/foo.rs", "line": 10, "message": "hello"}]}
+        let bytes = b"\xa1\x66events\x81\xa4\x6bmodule_path\x63foo\x64file\x6asrc/foo.rs\
+                      \x64line\x0a\x67message\x65hello";
+        let decoded: History<Location> = from_cbor(bytes).unwrap();
+        assert_eq!(decoded.events()[0].to_string(), "at src/foo.rs:10 -- hello");
+
+        let e: Failure = track!(Failed.cause("oops")).into();
+        let decoded: Failure = from_cbor(&to_cbor(&e).unwrap()).unwrap();
+        assert_eq!(decoded.to_string(), e.to_string());
+    }
+}
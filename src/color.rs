@@ -0,0 +1,138 @@
+//! ANSI-colored rendering of errors for terminals.
+//!
+//! This module is available only if the `color` feature is enabled.
+use std::fmt;
+use std::io::IsTerminal;
+
+use error::{ErrorKind, Severity, TrackableError};
+use fmt::Style;
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const CYAN: &str = "\x1b[1;36m";
+const HIGHLIGHT: &str = "\x1b[97m";
+
+/// Returns `true` if the standard error is a terminal and the `NO_COLOR` environment variable is not set.
+///
+/// See <https://no-color.org/> for the details of `NO_COLOR`.
+pub fn enabled() -> bool {
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    !no_color && std::io::stderr().is_terminal()
+}
+
+/// Returns a `Display` wrapper that renders `error` with ANSI colors if `enabled()` returns `true`.
+///
+/// In the colored form, the kind is colored by the severity of the error,
+/// the indices are emphasized, the file paths are dimmed and the messages are highlighted.
+/// Otherwise, the result is the same as the `Display` implementation of `error`.
+///
+/// Note that if a global renderer is set by `error::set_global_renderer`,
+/// it is used as is (i.e., the colors are applied only to the default format).
+///
+/// # Examples
+///
+/// ```
+/// use trackable::Location;
+/// use trackable::color;
+/// use trackable::error::{Failed, TrackableError};
+///
+/// let e = TrackableError::builder(Failed)
+///     .cause("something wrong")
+///     .location(Location::new("foo", "src/foo.rs", 10, "hello"))
+///     .build();
+/// assert_eq!(color::colored(&e).force(false).to_string(), e.to_string());
+/// assert_eq!(color::colored(&e).force(true).to_string(), "\
+/// \x1b[1;31mFailed\x1b[0m (cause; something wrong)
+/// HISTORY:
+///   \x1b[1m[0]\x1b[0m at \x1b[2msrc/foo.rs:10\x1b[0m -- \x1b[97mhello\x1b[0m
+/// ");
+/// ```
+pub fn colored<K: ErrorKind>(error: &TrackableError<K>) -> Colored<'_, K> {
+    Colored {
+        error,
+        enabled: enabled(),
+    }
+}
+
+/// A `Display` wrapper that renders a `TrackableError` with ANSI colors.
+///
+/// This is created by `colored` function.
+#[derive(Debug)]
+pub struct Colored<'a, K: 'a> {
+    error: &'a TrackableError<K>,
+    enabled: bool,
+}
+impl<'a, K: ErrorKind> Colored<'a, K> {
+    /// Overrides whether the colors are used, regardless of the terminal detection.
+    pub fn force(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+}
+impl<'a, K: ErrorKind> fmt::Display for Colored<'a, K> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.enabled {
+            return self.error.fmt_plain(f);
+        }
+
+        let kind = match self.error.severity() {
+            Severity::Fatal | Severity::Error => RED,
+            Severity::Warn => YELLOW,
+            Severity::Info => CYAN,
+            Severity::Debug => DIM,
+        };
+        let style = Style {
+            kind,
+            index: BOLD,
+            position: DIM,
+            message: HIGHLIGHT,
+            note: DIM,
+            reset: RESET,
+        };
+        self.error.fmt_styled(f, &style)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use error::Failed;
+    use {History, Location, Trackable};
+
+    fn strip_escapes(s: &str) -> String {
+        let mut stripped = String::new();
+        let mut rest = s;
+        while let Some(i) = rest.find("\x1b[") {
+            stripped.push_str(&rest[..i]);
+            rest = &rest[i..];
+            rest = &rest[rest.find('m').map_or(rest.len(), |j| j + 1)..];
+        }
+        stripped.push_str(rest);
+        stripped
+    }
+
+    #[test]
+    fn colored_is_plain_except_for_escapes() {
+        let mut e = TrackableError::builder(Failed)
+            .cause("something wrong")
+            .location(Location::new("foo", "src/foo.rs", 10, "hello -- world"))
+            .build()
+            .context("while testing");
+        for _ in 0..3 {
+            e.track(|| Location::new("foo", "src/foo.rs", 11, ""));
+        }
+        let mut branch = History::new();
+        branch.add(Location::new("bar", "src/bar.rs", 20, "in branch"));
+        e.history_mut().unwrap().merge_branch("worker", branch);
+
+        let colored = colored(&e).force(true);
+        assert_eq!(strip_escapes(&colored.to_string()), e.to_string());
+        assert_eq!(strip_escapes(&format!("{:#}", colored)), format!("{:#}", e));
+        assert!(colored.to_string().contains(
+            "\x1b[1m[0]\x1b[0m at \x1b[2msrc/foo.rs:10\x1b[0m -- \x1b[97mhello -- world\x1b[0m"
+        ));
+    }
+}
@@ -33,8 +33,8 @@
 //!     assert_eq!(format!("\nError: {}", error).replace('\\', "/"), r#"
 //! Error: Critical (cause; something wrong)
 //! HISTORY:
-//!   [0] at src/error.rs:27
-//!   [1] at src/error.rs:28 -- I passed here
+//!   [0] at src/error.rs:27:17
+//!   [1] at src/error.rs:28:17 -- I passed here
 //! "#);
 //!
 //!     // Tries to execute I/O operation
@@ -49,11 +49,12 @@
 //! }
 //! ```
 //!
-//! # `TrackableError` drive macro
+//! # `TrackableError` derive macro
 //!
 //! If it is specified (i.e., `#[derive(TrackableError)]`),
 //! the following traits will be automatically implemented in the target error type:
 //! - `Trackable`
+//! - `ContextualError`
 //! - `Error`
 //! - `Display`
 //! - `Deref<Target = TrackableError<$error_kind>>`
@@ -61,17 +62,119 @@
 //! - `From<TrackableError<$error_kind>>`
 //! - `From<$target_error_type> for TrackableError<$error_kind>`
 //!
-//! The default value of `$error_kind` is `ErrorKind`.
-//! It can be customized by using `#[trackable(error_type = "$error_kind")]` attribute.
+//! The target error type must be a struct that has a field of `TrackableError<$error_kind>` type.
+//! If it has other fields, they must implement `Default` trait
+//! (they are initialized by `Default::default()` in the generated `From` implementations).
+//! Generic parameters are supported.
 //!
-//! The target error type must be a newtype (i.e., a tuple struct that has a single element) of `TrackableError`.
+//! `$error_kind` is taken from the type of the field.
+//! It can also be specified explicitly by using `#[trackable(error_kind = "$error_kind")]` attribute
+//! (e.g., if the field type is an alias of `TrackableError<_>`).
+//!
+//! Conversions from other error types can be generated by
+//! `#[trackable(from($error_type => $kind, ..))]` attribute.
+//! The resulting error has the kind `$kind` and the original error as the cause.
+//! If `=> $kind` is omitted, `<$error_kind as Default>::default()` is used as the kind.
+//!
+//! ```
+//! #[macro_use]
+//! extern crate trackable;
+//! use std::fmt::Debug;
+//! use trackable::error::{ErrorKind, TrackableError};
+//!
+//! #[derive(Debug, Default, Clone, PartialEq, Eq)]
+//! enum MyErrorKind<T> {
+//!     #[default]
+//!     Other,
+//!     Parse,
+//!     Invalid(T),
+//! }
+//! impl<T: Debug> ErrorKind for MyErrorKind<T> {}
+//!
+//! #[derive(Debug, TrackableError)]
+//! #[trackable(from(std::io::Error, std::num::ParseIntError => MyErrorKind::Parse))]
+//! struct MyError<T: Debug> {
+//!     inner: TrackableError<MyErrorKind<T>>,
+//!     retries: usize,
+//! }
+//!
+//! fn parse(s: &str) -> Result<u8, MyError<u8>> {
+//!     let n = track!(s.parse::<u8>().map_err(MyError::from))?;
+//!     Ok(n)
+//! }
+//!
+//! fn main() {
+//!     let e = parse("foo").err().unwrap();
+//!     assert_eq!(*e.kind(), MyErrorKind::Parse);
+//!     assert_eq!(e.retries, 0);
+//!     assert_eq!(e.to_string().lines().next(), Some("Parse (cause; invalid digit found in string)"));
+//!
+//!     let e: MyError<u8> = MyErrorKind::Invalid(3).into();
+//!     assert_eq!(*e.kind(), MyErrorKind::Invalid(3));
+//! }
+//! ```
+//!
+//! # `ErrorKind` derive macro
+//!
+//! If it is specified for an enum (i.e., `#[derive(ErrorKind)]`),
+//! the following items will be automatically implemented in the target enum:
+//! - `ErrorKind` trait
+//! - `Display` trait
+//! - A constructor method for each variant
+//!
+//! The description and the displayed form of each variant are the name of the variant by default.
+//! They can be overridden by using `#[trackable(display = "$description")]` attribute.
+//!
+//! The name of a constructor is the snake case form of the variant name
+//! (e.g., `NotFound` => `not_found()`),
+//! and the fields of the variant (if any) are the arguments of the constructor.
+//!
+//! ```
+//! #[macro_use]
+//! extern crate trackable;
+//! use trackable::error::ErrorKindExt;
+//!
+//! #[derive(Debug, Clone, PartialEq, Eq, ErrorKind)]
+//! enum MyErrorKind {
+//!     #[trackable(display = "not found")]
+//!     NotFound,
+//!     InvalidInput,
+//!     Timeout { millis: u64 },
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(MyErrorKind::not_found(), MyErrorKind::NotFound);
+//!     assert_eq!(MyErrorKind::NotFound.to_string(), "not found");
+//!     assert_eq!(MyErrorKind::invalid_input().to_string(), "InvalidInput");
+//!     assert_eq!(MyErrorKind::timeout(100), MyErrorKind::Timeout { millis: 100 });
+//!
+//!     let e = MyErrorKind::not_found().cause("no such user");
+//!     assert_eq!(e.to_string().lines().next(), Some("not found (cause; no such user)"));
+//! }
+//! ```
+//!
+//! The derive macros are provided by the `trackable_derive` crate and re-exported from this crate,
+//! so you don't need to depend on `trackable_derive` directly.
+use std::any::{Any, TypeId};
+#[cfg(feature = "backtrace")]
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
 use std::error::Error;
 use std::fmt;
+use std::hash::{BuildHasher, Hasher};
 use std::io;
-use std::sync::Arc;
+use std::iter::FromIterator;
+use std::num::NonZeroU64;
+use std::ops::Deref;
+use std::panic;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
 
 use super::{Location, Trackable};
 
+pub use trackable_derive::ErrorKind;
+
 /// Boxed `Error` object.
 pub type BoxError = Box<dyn Error + Send + Sync>;
 
@@ -83,7 +186,7 @@ pub type History = ::History<Location>;
 
 /// Built-in `ErrorKind` implementation which represents opaque errors.
 #[derive(Debug, Default, Clone, Copy, PartialOrd, Ord, PartialEq, Eq, Hash)]
-#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Failed;
 impl ErrorKind for Failed {
     fn description(&self) -> &str {
@@ -92,12 +195,35 @@ impl ErrorKind for Failed {
 }
 
 /// `TrackableError` type specialized for `Failed`.
+///
+/// If the `std-errors` feature is enabled, `From` implementations for common errors
+/// in the standard library (e.g., `std::io::Error` and `std::num::ParseIntError`) are provided.
+/// The original error is kept as the cause of the resulting `Failure`.
 #[derive(Debug, Clone, TrackableError)]
 #[trackable(error_kind = "Failed")]
-#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Failure(TrackableError<Failed>);
 impl Failure {
     /// Makes a new `Failure` instance which was caused by the `error`.
+    ///
+    /// The boxed `error` is kept as is (i.e., it is not stringified),
+    /// so it can be retrieved via `Error::source` or `concrete_cause` method.
+    /// The `Display` representation of `error` is rendered as the cause of the resulting `Failure`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use std::io;
+    /// use trackable::error::Failure;
+    ///
+    /// let e = Failure::from_error(io::Error::new(io::ErrorKind::NotFound, "no such file"));
+    /// assert_eq!(e.to_string(), "Failed (cause; no such file)\nHISTORY:\n");
+    ///
+    /// let cause = e.source().and_then(|e| e.downcast_ref::<io::Error>()).unwrap();
+    /// assert_eq!(cause.kind(), io::ErrorKind::NotFound);
+    /// assert!(e.concrete_cause::<io::Error>().is_some());
+    /// ```
     pub fn from_error<E>(error: E) -> Self
     where
         E: Into<BoxError>,
@@ -106,6 +232,77 @@ impl Failure {
     }
 }
 
+#[cfg(feature = "std-errors")]
+macro_rules! impl_from_std_errors_for_failure {
+    ($($error:ty),*) => {
+        $(
+            impl From<$error> for Failure {
+                fn from(f: $error) -> Self {
+                    IntoTrackableError::<Failed>::into_trackable_error(f).into()
+                }
+            }
+        )*
+    };
+}
+#[cfg(feature = "std-errors")]
+impl_from_std_errors_for_failure!(
+    std::char::ParseCharError,
+    std::env::VarError,
+    std::ffi::NulError,
+    std::fmt::Error,
+    io::Error,
+    std::net::AddrParseError,
+    std::num::ParseFloatError,
+    std::num::ParseIntError,
+    std::num::TryFromIntError,
+    std::str::ParseBoolError,
+    std::str::Utf8Error,
+    std::string::FromUtf8Error,
+    std::time::SystemTimeError
+);
+
+/// This trait allows to customize how foreign errors are converted into `TrackableError<K>`.
+///
+/// A blanket implementation for `K = Failed` is provided,
+/// which is used by [track_any_err!](../macro.track_any_err.html) macro.
+/// Library authors can implement this trait for each foreign error type
+/// to decide the kind of the resulting error in one place.
+///
+/// # Examples
+///
+/// ```
+/// use std::num::ParseIntError;
+/// use trackable::error::{ErrorKind, ErrorKindExt, IntoTrackableError, TrackableError};
+///
+/// #[derive(Debug, PartialEq, Eq)]
+/// enum MyErrorKind {
+///     InvalidInput,
+/// }
+/// impl ErrorKind for MyErrorKind {}
+///
+/// impl IntoTrackableError<MyErrorKind> for ParseIntError {
+///     fn into_trackable_error(self) -> TrackableError<MyErrorKind> {
+///         MyErrorKind::InvalidInput.cause(self)
+///     }
+/// }
+///
+/// let e = "foo".parse::<u8>().map_err(IntoTrackableError::into_trackable_error);
+/// let e: TrackableError<MyErrorKind> = e.err().unwrap();
+/// assert_eq!(*e.kind(), MyErrorKind::InvalidInput);
+/// ```
+pub trait IntoTrackableError<K> {
+    /// Converts this into a `TrackableError` of `K` kind.
+    fn into_trackable_error(self) -> TrackableError<K>;
+}
+impl<E> IntoTrackableError<Failed> for E
+where
+    E: Into<BoxError>,
+{
+    fn into_trackable_error(self) -> TrackableError<Failed> {
+        Failed.cause(self)
+    }
+}
+
 /// A variant of `std::io::Error` that implements `Trackable` trait.
 #[derive(Debug, Clone, TrackableError)]
 #[trackable(error_kind = "io::ErrorKind")]
@@ -131,6 +328,75 @@ impl ErrorKind for io::ErrorKind {
     }
 }
 
+/// Built-in `ErrorKind` implementation which represents commonly used classes of errors.
+///
+/// This is finer than `Failed`, and useful for routing errors
+/// (e.g., mapping those to HTTP or gRPC status codes).
+#[derive(Debug, Clone, Copy, PartialOrd, Ord, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CommonErrorKind {
+    /// The input (e.g., an argument or a request) is invalid.
+    InvalidInput,
+
+    /// The requested entity was not found.
+    NotFound,
+
+    /// The operation is not permitted.
+    PermissionDenied,
+
+    /// The operation timed out.
+    Timeout,
+
+    /// The service is temporarily unavailable.
+    Unavailable,
+
+    /// An internal error occurred.
+    Internal,
+
+    /// The operation was cancelled.
+    Cancelled,
+}
+impl ErrorKind for CommonErrorKind {
+    fn description(&self) -> &str {
+        match *self {
+            CommonErrorKind::InvalidInput => "Invalid input",
+            CommonErrorKind::NotFound => "Not found",
+            CommonErrorKind::PermissionDenied => "Permission denied",
+            CommonErrorKind::Timeout => "Timeout",
+            CommonErrorKind::Unavailable => "Unavailable",
+            CommonErrorKind::Internal => "Internal error",
+            CommonErrorKind::Cancelled => "Cancelled",
+        }
+    }
+
+    fn is_retriable(&self) -> bool {
+        matches!(
+            *self,
+            CommonErrorKind::Timeout | CommonErrorKind::Unavailable
+        )
+    }
+}
+
+/// `TrackableError` type specialized for `CommonErrorKind`.
+///
+/// # Examples
+///
+/// ```
+/// use trackable::error::{CommonError, CommonErrorKind, ErrorKindExt};
+///
+/// let e: CommonError = CommonErrorKind::NotFound.cause("no such user").into();
+/// assert_eq!(*e.kind(), CommonErrorKind::NotFound);
+/// ```
+#[derive(Debug, Clone, TrackableError)]
+#[trackable(error_kind = "CommonErrorKind")]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CommonError(TrackableError<CommonErrorKind>);
+impl From<Failure> for CommonError {
+    fn from(f: Failure) -> Self {
+        CommonErrorKind::Internal.takes_over(f).into()
+    }
+}
+
 /// An `Error` type for unit tests.
 pub type TestError = TopLevelError;
 
@@ -146,12 +412,12 @@ impl<E: Error + Trackable + 'static> From<E> for TopLevelError {
 }
 impl fmt::Debug for TopLevelError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.0)
+        write!(f, "{:#}", self.0)
     }
 }
 impl fmt::Display for TopLevelError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.0)
+        write!(f, "{:#}", self.0)
     }
 }
 impl Error for TopLevelError {
@@ -177,6 +443,48 @@ pub trait ErrorKind: fmt::Debug {
     fn display(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{:?}", self)
     }
+
+    /// A stable code that identifies the error kind.
+    ///
+    /// If this returns `Some(..)`, the code is included in the displayed and serialized forms
+    /// of `TrackableError` instances.
+    ///
+    /// The default implementation always returns `None`.
+    fn code(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// The severity of the error kind.
+    ///
+    /// This can be used, for example, to decide the log level of an error.
+    ///
+    /// The default implementation always returns `Severity::Error`.
+    fn severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    /// Returns `true` if an operation that failed with an error of this kind may succeed by retrying.
+    ///
+    /// This is used by [`retry::retry_with`](../retry/fn.retry_with.html) function.
+    ///
+    /// The default implementation always returns `false`.
+    fn is_retriable(&self) -> bool {
+        false
+    }
+}
+
+/// The severity of an error.
+///
+/// The variants are ordered from the least severe to the most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[allow(missing_docs)]
+pub enum Severity {
+    Debug,
+    Info,
+    Warn,
+    Error,
+    Fatal,
 }
 impl ErrorKind for String {
     fn description(&self) -> &str {
@@ -184,6 +492,40 @@ impl ErrorKind for String {
     }
 }
 
+/// An `ErrorKind` which consists of the textual representation of another error kind.
+///
+/// This is a serializable fallback for the error kinds that do not implement `Serialize`
+/// (see `TrackableError::into_kind_name`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct KindName {
+    name: String,
+    severity: Severity,
+}
+impl KindName {
+    /// Makes a new `KindName` instance which has `Severity::Error`.
+    pub fn new<T: Into<String>>(name: T) -> Self {
+        KindName {
+            name: name.into(),
+            severity: Severity::Error,
+        }
+    }
+
+    /// Returns the name of the original error kind.
+    pub fn as_str(&self) -> &str {
+        &self.name
+    }
+}
+impl ErrorKind for KindName {
+    fn display(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+
+    fn severity(&self) -> Severity {
+        self.severity
+    }
+}
+
 /// An extention of `ErrorKind` trait.
 ///
 /// This provides convenient functions to create a `TrackableError` instance of this kind.
@@ -225,7 +567,9 @@ pub trait ErrorKindExt: ErrorKind + Sized {
 
     /// Takes over from other `TrackableError` instance.
     ///
-    /// The history of `from` will be preserved.
+    /// The history of `from` will be preserved,
+    /// and an event that records the change of the kind is appended to it.
+    /// The kind of `from` can be retrieved by `TrackableError::original_kind` method.
     ///
     /// # Examples
     ///
@@ -234,6 +578,7 @@ pub trait ErrorKindExt: ErrorKind + Sized {
     /// # extern crate trackable;
     /// #
     /// use trackable::error::{ErrorKind, ErrorKindExt};
+    /// use trackable::Trackable;
     ///
     /// #[derive(Debug)]
     /// struct Kind0;
@@ -253,26 +598,101 @@ pub trait ErrorKindExt: ErrorKind + Sized {
     ///   assert_eq!(format!("\nERROR: {}", e).replace('\\', "/"), r#"
     /// ERROR: Kind1
     /// HISTORY:
-    ///   [0] at src/error.rs:17
-    ///   [1] at src/error.rs:20
+    ///   [0] at src/error.rs:17:11
+    ///   [2] at src/error.rs:20:11
+    ///   ... and 1 more frame
     /// "#);
+    ///   let events = e.history().unwrap().events();
+    ///   assert_eq!(events[1].message(), "kind changed: Kind0 -> Kind1");
+    ///   assert_eq!(format!("{:?}", e.original_kind().unwrap()), "Kind0");
     /// }
     /// ```
+    #[track_caller]
     fn takes_over<F, K>(self, from: F) -> TrackableError<Self>
     where
         F: Into<TrackableError<K>>,
         K: ErrorKind + Send + Sync + 'static,
     {
         let from = from.into();
-        TrackableError {
-            kind: self,
-            cause: from.cause,
-            history: from.history,
-        }
+        let severity = from.severity();
+        let caller = panic::Location::caller();
+        let message = format!(
+            "kind changed: {} -> {}",
+            DisplayKind(&from.kind),
+            DisplayKind(&self)
+        );
+        let mut inner = from.inner;
+        Arc::make_mut(&mut inner.history).add(
+            Location::new("", caller.file(), caller.line(), message).with_column(caller.column()),
+        );
+        inner.severity = Some(severity);
+        inner.extras = Some(Box::new(Extras {
+            original_kind: Some(Arc::new(from.kind)),
+            ..inner.extras.take().map(|x| *x).unwrap_or_default()
+        }));
+        TrackableError { kind: self, inner }
+    }
+
+    /// Makes a `TrackableError` instance caused by the trackable `error`.
+    ///
+    /// Unlike `cause` method, the history events of `error` are moved into
+    /// the history of the resulting error as structured events,
+    /// instead of being flattened into the textual representation of the cause.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use]
+    /// # extern crate trackable;
+    /// #
+    /// use trackable::error::{ErrorKind, ErrorKindExt};
+    ///
+    /// #[derive(Debug)]
+    /// struct Kind0;
+    /// impl ErrorKind for Kind0 {}
+    ///
+    /// #[derive(Debug)]
+    /// struct Kind1;
+    /// impl ErrorKind for Kind1 {}
+    ///
+    /// fn main() {
+    ///   let e = track!(Kind0.cause("oops"));
+    ///   let e = track!(Kind1.absorb(e));
+    ///
+    ///   assert_eq!(format!("\nERROR: {}", e).replace('\\', "/"), r#"
+    /// ERROR: Kind1 (cause; Kind0 (cause; oops))
+    /// HISTORY:
+    ///   [0] at src/error.rs:15:11
+    ///   [1] at src/error.rs:16:11
+    /// "#);
+    /// }
+    /// ```
+    fn absorb<F, K>(self, from: F) -> TrackableError<Self>
+    where
+        F: Into<TrackableError<K>>,
+        K: ErrorKind + Send + Sync + 'static,
+    {
+        let mut from = from.into();
+        let history = std::mem::take(Arc::make_mut(&mut from.inner.history));
+        let mut e = TrackableError::new(self, from);
+        e.inner.history = Arc::new(history);
+        e.extras_mut().absorbed = Some(AbsorbedCause(absorbed_parts::<K>));
+        e
     }
 }
 impl<T: ErrorKind> ErrorKindExt for T {}
 
+/// An error which can have context messages.
+///
+/// This is implemented for `TrackableError` and the types which derive `TrackableError`,
+/// and is used by [`ResultExt::with_context`](../trait.ResultExt.html#tymethod.with_context).
+pub trait ContextualError {
+    /// Adds a context message to this error.
+    ///
+    /// See `TrackableError::context` for more details.
+    fn add_context<C: fmt::Display>(&mut self, context: C);
+}
+
 /// Trackable error.
 ///
 /// # Examples
@@ -310,8 +730,8 @@ impl<T: ErrorKind> ErrorKindExt for T {}
 ///     assert_eq!(format!("\nError: {}", error).replace('\\', "/"), r#"
 /// Error: Critical (cause; something wrong)
 /// HISTORY:
-///   [0] at src/error.rs:27
-///   [1] at src/error.rs:28 -- I passed here
+///   [0] at src/error.rs:27:17
+///   [1] at src/error.rs:28:17 -- I passed here
 /// "#);
 ///
 ///     // Tries to execute I/O operation
@@ -326,8 +746,58 @@ impl<T: ErrorKind> ErrorKindExt for T {}
 /// }
 /// ```
 ///
+/// # Formatting
+///
+/// The verbosity of the rendered error can be chosen at each format call site:
+///
+/// - `{}` renders a concise report, which consists of the kind, the cause and
+///   the first and the last events of the history.
+/// - `{:#}` renders an extended report, which consists of the kind, the cause,
+///   the source chain of the cause, the ID, the contexts, the whole history
+///   (including the function names and the source snippets of the events),
+///   the linked errors and the backtrace (if captured).
+/// - The precision (e.g., `{:.2}` or `{:#.2}`) specifies the number of the first and the last events rendered.
+///
+/// The source chain (i.e., `cause.source()`, its source and so on) is rendered as `Caused by:` entries
+/// below the kind line, so that nested errors of third-party libraries are visible in one report.
+///
+/// ```
+/// use trackable::{Location, Trackable};
+/// use trackable::error::{ErrorKindExt, Failed};
+///
+/// let mut e = Failed.absorb(Failed.cause("disk full")).context("writing foo");
+/// e.track(|| Location::new("foo", "src/foo.rs", 10, "").with_function("foo::write"));
+/// for line in 11..14 {
+///     e.track(|| Location::new("foo", "src/foo.rs", line, ""));
+/// }
+///
+/// assert_eq!(format!("\n{}", e), r#"
+/// Failed (cause; Failed (cause; disk full))
+/// HISTORY:
+///   [0] at src/foo.rs:10
+///   [3] at src/foo.rs:13
+///   ... and 2 more frames
+/// "#);
+/// // The backtrace follows the history if the `backtrace` feature is enabled
+/// assert!(format!("\n{:#}", e).starts_with(r#"
+/// Failed (cause; Failed (cause; disk full))
+/// Caused by:
+///   0: disk full
+/// CONTEXT:
+///   - writing foo
+/// HISTORY:
+///   [0] at src/foo.rs:10 in foo::write
+///   [1] at src/foo.rs:11
+///   [2] at src/foo.rs:12
+///   [3] at src/foo.rs:13
+/// "#));
+/// ```
+///
 /// `TrackableError` is cloneable if `K` is so.
 ///
+/// The history is shared between the clones (i.e., cloning does not copy the history)
+/// until one of them is tracked.
+///
 /// ```no_run
 /// #[macro_use]
 /// extern crate trackable;
@@ -345,23 +815,23 @@ impl<T: ErrorKind> ErrorKindExt for T {}
 ///     assert_eq!(format!("\n{}", original).replace('\\', "/"), r#"
 /// Failed
 /// HISTORY:
-///   [0] at src/error.rs:11 -- Hello `original`!
+///   [0] at src/error.rs:11:20 -- Hello `original`!
 /// "#);
 ///
 ///     assert_eq!(format!("\n{}", forked).replace('\\', "/"), r#"
 /// Failed
 /// HISTORY:
-///   [0] at src/error.rs:11 -- Hello `original`!
-///   [1] at src/error.rs:13 -- Hello `forked`!
+///   [0] at src/error.rs:11:20 -- Hello `original`!
+///   [1] at src/error.rs:13:18 -- Hello `forked`!
 /// "#);
 /// }
 /// ```
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
 pub struct TrackableError<K> {
     kind: K,
-    cause: Option<Cause>,
-    history: History,
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    inner: Box<Inner>,
 }
 impl<K: ErrorKind> TrackableError<K> {
     /// Makes a new `TrackableError` instance.
@@ -369,11 +839,9 @@ impl<K: ErrorKind> TrackableError<K> {
     where
         E: Into<BoxError>,
     {
-        TrackableError {
-            kind,
-            cause: Some(Cause(Arc::new(cause.into()))),
-            history: History::new(),
-        }
+        let mut error = Self::from_kind(kind);
+        error.inner.cause = Some(Cause(Arc::new(cause.into())));
+        error
     }
 
     /// Makes a new `TrackableError` instance from `kind`.
@@ -382,8 +850,45 @@ impl<K: ErrorKind> TrackableError<K> {
     fn from_kind(kind: K) -> Self {
         TrackableError {
             kind,
-            cause: None,
-            history: History::new(),
+            inner: Box::new(Inner {
+                severity: None,
+                cause: None,
+                contexts: Contexts::default(),
+                extras: None,
+                backtrace: CapturedBacktrace::capture(),
+                instance_id: new_instance_id(),
+                history: Arc::new(History::new()),
+            }),
+        }
+    }
+
+    /// Makes a new `TrackableErrorBuilder` instance for building an error of `kind`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::{Location, Trackable};
+    /// use trackable::error::{Failed, TrackableError};
+    ///
+    /// let e = TrackableError::builder(Failed)
+    ///     .cause("something wrong")
+    ///     .message("while decoding a request")
+    ///     .location(Location::new("foo", "src/foo.rs", 10, ""))
+    ///     .history_capacity(8)
+    ///     .build();
+    /// assert_eq!(e.to_string(), "\
+    /// Failed (cause; something wrong)
+    /// HISTORY:
+    ///   [0] at src/foo.rs:10
+    /// ");
+    /// assert_eq!(e.contexts(), ["while decoding a request"]);
+    /// ```
+    pub fn builder(kind: K) -> TrackableErrorBuilder<K> {
+        TrackableErrorBuilder {
+            error: Self::from_kind(kind),
+            locations: Vec::new(),
+            history_capacity: 0,
+            history_limit: None,
         }
     }
 
@@ -393,71 +898,1804 @@ impl<K: ErrorKind> TrackableError<K> {
         &self.kind
     }
 
-    /// Tries to return the cause of this error as a value of `T` type.
+    /// Returns the code of the kind of this error.
     ///
-    /// If neither this error has a cause nor it is an `T` value,
-    /// this method will return `None`.
-    #[inline]
-    pub fn concrete_cause<T>(&self) -> Option<&T>
-    where
-        T: Error + 'static,
-    {
-        self.cause.as_ref().and_then(|c| c.0.downcast_ref())
-    }
-}
-impl<K: ErrorKind> From<K> for TrackableError<K> {
-    #[inline]
-    fn from(kind: K) -> Self {
-        Self::from_kind(kind)
-    }
-}
-impl<K: ErrorKind + Default> Default for TrackableError<K> {
+    /// This is equivalent to `self.kind().code()`.
     #[inline]
-    fn default() -> Self {
-        Self::from_kind(K::default())
+    pub fn code(&self) -> Option<&'static str> {
+        self.kind.code()
     }
-}
-impl<K: ErrorKind> fmt::Display for TrackableError<K> {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        self.kind.display(f)?;
-        if let Some(ref e) = self.cause {
-            write!(f, " (cause; {})", e.0)?;
-        }
-        write!(f, "\n{}", self.history)?;
-        Ok(())
+
+    /// Returns the severity of this error.
+    ///
+    /// This is the severity of the kind of this error,
+    /// or the severity of the original error taken over by `ErrorKindExt::takes_over`
+    /// if it is more severe.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::error::{ErrorKind, ErrorKindExt, Failed, Severity};
+    ///
+    /// #[derive(Debug)]
+    /// struct Fatal;
+    /// impl ErrorKind for Fatal {
+    ///     fn severity(&self) -> Severity {
+    ///         Severity::Fatal
+    ///     }
+    /// }
+    ///
+    /// let e = Fatal.error();
+    /// assert_eq!(e.severity(), Severity::Fatal);
+    ///
+    /// let e = Failed.takes_over(e);
+    /// assert_eq!(e.severity(), Severity::Fatal);
+    /// ```
+    pub fn severity(&self) -> Severity {
+        self.parts().severity()
     }
-}
-impl<K: ErrorKind> Error for TrackableError<K> {
-    fn description(&self) -> &str {
-        self.kind.description()
+
+    /// Returns the backtrace captured when this error was created.
+    ///
+    /// The backtrace is captured only if it is enabled by the `RUST_BACKTRACE` or
+    /// `RUST_LIB_BACKTRACE` environment variables.
+    /// If captured, the backtrace is displayed after the history of this error
+    /// when this error is formatted with the alternate flag (i.e., `{:#}`).
+    ///
+    /// Only the unresolved frames are recorded when this error is created.
+    /// Symbols are resolved lazily when the backtrace is displayed for the first time,
+    /// so capturing it does not make error construction much slower.
+    ///
+    /// This method is available only if the `backtrace` feature is enabled.
+    #[cfg(feature = "backtrace")]
+    pub fn backtrace(&self) -> Option<&Backtrace> {
+        self.parts().backtrace()
     }
-    fn cause(&self) -> Option<&dyn Error> {
-        self.cause.as_ref().map::<&dyn Error, _>(|e| &**e.0)
+
+    /// Returns the unique ID of this error instance.
+    ///
+    /// IDs are assigned to errors only if `set_instance_id_enabled(true)` has been called
+    /// before the errors are created.
+    /// The ID is preserved when the error is cloned or its kind is changed,
+    /// so it can be used to correlate log entries about the same error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::error::{self, Failed, ErrorKindExt};
+    ///
+    /// assert_eq!(Failed.error().instance_id(), None);
+    ///
+    /// error::set_instance_id_enabled(true);
+    /// let e = Failed.error();
+    /// let id = e.instance_id().unwrap();
+    /// assert!(format!("{:#}", e).starts_with(&format!("Failed\nID: {:016x}\nHISTORY:\n", id)));
+    /// assert_ne!(Failed.error().instance_id(), Some(id));
+    /// # error::set_instance_id_enabled(false);
+    /// ```
+    #[inline]
+    pub fn instance_id(&self) -> Option<u64> {
+        self.inner.instance_id.map(NonZeroU64::get)
     }
-}
-impl<K> Trackable for TrackableError<K> {
-    type Event = Location;
 
+    /// Returns the kind of the error that this error took over from.
+    ///
+    /// See `ErrorKindExt::takes_over` for more details.
     #[inline]
-    fn history(&self) -> Option<&History> {
-        Some(&self.history)
+    pub fn original_kind(&self) -> Option<&(dyn ErrorKind + Send + Sync)> {
+        self.inner
+            .extras
+            .as_ref()
+            .and_then(|x| x.original_kind.as_deref())
     }
 
+    /// Converts this error into its kind.
     #[inline]
-    fn history_mut(&mut self) -> Option<&mut History> {
-        Some(&mut self.history)
+    pub fn into_kind(self) -> K {
+        self.kind
     }
-}
+
+    /// Returns the mutable reference to the kind of this error.
+    #[inline]
+    pub fn kind_mut(&mut self) -> &mut K {
+        &mut self.kind
+    }
+
+    /// Converts the kind of this error by using `f`.
+    ///
+    /// The cause and the history of this error are carried over to the resulting error.
+    pub fn map_kind<K2, F>(self, f: F) -> TrackableError<K2>
+    where
+        K2: ErrorKind,
+        F: FnOnce(K) -> K2,
+    {
+        TrackableError {
+            kind: f(self.kind),
+            inner: self.inner,
+        }
+    }
+
+    /// Converts the kind of this error into its textual representation.
+    ///
+    /// The severity of the original kind is carried over to the resulting kind.
+    /// This is useful for serializing errors whose kinds do not implement `Serialize`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::error::{ErrorKindExt, KindName};
+    ///
+    /// let e = std::io::ErrorKind::NotFound.cause("no such file").into_kind_name();
+    /// assert_eq!(*e.kind(), KindName::new("NotFound"));
+    /// assert_eq!(e.to_string(), "NotFound (cause; no such file)\nHISTORY:\n");
+    /// ```
+    pub fn into_kind_name(self) -> TrackableError<KindName> {
+        self.map_kind(|kind| KindName {
+            name: DisplayKind(&kind).to_string(),
+            severity: kind.severity(),
+        })
+    }
+
+    /// Adds a context message to this error.
+    ///
+    /// The context messages are displayed before the tracking history in the extended report (i.e., `{:#}`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::error::{Failed, ErrorKindExt};
+    ///
+    /// let error = Failed.cause("connection refused").context("while syncing user profile");
+    /// assert!(format!("{:#}", error).starts_with("\
+    /// Failed (cause; connection refused)
+    /// CONTEXT:
+    ///   - while syncing user profile
+    /// HISTORY:
+    /// "));
+    /// ```
+    pub fn context<C>(mut self, context: C) -> Self
+    where
+        C: fmt::Display,
+    {
+        self.add_context(context);
+        self
+    }
+
+    /// Adds a context message, which is lazily evaluated by calling `f`, to this error.
+    ///
+    /// `f` is not called until the contexts of this error are accessed for the first time
+    /// (e.g., when this error is displayed),
+    /// so building the message costs nothing if this error is handled without being reported.
+    ///
+    /// If you want to add a context to the error in a `Result` (with a non-`'static` closure),
+    /// use [`ResultExt::with_context`](../trait.ResultExt.html#tymethod.with_context) instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::atomic::{AtomicBool, Ordering};
+    /// use std::sync::Arc;
+    /// use trackable::error::{Failed, ErrorKindExt};
+    ///
+    /// let called = Arc::new(AtomicBool::new(false));
+    /// let flag = Arc::clone(&called);
+    /// let error = Failed.cause("connection refused").with_context(move || {
+    ///     flag.store(true, Ordering::SeqCst);
+    ///     "while syncing user profile"
+    /// });
+    /// assert!(!called.load(Ordering::SeqCst));
+    ///
+    /// assert_eq!(error.contexts(), ["while syncing user profile"]);
+    /// assert!(called.load(Ordering::SeqCst));
+    /// ```
+    pub fn with_context<C, F>(mut self, f: F) -> Self
+    where
+        C: fmt::Display,
+        F: FnOnce() -> C + Send + 'static,
+    {
+        self.inner
+            .contexts
+            .push(Context::Lazy(Arc::new(LazyContext {
+                message: OnceLock::new(),
+                f: Mutex::new(Some(Box::new(move || f().to_string()))),
+            })));
+        self
+    }
+
+    /// Links `error` to this error.
+    ///
+    /// This is useful, for example, when a cleanup process fails while handling this error.
+    /// The linked errors are displayed after the history of this error in the extended report (i.e., `{:#}`),
+    /// and the first one can be retrieved via `Error::source` if this error has no cause.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use]
+    /// # extern crate trackable;
+    /// use trackable::error::{Failed, ErrorKindExt};
+    ///
+    /// # fn main() {
+    /// let primary = track!(Failed.cause("write failed"));
+    /// let secondary = track!(Failed.cause("cleanup failed"));
+    /// let e = primary.chain(secondary);
+    ///
+    /// assert_eq!(e.linked_errors().count(), 1);
+    /// assert!(format!("{:#}", e).contains("\
+    /// LINKED ERROR [0]:
+    ///   Failed (cause; cleanup failed)
+    ///   HISTORY:
+    /// "));
+    /// # }
+    /// ```
+    pub fn chain<E>(mut self, error: E) -> Self
+    where
+        E: Into<BoxError>,
+    {
+        self.extras_mut().linked.push(Cause(Arc::new(error.into())));
+        self
+    }
+
+    /// Returns an iterator over the errors linked by `chain` method.
+    pub fn linked_errors(&self) -> impl Iterator<Item = &(dyn Error + Send + Sync + 'static)> {
+        self.parts().linked_errors()
+    }
+
+    pub(crate) fn parts(&self) -> ErrorParts<'_> {
+        ErrorParts {
+            kind: &self.kind,
+            inner: &self.inner,
+        }
+    }
+
+    /// Renders this error without colors (i.e., by the global renderer or in the default format).
+    pub(crate) fn fmt_plain(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_styled(f, &::fmt::Style::PLAIN)
+    }
+
+    /// Renders this error in the default format decorated by `style`.
+    ///
+    /// If the global renderer is set, it is used instead and `style` is ignored.
+    pub(crate) fn fmt_styled(&self, f: &mut fmt::Formatter, style: &::fmt::Style) -> fmt::Result {
+        let parts = self.parts();
+        match global_renderer() {
+            Some(renderer) => renderer.render(&parts, f),
+            None => parts.fmt_styled(f, style),
+        }
+    }
+
+    fn extras_mut(&mut self) -> &mut Extras {
+        self.inner.extras.get_or_insert_with(Default::default)
+    }
+
+    /// Returns the context messages of this error in the order they were added.
+    #[inline]
+    pub fn contexts(&self) -> &[String] {
+        &self.inner.contexts
+    }
+
+    /// Attaches a typed value to this error.
+    ///
+    /// If a value of the same type has already been attached, it will be replaced.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::error::{Failed, ErrorKindExt};
+    ///
+    /// #[derive(Debug, PartialEq, Eq)]
+    /// struct RequestId(u64);
+    ///
+    /// let mut error = Failed.error();
+    /// error.insert_ext(RequestId(42));
+    /// assert_eq!(error.get_ext::<RequestId>(), Some(&RequestId(42)));
+    /// assert_eq!(error.get_ext::<String>(), None);
+    /// ```
+    pub fn insert_ext<T>(&mut self, value: T)
+    where
+        T: Any + Send + Sync,
+    {
+        self.extras_mut()
+            .extensions
+            .0
+            .insert(TypeId::of::<T>(), Arc::new(value));
+    }
+
+    /// Returns the reference to the attached value of `T` type.
+    pub fn get_ext<T>(&self) -> Option<&T>
+    where
+        T: Any + Send + Sync,
+    {
+        self.inner
+            .extras
+            .as_ref()
+            .and_then(|x| x.extensions.0.get(&TypeId::of::<T>()))
+            .and_then(|v| v.downcast_ref())
+    }
+
+    /// Removes the attached value of `T` type from this error.
+    ///
+    /// Returns `true` if such a value was attached.
+    pub fn remove_ext<T>(&mut self) -> bool
+    where
+        T: Any + Send + Sync,
+    {
+        match self.inner.extras {
+            Some(ref mut x) => x.extensions.0.remove(&TypeId::of::<T>()).is_some(),
+            None => false,
+        }
+    }
+
+    /// Returns a view of the kind and the cause of this error.
+    ///
+    /// The view can be compared and hashed ignoring the history of this error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use]
+    /// # extern crate trackable;
+    /// use trackable::error::{Failed, ErrorKindExt};
+    ///
+    /// # fn main() {
+    /// let e0 = track!(Failed.cause("oops"));
+    /// let e1 = track!(track!(Failed.cause("oops")));
+    /// assert_eq!(e0.kind_and_cause(), e1.kind_and_cause());
+    /// assert_ne!(e0.kind_and_cause(), Failed.cause("other").kind_and_cause());
+    /// # }
+    /// ```
+    pub fn kind_and_cause(&self) -> KindAndCause<'_, K> {
+        KindAndCause {
+            kind: &self.kind,
+            cause: self.inner.cause.as_ref().map(|c| c.0.to_string()),
+        }
+    }
+
+    /// Returns a fingerprint of this error which can be used for grouping identical failures.
+    ///
+    /// The fingerprint is computed from the kind, the cause (with digits masked),
+    /// and the first location in the history of this error.
+    /// The algorithm (64-bit FNV-1a) is fixed, so the fingerprint is stable across runs and builds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use]
+    /// # extern crate trackable;
+    /// use trackable::error::{Failed, ErrorKindExt};
+    ///
+    /// # fn main() {
+    /// let errors = (0..2)
+    ///     .map(|i| track!(Failed.cause(format!("no such user: {}", i))))
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(errors[0].fingerprint(), errors[1].fingerprint());
+    /// assert_eq!(errors[0].fingerprint_hex().len(), 16);
+    /// # }
+    /// ```
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = Fnv1a::new();
+        hasher.write(DisplayKind(&self.kind).to_string().as_bytes());
+        hasher.write(&[0]);
+        if let Some(ref cause) = self.inner.cause {
+            let cause = cause.0.to_string();
+            let mut prev_is_digit = false;
+            for c in cause.chars() {
+                let is_digit = c.is_ascii_digit();
+                if !is_digit {
+                    hasher.write(c.encode_utf8(&mut [0; 4]).as_bytes());
+                } else if !prev_is_digit {
+                    hasher.write(b"#");
+                }
+                prev_is_digit = is_digit;
+            }
+        }
+        hasher.write(&[0]);
+        if let Some(location) = self.inner.history.events().first() {
+            hasher.write(location.file().as_bytes());
+            hasher.write(&location.line().to_le_bytes());
+        }
+        hasher.finish()
+    }
+
+    /// Returns the hexadecimal string form of `self.fingerprint()`.
+    pub fn fingerprint_hex(&self) -> String {
+        format!("{:016x}", self.fingerprint())
+    }
+
+    /// Returns the cause of this error.
+    #[inline]
+    pub fn cause_error(&self) -> Option<&(dyn Error + Send + Sync + 'static)> {
+        self.inner.cause.as_ref().map(|c| &**c.0)
+    }
+
+    /// Returns the textual representation of the cause of this error.
+    pub fn cause_message(&self) -> Option<String> {
+        self.inner.cause.as_ref().map(|c| c.0.to_string())
+    }
+
+    /// Returns the textual representation of the history of this error.
+    ///
+    /// Unlike the `Display` implementation of this error, the result does not contain the kind and the cause.
+    pub fn history_string(&self) -> String {
+        self.inner.history.to_string()
+    }
+
+    /// Returns a `Display` wrapper that renders this error on a single line.
+    ///
+    /// This is useful for log backends that cannot handle multi-line messages.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::Location;
+    /// use trackable::error::{Failed, TrackableError};
+    ///
+    /// let e = TrackableError::builder(Failed)
+    ///     .cause("something wrong")
+    ///     .message("while decoding a request")
+    ///     .location(Location::new("foo", "src/a.rs", 10, ""))
+    ///     .location(Location::new("foo", "src/b.rs", 42, "note"))
+    ///     .location(Location::new("foo", "src/c.rs", 7, ""))
+    ///     .build();
+    /// assert_eq!(
+    ///     e.display_compact().to_string(),
+    ///     "Failed (cause; something wrong) (context; while decoding a request) \
+    ///      [src/a.rs:10 -> src/b.rs:42 -- note -> src/c.rs:7]"
+    /// );
+    /// ```
+    pub fn display_compact(&self) -> DisplayCompact<'_, K> {
+        DisplayCompact(self)
+    }
+
+    /// Returns a `Display` wrapper that renders this error as a single-line JSON object.
+    ///
+    /// The object has the following shape (the `code` and `cause` fields are `null` if absent):
+    ///
+    /// ```json
+    /// {
+    ///   "kind": "Failed",
+    ///   "code": "E42",
+    ///   "severity": "Error",
+    ///   "cause": "something wrong",
+    ///   "contexts": ["while decoding a request"],
+    ///   "history": [
+    ///     {"file": "src/foo.rs", "line": 10, "column": 5, "module": "foo",
+    ///      "function": "foo::decode", "message": "", "fields": {"user_id": "42"}}
+    ///   ]
+    /// }
+    /// ```
+    ///
+    /// This does not require the `serde` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::Location;
+    /// use trackable::error::{Failed, TrackableError};
+    ///
+    /// let e = TrackableError::builder(Failed)
+    ///     .cause("something \"wrong\"")
+    ///     .message("while decoding a request")
+    ///     .location(Location::new("foo", "src/foo.rs", 10, ""))
+    ///     .build();
+    /// assert_eq!(
+    ///     e.json_report().to_string(),
+    ///     r#"{"kind":"Failed","code":null,"severity":"Error","cause":"something \"wrong\"","#.to_owned()
+    ///         + r#""contexts":["while decoding a request"],"#
+    ///         + r#""history":[{"file":"src/foo.rs","line":10,"column":0,"module":"foo","#
+    ///         + r#""function":"","message":"","fields":{}}]}"#
+    /// );
+    /// ```
+    pub fn json_report(&self) -> JsonReport<'_, K> {
+        JsonReport(self)
+    }
+
+    /// Returns the first tracked location of this error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use]
+    /// # extern crate trackable;
+    /// use trackable::error::{Failed, ErrorKindExt};
+    ///
+    /// # fn main() {
+    /// let (e, line) = (track!(Failed.cause("oops")), line!());
+    /// let e = track!(e, "bar");
+    ///
+    /// assert_eq!(e.origin().map(|l| l.line()), Some(line));
+    /// assert_eq!(e.cause_message(), Some("oops".to_owned()));
+    /// assert_eq!(e.history_string().lines().next(), Some("HISTORY:"));
+    /// # }
+    /// ```
+    #[inline]
+    pub fn origin(&self) -> Option<&Location> {
+        self.inner.history.events().first()
+    }
+
+    /// Tries to return the cause of this error as a value of `T` type.
+    ///
+    /// If neither this error has a cause nor it is an `T` value,
+    /// this method will return `None`.
+    #[inline]
+    pub fn concrete_cause<T>(&self) -> Option<&T>
+    where
+        T: Error + 'static,
+    {
+        self.inner.cause.as_ref().and_then(|c| c.0.downcast_ref())
+    }
+}
+impl<K: ErrorKind> From<K> for TrackableError<K> {
+    #[inline]
+    fn from(kind: K) -> Self {
+        Self::from_kind(kind)
+    }
+}
+impl<K: ErrorKind + Default> Default for TrackableError<K> {
+    #[inline]
+    fn default() -> Self {
+        Self::from_kind(K::default())
+    }
+}
+impl<K: ErrorKind> fmt::Display for TrackableError<K> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        #[cfg(feature = "color")]
+        {
+            if ::fmt::config().color() && ::color::enabled() {
+                return fmt::Display::fmt(&::color::colored(self).force(true), f);
+            }
+        }
+        self.fmt_plain(f)
+    }
+}
+/// A view of the parts of a `TrackableError` which is passed to an `ErrorRenderer`.
+#[derive(Debug)]
+pub struct ErrorParts<'a> {
+    kind: &'a dyn ErrorKind,
+    inner: &'a Inner,
+}
+impl<'a> ErrorParts<'a> {
+    /// Returns the kind of the error.
+    pub fn kind(&self) -> &'a dyn ErrorKind {
+        self.kind
+    }
+
+    /// Returns the severity of the error (see `TrackableError::severity`).
+    pub fn severity(&self) -> Severity {
+        let severity = self.kind.severity();
+        self.inner.severity.map_or(severity, |s| s.max(severity))
+    }
+
+    /// Returns the cause of the error (if any).
+    pub fn cause(&self) -> Option<&'a (dyn Error + Send + Sync + 'static)> {
+        self.inner.cause.as_ref().map(|c| &**c.0)
+    }
+
+    /// Returns a `Display` wrapper of the cause.
+    ///
+    /// A trackable cause absorbed by `ErrorKindExt::absorb` is rendered as its kind, code and cause,
+    /// since its history has been moved into this error.
+    pub(crate) fn cause_text(&self) -> Option<CauseText<'a>> {
+        let cause = self.cause()?;
+        let absorbed = self
+            .inner
+            .extras
+            .as_ref()
+            .and_then(|x| x.absorbed)
+            .and_then(|a| (a.0)(cause));
+        Some(CauseText { cause, absorbed })
+    }
+
+    /// Returns an iterator over the source chain of the cause (i.e., `cause().source()` and its sources).
+    pub fn sources(&self) -> impl Iterator<Item = &'a (dyn Error + 'static)> {
+        let first = self.cause().and_then(|c| c.source());
+        std::iter::successors(first, |&e| e.source())
+    }
+
+    /// Returns the unique ID of the error (if any).
+    pub fn instance_id(&self) -> Option<u64> {
+        self.inner.instance_id.map(NonZeroU64::get)
+    }
+
+    /// Returns the context messages of the error.
+    pub fn contexts(&self) -> &'a [String] {
+        &self.inner.contexts
+    }
+
+    /// Returns the tracking history of the error.
+    pub fn history(&self) -> &'a History {
+        &self.inner.history
+    }
+
+    /// Returns an iterator over the errors linked to the error.
+    pub fn linked_errors(&self) -> impl Iterator<Item = &'a (dyn Error + Send + Sync + 'static)> {
+        self.inner
+            .extras
+            .iter()
+            .flat_map(|x| x.linked.iter())
+            .map(|c| &**c.0)
+    }
+
+    /// Returns the backtrace captured when the error was created.
+    ///
+    /// This method is available only if the `backtrace` feature is enabled.
+    #[cfg(feature = "backtrace")]
+    pub fn backtrace(&self) -> Option<&'a Backtrace> {
+        self.inner
+            .backtrace
+            .0
+            .as_deref()
+            .filter(|b| b.status() == BacktraceStatus::Captured)
+    }
+
+    /// Renders the error in the default format (i.e., the format used if no global renderer is set).
+    pub fn fmt_default(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_styled(f, &::fmt::Style::PLAIN)
+    }
+
+    pub(crate) fn fmt_styled(&self, f: &mut fmt::Formatter, style: &::fmt::Style) -> fmt::Result {
+        f.write_str(style.kind)?;
+        self.kind.display(f)?;
+        f.write_str(style.reset)?;
+        if let Some(code) = self.kind.code() {
+            write!(f, " [{}]", code)?;
+        }
+        if let Some(cause) = self.cause_text() {
+            write!(f, " (cause; {})", cause)?;
+        }
+        let limit = match f.precision() {
+            Some(n) => Some((n, n)),
+            None if f.alternate() => ::display_frame_limit(),
+            None => Some((1, 1)),
+        };
+        if !f.alternate() {
+            f.write_str("\n")?;
+            return self.inner.history.fmt_styled(f, style, limit);
+        }
+
+        let mut sources = self.sources().peekable();
+        if sources.peek().is_some() {
+            write!(f, "\nCaused by:")?;
+        }
+        for (i, source) in sources.enumerate() {
+            write!(f, "\n  {}: {}", i, source)?;
+        }
+        if let Some(id) = self.inner.instance_id {
+            write!(f, "\nID: {:016x}", id)?;
+        }
+        if !self.inner.contexts.is_empty() {
+            write!(f, "\nCONTEXT:")?;
+            for context in self.inner.contexts.iter() {
+                write!(f, "\n  - {}", context)?;
+            }
+        }
+        f.write_str("\n")?;
+        self.inner.history.fmt_styled(f, style, limit)?;
+        for (i, e) in self.linked_errors().enumerate() {
+            writeln!(f, "LINKED ERROR [{}]:", i)?;
+            for line in format!("{:#}", e).trim_end().lines() {
+                writeln!(f, "  {}", line)?;
+            }
+        }
+        #[cfg(feature = "backtrace")]
+        {
+            if let Some(backtrace) = self.backtrace() {
+                write!(f, "BACKTRACE:\n{}", backtrace)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A renderer that decides how `TrackableError` instances are displayed.
+///
+/// See `set_global_renderer` for more details.
+pub trait ErrorRenderer: Send + Sync {
+    /// Renders the error consisting of `parts`.
+    ///
+    /// Note that `f.alternate()` returns `true` if the error is formatted with `{:#}`.
+    fn render(&self, parts: &ErrorParts, f: &mut fmt::Formatter) -> fmt::Result;
+}
+
+static GLOBAL_RENDERER: RwLock<Option<Arc<dyn ErrorRenderer>>> = RwLock::new(None);
+
+/// Sets the renderer used by the `Display` implementation of every `TrackableError` in this process.
+///
+/// If `None` is specified, the default format is used.
+///
+/// # Examples
+///
+/// ```
+/// use std::fmt;
+/// use trackable::error::{self, ErrorKindExt, ErrorParts, ErrorRenderer, Failed};
+///
+/// struct OneLine;
+/// impl ErrorRenderer for OneLine {
+///     fn render(&self, parts: &ErrorParts, f: &mut fmt::Formatter) -> fmt::Result {
+///         parts.kind().display(f)?;
+///         write!(f, ": {} events", parts.history().len())
+///     }
+/// }
+///
+/// error::set_global_renderer(Some(Box::new(OneLine)));
+/// assert_eq!(Failed.cause("oops").to_string(), "Failed: 0 events");
+///
+/// error::set_global_renderer(None);
+/// assert_eq!(Failed.cause("oops").to_string(), "Failed (cause; oops)\nHISTORY:\n");
+/// ```
+pub fn set_global_renderer(renderer: Option<Box<dyn ErrorRenderer>>) {
+    *GLOBAL_RENDERER.write().unwrap_or_else(|e| e.into_inner()) = renderer.map(Arc::from);
+}
+
+fn global_renderer() -> Option<Arc<dyn ErrorRenderer>> {
+    GLOBAL_RENDERER
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+}
+
+impl<K: ErrorKind> Error for TrackableError<K> {
+    fn description(&self) -> &str {
+        self.kind.description()
+    }
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.inner
+            .cause
+            .as_ref()
+            .map::<&(dyn Error + 'static), _>(|e| &**e.0)
+            .or_else(|| {
+                self.linked_errors()
+                    .next()
+                    .map::<&(dyn Error + 'static), _>(|e| e)
+            })
+    }
+
+    /// Provides the history, the origin location and the backtrace (if captured) of this error.
+    ///
+    /// This is available only on nightly Rust with `--cfg trackable_nightly`.
+    #[cfg(trackable_nightly)]
+    fn provide<'a>(&'a self, request: &mut std::error::Request<'a>) {
+        request.provide_ref::<History>(&self.inner.history);
+        if let Some(origin) = self.inner.history.events().first() {
+            request.provide_ref::<Location>(origin);
+        }
+        #[cfg(feature = "backtrace")]
+        {
+            if let Some(backtrace) = self.backtrace() {
+                request.provide_ref::<Backtrace>(backtrace);
+            }
+        }
+    }
+}
+impl<K> ContextualError for TrackableError<K> {
+    fn add_context<C: fmt::Display>(&mut self, context: C) {
+        self.inner
+            .contexts
+            .push(Context::Evaluated(context.to_string()));
+    }
+}
+impl<K> Trackable for TrackableError<K> {
+    type Event = Location;
+
+    #[inline]
+    fn history(&self) -> Option<&History> {
+        Some(&self.inner.history)
+    }
+
+    #[inline]
+    fn history_mut(&mut self) -> Option<&mut History> {
+        Some(Arc::make_mut(&mut self.inner.history))
+    }
+}
+
+/// A wrapper that makes an arbitrary error trackable.
+///
+/// This is useful for tracking foreign errors (e.g., enums defined by other crates)
+/// without defining a `TrackableError` kind for each of those.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate trackable;
+///
+/// use trackable::error::{Tracked, TrackedExt};
+///
+/// fn parse(s: &str) -> Result<u8, Tracked<std::num::ParseIntError>> {
+///     let n = track!(s.parse::<u8>().tracked())?;
+///     Ok(n)
+/// }
+///
+/// # fn main() {
+/// let e = parse("foo").err().unwrap();
+/// assert_eq!(e.to_string(), e.inner().to_string() + "\n" + &e.history().to_string());
+/// assert_eq!(e.history().events().len(), 1);
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Tracked<E> {
+    inner: E,
+    history: History,
+}
+impl<E> Tracked<E> {
+    /// Makes a new `Tracked` instance.
+    pub fn new(inner: E) -> Self {
+        Tracked {
+            inner,
+            history: History::new(),
+        }
+    }
+
+    /// Returns the reference to the inner error.
+    pub fn inner(&self) -> &E {
+        &self.inner
+    }
+
+    /// Returns the tracking history of this error.
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    /// Converts this into the inner error.
+    pub fn into_inner(self) -> E {
+        self.inner
+    }
+}
+impl<E> From<E> for Tracked<E> {
+    fn from(f: E) -> Self {
+        Tracked::new(f)
+    }
+}
+impl<E: fmt::Display> fmt::Display for Tracked<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}\n{}", self.inner, self.history)
+    }
+}
+impl<E: Error> Error for Tracked<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.inner.source()
+    }
+}
+impl<E> Trackable for Tracked<E> {
+    type Event = Location;
+
+    #[inline]
+    fn history(&self) -> Option<&History> {
+        Some(&self.history)
+    }
+
+    #[inline]
+    fn history_mut(&mut self) -> Option<&mut History> {
+        Some(&mut self.history)
+    }
+}
+
+/// An extension of `Result` for wrapping the error by `Tracked`.
+pub trait TrackedExt<T, E> {
+    /// Wraps the error of this result by `Tracked`.
+    // `Tracked` holds its history inline, which is large if the `smallvec` feature is enabled.
+    #[cfg_attr(feature = "smallvec", allow(clippy::result_large_err))]
+    fn tracked(self) -> Result<T, Tracked<E>>;
+}
+impl<T, E: Error> TrackedExt<T, E> for Result<T, E> {
+    fn tracked(self) -> Result<T, Tracked<E>> {
+        self.map_err(Tracked::new)
+    }
+}
+
+/// An aggregation of multiple `TrackableError`s.
+///
+/// This is useful, for example, to report all the errors that occurred while validating many fields.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate trackable;
+///
+/// use trackable::error::{Failed, ErrorKindExt, MultiError};
+///
+/// # fn main() {
+/// let mut errors = MultiError::new();
+/// errors.push(Failed.cause("foo"));
+/// errors.push(Failed.cause("bar"));
+/// let errors = track!(errors);
+///
+/// assert_eq!(format!("\n{}", errors).replace('\\', "/"), r#"
+/// 2 errors occurred
+/// [0] Failed (cause; foo)
+///   HISTORY:
+/// [1] Failed (cause; bar)
+///   HISTORY:
+/// HISTORY:
+///   [0] at src/error.rs:10:14
+/// "#);
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct MultiError<K> {
+    errors: Vec<TrackableError<K>>,
+    history: History,
+}
+impl<K> MultiError<K> {
+    /// Makes an empty `MultiError` instance.
+    pub fn new() -> Self {
+        MultiError {
+            errors: Vec::new(),
+            history: History::new(),
+        }
+    }
+
+    /// Adds an error to this aggregation.
+    pub fn push<E>(&mut self, error: E)
+    where
+        E: Into<TrackableError<K>>,
+    {
+        self.errors.push(error.into());
+    }
+
+    /// Returns the aggregated errors.
+    pub fn errors(&self) -> &[TrackableError<K>] {
+        &self.errors
+    }
+
+    /// Converts this into the aggregated errors.
+    pub fn into_errors(self) -> Vec<TrackableError<K>> {
+        self.errors
+    }
+
+    /// Returns the number of the aggregated errors.
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+
+    /// Returns `true` if this has no errors, otherwise `false`.
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+impl<K> Default for MultiError<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<K> FromIterator<TrackableError<K>> for MultiError<K> {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = TrackableError<K>>,
+    {
+        MultiError {
+            errors: iter.into_iter().collect(),
+            history: History::new(),
+        }
+    }
+}
+impl<K: ErrorKind> fmt::Display for MultiError<K> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let plural = if self.errors.len() == 1 { "" } else { "s" };
+        writeln!(f, "{} error{} occurred", self.errors.len(), plural)?;
+        for (i, e) in self.errors.iter().enumerate() {
+            let e = if f.alternate() {
+                format!("{:#}", e)
+            } else {
+                e.to_string()
+            };
+            for (j, line) in e.lines().enumerate() {
+                if j == 0 {
+                    writeln!(f, "[{}] {}", i, line)?;
+                } else {
+                    writeln!(f, "  {}", line)?;
+                }
+            }
+        }
+        write!(f, "{}", self.history)
+    }
+}
+impl<K: ErrorKind> Error for MultiError<K> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        None
+    }
+}
+impl<K> Trackable for MultiError<K> {
+    type Event = Location;
+
+    #[inline]
+    fn history(&self) -> Option<&History> {
+        Some(&self.history)
+    }
+
+    #[inline]
+    fn history_mut(&mut self) -> Option<&mut History> {
+        Some(&mut self.history)
+    }
+}
+
+/// A view of the kind and the cause of a `TrackableError`.
+///
+/// This is created by `TrackableError::kind_and_cause` method.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KindAndCause<'a, K: 'a> {
+    /// The kind of the error.
+    pub kind: &'a K,
+
+    /// The textual representation of the cause of the error.
+    pub cause: Option<String>,
+}
+
+/// A builder for `TrackableError`.
+///
+/// This is created by `TrackableError::builder` function.
+#[derive(Debug)]
+pub struct TrackableErrorBuilder<K> {
+    error: TrackableError<K>,
+    locations: Vec<Location>,
+    history_capacity: usize,
+    history_limit: Option<usize>,
+}
+impl<K: ErrorKind> TrackableErrorBuilder<K> {
+    /// Sets the cause of the error.
+    pub fn cause<E>(mut self, cause: E) -> Self
+    where
+        E: Into<BoxError>,
+    {
+        self.error.inner.cause = Some(Cause(Arc::new(cause.into())));
+        self
+    }
+
+    /// Adds a context message to the error.
+    ///
+    /// See `TrackableError::context` for more details.
+    pub fn message<M>(mut self, message: M) -> Self
+    where
+        M: fmt::Display,
+    {
+        self.error = self.error.context(message);
+        self
+    }
+
+    /// Adds a location to the history of the error.
+    pub fn location(mut self, location: Location) -> Self {
+        self.locations.push(location);
+        self
+    }
+
+    /// Sets the initial capacity of the history of the error.
+    pub fn history_capacity(mut self, capacity: usize) -> Self {
+        self.history_capacity = capacity;
+        self
+    }
+
+    /// Sets the maximum number of the events held by the history of the error.
+    ///
+    /// See `History::with_capacity_limit` for more details.
+    pub fn history_limit(mut self, limit: usize) -> Self {
+        self.history_limit = Some(limit);
+        self
+    }
+
+    /// Builds the error.
+    pub fn build(self) -> TrackableError<K> {
+        let mut error = self.error;
+        let mut history = if let Some(limit) = self.history_limit {
+            History::with_capacity_limit(limit)
+        } else {
+            let capacity = self.history_capacity.max(self.locations.len());
+            History::with_capacity(capacity)
+        };
+        for location in self.locations {
+            history.add(location);
+        }
+        error.inner.history = Arc::new(history);
+        error
+    }
+}
+
+/// A `Display` wrapper that renders a `TrackableError` on a single line.
+///
+/// See `TrackableError::display_compact` for more details.
+#[derive(Debug)]
+pub struct DisplayCompact<'a, K: 'a>(&'a TrackableError<K>);
+impl<'a, K: ErrorKind> fmt::Display for DisplayCompact<'a, K> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let e = self.0;
+        e.kind.display(f)?;
+        if let Some(code) = e.kind.code() {
+            write!(f, " [{}]", code)?;
+        }
+        if let Some(cause) = e.parts().cause_text() {
+            let cause = cause.to_string();
+            let lines = cause.trim_end().lines().map(str::trim).collect::<Vec<_>>();
+            write!(f, " (cause; {})", lines.join(" "))?;
+        }
+        if !e.inner.contexts.is_empty() {
+            write!(f, " (context; {})", e.inner.contexts.join("; "))?;
+        }
+        write!(f, " [")?;
+        for (i, location) in e.inner.history.events().iter().enumerate() {
+            let delimiter = if i == 0 { "" } else { " -> " };
+            let location = location.to_string();
+            write!(f, "{}{}", delimiter, location.trim_start_matches("at "))?;
+        }
+        write!(f, "]")
+    }
+}
+
+/// A `Display` wrapper that renders a `TrackableError` as a JSON object.
+///
+/// See `TrackableError::json_report` for the shape of the object.
+#[derive(Debug)]
+pub struct JsonReport<'a, K: 'a>(&'a TrackableError<K>);
+impl<'a, K: ErrorKind> fmt::Display for JsonReport<'a, K> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let e = self.0;
+        write!(
+            f,
+            "{{\"kind\":{}",
+            JsonStr(&DisplayKind(&e.kind).to_string())
+        )?;
+        match e.kind.code() {
+            Some(code) => write!(f, ",\"code\":{}", JsonStr(code))?,
+            None => write!(f, ",\"code\":null")?,
+        }
+        write!(f, ",\"severity\":\"{:?}\"", e.severity())?;
+        match e.inner.cause {
+            Some(ref cause) => write!(f, ",\"cause\":{}", JsonStr(&cause.0.to_string()))?,
+            None => write!(f, ",\"cause\":null")?,
+        }
+        write!(f, ",\"contexts\":[")?;
+        for (i, context) in e.inner.contexts.iter().enumerate() {
+            let delimiter = if i == 0 { "" } else { "," };
+            write!(f, "{}{}", delimiter, JsonStr(context))?;
+        }
+        write!(f, "],\"history\":[")?;
+        for (i, l) in e.inner.history.events().iter().enumerate() {
+            let delimiter = if i == 0 { "" } else { "," };
+            write!(
+                f,
+                "{}{{\"file\":{},\"line\":{},\"column\":{},\"module\":{},\"function\":{},\"message\":{},\"fields\":{{",
+                delimiter,
+                JsonStr(l.file()),
+                l.line(),
+                l.column(),
+                JsonStr(l.module_path()),
+                JsonStr(l.function()),
+                JsonStr(l.message())
+            )?;
+            for (j, field) in l.fields().iter().enumerate() {
+                let delimiter = if j == 0 { "" } else { "," };
+                write!(
+                    f,
+                    "{}{}:{}",
+                    delimiter,
+                    JsonStr(field.key()),
+                    JsonStr(field.value())
+                )?;
+            }
+            write!(f, "}}}}")?;
+        }
+        write!(f, "]}}")
+    }
+}
+
+// A JSON string literal.
+struct JsonStr<'a>(&'a str);
+impl<'a> fmt::Display for JsonStr<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("\"")?;
+        for c in self.0.chars() {
+            match c {
+                '"' => f.write_str("\\\"")?,
+                '\\' => f.write_str("\\\\")?,
+                '\n' => f.write_str("\\n")?,
+                '\r' => f.write_str("\\r")?,
+                '\t' => f.write_str("\\t")?,
+                c if c < ' ' => write!(f, "\\u{:04x}", c as u32)?,
+                c => write!(f, "{}", c)?,
+            }
+        }
+        f.write_str("\"")
+    }
+}
+
+/// Structured data parsed from the textual representation of a `TrackableError`.
+///
+/// This is useful for tools that reconstruct errors from captured logs.
+///
+/// # Examples
+///
+/// ```
+/// use trackable::error::ParsedReport;
+///
+/// let report: ParsedReport = r#"
+/// NotFound [E404] (cause; no such user)
+/// CONTEXT:
+///   - while handling GET /users/42
+/// HISTORY:
+///   [0] at src/db.rs:10:5
+///   [1] at src/handler.rs:20:9 -- user_id=42
+/// "#.trim_start().parse().unwrap();
+///
+/// assert_eq!(report.kind(), "NotFound");
+/// assert_eq!(report.code(), Some("E404"));
+/// assert_eq!(report.cause(), Some("no such user"));
+/// assert_eq!(report.contexts(), ["while handling GET /users/42"]);
+/// assert_eq!(report.history().events()[1].file(), "src/handler.rs");
+/// ```
+#[derive(Debug, Clone)]
+pub struct ParsedReport {
+    kind: String,
+    code: Option<String>,
+    cause: Option<String>,
+    sources: Vec<String>,
+    instance_id: Option<u64>,
+    contexts: Vec<String>,
+    history: History,
+    linked: Vec<ParsedReport>,
+}
+impl ParsedReport {
+    /// Returns the textual representation of the kind of the error.
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    /// Returns the code of the error kind (if any).
+    pub fn code(&self) -> Option<&str> {
+        self.code.as_deref()
+    }
+
+    /// Returns the textual representation of the cause of the error (if any).
+    pub fn cause(&self) -> Option<&str> {
+        self.cause.as_deref()
+    }
+
+    /// Returns the source chain of the cause (i.e., the `Caused by:` entries of a report).
+    pub fn sources(&self) -> &[String] {
+        &self.sources
+    }
+
+    /// Returns the instance ID of the error (if any).
+    pub fn instance_id(&self) -> Option<u64> {
+        self.instance_id
+    }
+
+    /// Returns the context messages of the error.
+    pub fn contexts(&self) -> &[String] {
+        &self.contexts
+    }
+
+    /// Returns the history of the error.
+    ///
+    /// See the `FromStr` implementation of `History` for the details of the restored data.
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    /// Returns the reports of the linked errors.
+    pub fn linked_errors(&self) -> &[ParsedReport] {
+        &self.linked
+    }
+}
+impl std::str::FromStr for ParsedReport {
+    type Err = Failure;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (header, rest) = track_assert_some!(split_report_header(s), Failed);
+        let (kind, cause) = match header.split_once(" (cause; ") {
+            Some((kind, cause)) => (kind, Some(cause[..cause.len() - 1].to_owned())),
+            None => (header, None),
+        };
+        let (kind, code) = match kind.strip_suffix(']').and_then(|k| k.rsplit_once(" [")) {
+            Some((kind, code)) => (kind, Some(code.to_owned())),
+            None => (kind, None),
+        };
+        let mut report = ParsedReport {
+            kind: kind.to_owned(),
+            code,
+            cause,
+            sources: Vec::new(),
+            instance_id: None,
+            contexts: Vec::new(),
+            history: History::default(),
+            linked: Vec::new(),
+        };
+
+        let mut lines = rest.lines().peekable();
+        if lines.peek() == Some(&"Caused by:") {
+            lines.next();
+            while let Some(source) = lines
+                .peek()
+                .and_then(|l| l.strip_prefix("  "))
+                .and_then(|l| l.split_once(": "))
+            {
+                report.sources.push(source.1.to_owned());
+                lines.next();
+            }
+        }
+        if let Some(id) = lines.peek().and_then(|l| l.strip_prefix("ID: ")) {
+            let id = track!(u64::from_str_radix(id, 16).map_err(Failure::from_error))?;
+            report.instance_id = Some(id);
+            lines.next();
+        }
+        if lines.peek() == Some(&"CONTEXT:") {
+            lines.next();
+            while let Some(context) = lines.peek().and_then(|l| l.strip_prefix("  - ")) {
+                report.contexts.push(context.to_owned());
+                lines.next();
+            }
+        }
+        report.history = track!(::parse::parse_history(&mut lines))?;
+        while let Some(line) = lines.next() {
+            if line.starts_with("BACKTRACE:") {
+                break;
+            }
+            track_assert!(
+                line.starts_with("LINKED ERROR ["),
+                Failed,
+                "line={:?}",
+                line
+            );
+            let mut linked = String::new();
+            while let Some(l) = lines.peek().and_then(|l| l.strip_prefix("  ")) {
+                linked.push_str(l);
+                linked.push('\n');
+                lines.next();
+            }
+            report.linked.push(track!(linked.parse())?);
+        }
+        Ok(report)
+    }
+}
+
+// Splits a report into the header (i.e., the kind and the cause) and the following sections.
+fn split_report_header(s: &str) -> Option<(&str, &str)> {
+    let first_line = s.lines().next()?;
+    let end = match first_line.find(" (cause; ") {
+        None => first_line.len(),
+        Some(start) => {
+            // The cause may be a multi-line text which contains parentheses
+            let mut depth = 0;
+            let balanced = s[start + 1..].char_indices().find_map(|(i, c)| {
+                match c {
+                    '(' => depth += 1,
+                    ')' => depth -= 1,
+                    _ => {}
+                }
+                if depth == 0 {
+                    Some(start + 1 + i + 1)
+                } else {
+                    None
+                }
+            });
+            let is_end = |i: &usize| s[*i..].is_empty() || s[*i..].starts_with('\n');
+            let is_section = |t: &str| {
+                ["\nCaused by:", "\nID: ", "\nCONTEXT:", "\nHISTORY:"]
+                    .iter()
+                    .any(|p| t.starts_with(p))
+            };
+            balanced.filter(is_end).or_else(|| {
+                s.match_indices(")\n")
+                    .map(|(i, _)| i + 1)
+                    .find(|&i| i > start && is_section(&s[i..]))
+            })?
+        }
+    };
+    Some((&s[..end], s[end..].strip_prefix('\n').unwrap_or("")))
+}
+
+static INSTANCE_ID_ENABLED: AtomicBool = AtomicBool::new(false);
+
+static INSTANCE_ID_SEQNO: AtomicU64 = AtomicU64::new(0);
+
+/// Sets whether a unique ID is assigned to each `TrackableError` instance created after this call.
+///
+/// If enabled, the ID is included in the textual representation of the error.
+/// See `TrackableError::instance_id` for more details.
+///
+/// The default value is `false`.
+pub fn set_instance_id_enabled(enabled: bool) {
+    INSTANCE_ID_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns `true` if a unique ID is assigned to each newly created `TrackableError` instance.
+pub fn instance_id_enabled() -> bool {
+    INSTANCE_ID_ENABLED.load(Ordering::Relaxed)
+}
+
+fn new_instance_id() -> Option<NonZeroU64> {
+    if !instance_id_enabled() {
+        return None;
+    }
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(INSTANCE_ID_SEQNO.fetch_add(1, Ordering::Relaxed));
+    NonZeroU64::new(hasher.finish().max(1))
+}
+
+/// The default value of the limit returned by `assertion_value_limit` function.
+pub const DEFAULT_ASSERTION_VALUE_LIMIT: usize = 4096;
+
+static ASSERTION_VALUE_LIMIT: AtomicUsize = AtomicUsize::new(DEFAULT_ASSERTION_VALUE_LIMIT);
+
+/// Sets the maximum length (in bytes) of the debugging representations of values
+/// embedded in the cause messages by `track_assert_*` macros.
+///
+/// Longer representations are truncated and annotated with their total length.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// # fn main() {
+/// use trackable::error::{self, Failed, Failure};
+///
+/// fn check(buf: &[u8]) -> Result<(), Failure> {
+///     track_assert_eq!(buf, &[0; 4][..], Failed);
+///     Ok(())
+/// }
+///
+/// error::set_assertion_value_limit(8);
+/// let e = check(&[1; 4]).err().unwrap();
+/// assert_eq!(e.to_string().lines().next(),
+///            Some("Failed (cause; assertion failed: `left == right`; \
+///                  assertion failed: `(left == right)` \
+///                  (left: `[1, 1, 1...(12 bytes)`, right: `[0, 0, 0...(12 bytes)`))"));
+/// # error::set_assertion_value_limit(error::DEFAULT_ASSERTION_VALUE_LIMIT);
+/// # }
+/// ```
+pub fn set_assertion_value_limit(limit: usize) {
+    ASSERTION_VALUE_LIMIT.store(limit, Ordering::Relaxed);
+}
+
+/// Returns the maximum length (in bytes) of the debugging representations of values
+/// embedded in the cause messages by `track_assert_*` macros.
+///
+/// The default value is `DEFAULT_ASSERTION_VALUE_LIMIT`.
+pub fn assertion_value_limit() -> usize {
+    ASSERTION_VALUE_LIMIT.load(Ordering::Relaxed)
+}
+
+/// A helper type for `track_assert_*` macros.
+///
+/// The debugging representation of this is truncated if it exceeds `assertion_value_limit()`.
+#[doc(hidden)]
+pub struct TruncatedDebug<T>(pub T);
+impl<T: fmt::Debug> fmt::Debug for TruncatedDebug<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use std::fmt::Write;
+
+        let mut w = BoundedWriter {
+            buf: String::new(),
+            limit: assertion_value_limit(),
+            len: 0,
+        };
+        if f.alternate() {
+            write!(w, "{:#?}", self.0)?;
+        } else {
+            write!(w, "{:?}", self.0)?;
+        }
+        if w.len <= w.limit {
+            f.write_str(&w.buf)
+        } else {
+            write!(f, "{}...({} bytes)", w.buf, w.len)
+        }
+    }
+}
+
+/// A `fmt::Write` implementation which keeps only the first `limit` bytes of the written string
+/// (and counts the total length).
+struct BoundedWriter {
+    buf: String,
+    limit: usize,
+    len: usize,
+}
+impl fmt::Write for BoundedWriter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.len += s.len();
+        let rest = self.limit - self.buf.len();
+        if s.len() <= rest {
+            self.buf.push_str(s);
+        } else {
+            let mut end = rest;
+            while !s.is_char_boundary(end) {
+                end -= 1;
+            }
+            self.buf.push_str(&s[..end]);
+
+            // Ignores the following strings (even if `s` has been cut before a multi-byte char)
+            self.limit = self.buf.len();
+        }
+        Ok(())
+    }
+}
+
+/// A helper trait for the `track_expect!` macro.
+///
+/// This converts `Option` and `Result` values into `Result<_, TrackableError<_>>` uniformly.
+#[doc(hidden)]
+pub trait TrackExpect<T> {
+    fn into_trackable_result<K: ErrorKind>(
+        self,
+        kind: K,
+        expr: &'static str,
+    ) -> Result<T, TrackableError<K>>;
+}
+impl<T> TrackExpect<T> for Option<T> {
+    fn into_trackable_result<K: ErrorKind>(
+        self,
+        kind: K,
+        expr: &'static str,
+    ) -> Result<T, TrackableError<K>> {
+        self.ok_or_else(|| kind.cause(format!("`{}` is `None`", expr)))
+    }
+}
+impl<T, E: Into<BoxError>> TrackExpect<T> for Result<T, E> {
+    fn into_trackable_result<K: ErrorKind>(
+        self,
+        kind: K,
+        _expr: &'static str,
+    ) -> Result<T, TrackableError<K>> {
+        self.map_err(|e| kind.cause(e))
+    }
+}
+
+/// A helper function for the `track_parse!` macro.
+///
+/// This returns the debugging representation of `input`.
+/// If `input` is too long, it will be truncated and annotated with the total length.
+#[doc(hidden)]
+pub fn truncated_input(input: &str) -> String {
+    const MAX_CHARS: usize = 64;
+    match input.char_indices().nth(MAX_CHARS) {
+        None => format!("{:?}", input),
+        Some((end, _)) => format!("{:?}... ({} bytes)", &input[..end], input.len()),
+    }
+}
+
+/// A helper function for the `track_assert_eq_diff!` macro.
+///
+/// This renders a line-by-line diff of `left` and `right`.
+/// The lines only in `left` are prefixed with `-`, and the lines only in `right` are prefixed with `+`.
+#[doc(hidden)]
+pub fn line_diff(left: &str, right: &str) -> String {
+    let left = left.lines().collect::<Vec<_>>();
+    let right = right.lines().collect::<Vec<_>>();
+
+    // lcs[i][j]: the length of the longest common subsequence of `left[i..]` and `right[j..]`
+    let mut lcs = vec![vec![0; right.len() + 1]; left.len() + 1];
+    for i in (0..left.len()).rev() {
+        for j in (0..right.len()).rev() {
+            lcs[i][j] = if left[i] == right[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = String::from("diff (- left, + right):\n");
+    let (mut i, mut j) = (0, 0);
+    while i < left.len() || j < right.len() {
+        if i < left.len() && j < right.len() && left[i] == right[j] {
+            diff.push_str(&format!("  {}\n", left[i]));
+            i += 1;
+            j += 1;
+        } else if j == right.len() || (i < left.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            diff.push_str(&format!("- {}\n", left[i]));
+            i += 1;
+        } else {
+            diff.push_str(&format!("+ {}\n", right[j]));
+            j += 1;
+        }
+    }
+    diff
+}
+
+pub(crate) struct DisplayKind<'a, K: 'a>(pub &'a K);
+impl<'a, K: ErrorKind> fmt::Display for DisplayKind<'a, K> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.display(f)
+    }
+}
+
+struct Fnv1a(u64);
+impl Fnv1a {
+    fn new() -> Self {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 ^= u64::from(b);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
 
 #[derive(Debug, Clone)]
 struct Cause(Arc<BoxError>);
 
-#[cfg(feature = "serialize")]
+/// A `Display` wrapper of the cause of a `TrackableError` (see `ErrorParts::cause_text`).
+pub(crate) struct CauseText<'a> {
+    cause: &'a (dyn Error + Send + Sync + 'static),
+    absorbed: Option<ErrorParts<'a>>,
+}
+impl<'a> fmt::Display for CauseText<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let parts = match self.absorbed {
+            Some(ref parts) => parts,
+            None => return fmt::Display::fmt(self.cause, f),
+        };
+        parts.kind.display(f)?;
+        if let Some(code) = parts.kind.code() {
+            write!(f, " [{}]", code)?;
+        }
+        if let Some(cause) = parts.cause_text() {
+            write!(f, " (cause; {})", cause)?;
+        }
+        Ok(())
+    }
+}
+
+/// A function that extracts the parts of a cause absorbed by `ErrorKindExt::absorb`.
+#[derive(Debug, Clone, Copy)]
+struct AbsorbedCause(for<'a> fn(&'a (dyn Error + Send + Sync + 'static)) -> Option<ErrorParts<'a>>);
+
+fn absorbed_parts<'a, K>(cause: &'a (dyn Error + Send + Sync + 'static)) -> Option<ErrorParts<'a>>
+where
+    K: ErrorKind + Send + Sync + 'static,
+{
+    cause.downcast_ref::<TrackableError<K>>().map(|e| e.parts())
+}
+
+#[derive(Debug, Clone, Default)]
+struct CapturedBacktrace(#[cfg(feature = "backtrace")] Option<Arc<Backtrace>>);
+impl CapturedBacktrace {
+    #[cfg(feature = "backtrace")]
+    fn capture() -> Self {
+        // `Backtrace::capture` defers the symbol resolution until the backtrace is formatted
+        CapturedBacktrace(Some(Arc::new(Backtrace::capture())))
+    }
+
+    #[cfg(not(feature = "backtrace"))]
+    fn capture() -> Self {
+        CapturedBacktrace()
+    }
+}
+
+/// The parts of `TrackableError` other than the kind.
+///
+/// These are boxed to keep the size of `TrackableError` (and `Result`s containing it) small.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+struct Inner {
+    #[cfg_attr(feature = "serde", serde(default))]
+    severity: Option<Severity>,
+    cause: Option<Cause>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    contexts: Contexts,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    extras: Option<Box<Extras>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[cfg_attr(not(feature = "backtrace"), allow(dead_code))]
+    backtrace: CapturedBacktrace,
+    #[cfg_attr(feature = "serde", serde(default))]
+    instance_id: Option<NonZeroU64>,
+    history: Arc<History>,
+}
+
+/// The context messages of a `TrackableError`.
+///
+/// The messages added by `TrackableError::with_context` are evaluated on the first access.
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Deserialize), serde(from = "Vec<String>"))]
+struct Contexts {
+    contexts: Vec<Context>,
+    evaluated: OnceLock<Vec<String>>,
+}
+impl Contexts {
+    fn push(&mut self, context: Context) {
+        self.contexts.push(context);
+        self.evaluated = OnceLock::new();
+    }
+}
+impl Deref for Contexts {
+    type Target = [String];
+
+    fn deref(&self) -> &Self::Target {
+        self.evaluated.get_or_init(|| {
+            self.contexts
+                .iter()
+                .map(|c| match *c {
+                    Context::Evaluated(ref message) => message.clone(),
+                    Context::Lazy(ref lazy) => lazy.get().to_owned(),
+                })
+                .collect()
+        })
+    }
+}
+impl From<Vec<String>> for Contexts {
+    fn from(f: Vec<String>) -> Self {
+        Contexts {
+            contexts: f.into_iter().map(Context::Evaluated).collect(),
+            evaluated: OnceLock::new(),
+        }
+    }
+}
+impl fmt::Debug for Contexts {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[derive(Clone)]
+enum Context {
+    Evaluated(String),
+    Lazy(Arc<LazyContext>),
+}
+
+struct LazyContext {
+    message: OnceLock<String>,
+    f: Mutex<Option<Box<dyn FnOnce() -> String + Send>>>,
+}
+impl LazyContext {
+    fn get(&self) -> &str {
+        self.message.get_or_init(|| {
+            let f = self.f.lock().unwrap_or_else(|e| e.into_inner()).take();
+            f.map_or_else(String::new, |f| f())
+        })
+    }
+}
+
+/// Rarely used parts of `TrackableError`.
+///
+/// These are boxed separately to keep the allocation of `Inner` small.
+#[derive(Debug, Clone, Default)]
+struct Extras {
+    original_kind: Option<Arc<dyn ErrorKind + Send + Sync>>,
+    extensions: Extensions,
+    linked: Vec<Cause>,
+    absorbed: Option<AbsorbedCause>,
+}
+
+#[derive(Clone, Default)]
+struct Extensions(HashMap<TypeId, Arc<dyn Any + Send + Sync>>);
+impl fmt::Debug for Extensions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Extensions")
+            .field("len", &self.0.len())
+            .finish()
+    }
+}
+
+#[cfg(feature = "serde")]
 mod impl_serde {
+    use serde::ser::SerializeStruct;
     use serde::{Deserialize, Deserializer, Serialize, Serializer};
     use std::sync::Arc;
 
-    use super::Cause;
+    use super::{Cause, ErrorKind, TrackableError};
+
+    impl<K: ErrorKind + Serialize> Serialize for TrackableError<K> {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            let mut s = serializer.serialize_struct("TrackableError", 7)?;
+            s.serialize_field("kind", &self.kind)?;
+            s.serialize_field("severity", &self.inner.severity)?;
+            s.serialize_field("code", &self.kind.code())?;
+            s.serialize_field("cause", &self.inner.cause)?;
+            s.serialize_field("contexts", &*self.inner.contexts)?;
+            s.serialize_field("instance_id", &self.inner.instance_id)?;
+            s.serialize_field("history", &*self.inner.history)?;
+            s.end()
+        }
+    }
 
     impl Serialize for Cause {
         fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -483,6 +2721,101 @@ mod test {
     use super::*;
     use std;
 
+    #[test]
+    fn error_is_pointer_sized() {
+        use std::mem::size_of;
+
+        assert_eq!(size_of::<Failure>(), size_of::<usize>());
+        assert_eq!(size_of::<Result<(), Failure>>(), size_of::<usize>());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_roundtrip_works() {
+        extern crate serde_json;
+
+        let e: Failure = track!(Failed.cause("oops").context("ctx"), "hello").into();
+        let json = serde_json::to_string(&e).unwrap();
+        let decoded: Failure = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.to_string(), e.to_string());
+        assert_eq!(decoded.history().map(History::len), Some(1));
+
+        let e = track!(io::ErrorKind::NotFound.cause("no such file")).into_kind_name();
+        let json = serde_json::to_string(&e).unwrap();
+        let decoded: TrackableError<KindName> = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.to_string(), e.to_string());
+    }
+
+    #[test]
+    fn display_compact_is_single_line() {
+        let inner: Failure = track!(Failed.cause("line1\nline2")).into();
+        let e = track!(Failed.cause(inner), "outer");
+        let compact = e.display_compact().to_string();
+        assert!(!compact.contains('\n'));
+        assert!(compact.starts_with("Failed (cause; Failed (cause; line1 line2) HISTORY: [0] at "));
+        assert!(compact.ends_with(" -- outer]"));
+    }
+
+    #[test]
+    fn json_report_is_valid_json() {
+        extern crate serde_json;
+
+        let e = track!(
+            Failed.cause("line1\nline2\t\"quoted\" \\ \u{1}"),
+            { user = "ü" },
+            "msg"
+        );
+        let json: serde_json::Value = serde_json::from_str(&e.json_report().to_string()).unwrap();
+        assert_eq!(json["kind"], "Failed");
+        assert_eq!(json["cause"], "line1\nline2\t\"quoted\" \\ \u{1}");
+        assert_eq!(json["history"][0]["message"], "msg");
+        assert_eq!(json["history"][0]["fields"]["user"], "\"ü\"");
+        assert_eq!(json["history"][0]["module"], module_path!());
+    }
+
+    #[test]
+    fn parse_report_works() {
+        let inner: Failure = track!(Failed.cause("deep (nested)")).into();
+        let linked: Failure = track!(Failed.cause("oops :(").context("cleaning up")).into();
+        let e = track!(Failed.cause(inner).context("ctx"));
+        let e: Failure = track!(e.chain(linked), "outer").into();
+
+        let report: ParsedReport = e.to_string().parse().unwrap();
+        assert_eq!(report.kind(), "Failed");
+        assert_eq!(report.code(), None);
+        assert!(report
+            .cause()
+            .unwrap()
+            .starts_with("Failed (cause; deep (nested))\nHISTORY:"));
+        assert_eq!(report.history().len(), 2);
+        assert_eq!(report.history().events()[1].message(), "outer");
+        assert!(report.contexts().is_empty());
+        assert!(report.linked_errors().is_empty());
+
+        // The extended report also contains the contexts, the linked errors and the sources
+        let report: ParsedReport = format!("{:#}", e).parse().unwrap();
+        assert_eq!(report.contexts(), ["ctx"]);
+        assert_eq!(report.history().len(), 2);
+        assert_eq!(report.sources(), ["deep (nested)"]);
+
+        let linked = &report.linked_errors()[0];
+        assert_eq!(linked.cause(), Some("oops :("));
+        assert_eq!(linked.contexts(), ["cleaning up"]);
+        assert_eq!(linked.history().len(), 1);
+    }
+
+    #[test]
+    fn clones_share_history_until_tracked() {
+        let original: Failure = track!(Failure::from(Failed.error()));
+        let forked = original.clone();
+        assert!(Arc::ptr_eq(&original.inner.history, &forked.inner.history));
+
+        let forked = track!(forked, "forked");
+        assert!(!Arc::ptr_eq(&original.inner.history, &forked.inner.history));
+        assert_eq!(original.history().map(History::len), Some(1));
+        assert_eq!(forked.history().map(History::len), Some(2));
+    }
+
     #[test]
     fn it_works() {
         #[derive(Debug, TrackableError)]
@@ -512,8 +2845,8 @@ mod test {
             r#"
 Error: Critical (cause; something wrong)
 HISTORY:
-  [0] at src/error.rs:508
-  [1] at src/error.rs:509 -- I passed here
+  [0] at src/error.rs:2841:21
+  [1] at src/error.rs:2842:21 -- I passed here
 "#
         );
 
@@ -527,4 +2860,172 @@ HISTORY:
         let cause = error.concrete_cause::<std::io::Error>().unwrap();
         assert_eq!(cause.kind(), std::io::ErrorKind::NotFound);
     }
+
+    #[test]
+    fn source_works() {
+        let io_error = std::io::Error::other("oops");
+        let error = Failed.cause(io_error);
+        let source = error.source().unwrap();
+        assert_eq!(source.to_string(), "oops");
+        assert!(source.downcast_ref::<std::io::Error>().is_some());
+
+        let error: TrackableError<Failed> = Failed.error();
+        assert!(error.source().is_none());
+    }
+
+    #[test]
+    fn map_kind_works() {
+        let mut error: TrackableError<Failed> = track!(Failed.cause("oops"));
+        *error.kind_mut() = Failed;
+
+        let error = error.map_kind(|_| String::from("Fatal"));
+        assert_eq!(error.kind(), "Fatal");
+        assert_eq!(
+            error.source().map(|e| e.to_string()),
+            Some("oops".to_owned())
+        );
+        assert_eq!(error.history().map(|h| h.events().len()), Some(1));
+    }
+
+    #[test]
+    fn code_works() {
+        #[derive(Debug)]
+        struct NotFound;
+        impl ErrorKind for NotFound {
+            fn code(&self) -> Option<&'static str> {
+                Some("E0404")
+            }
+        }
+
+        let error = NotFound.cause("no such user");
+        assert_eq!(error.code(), Some("E0404"));
+        assert_eq!(
+            error.to_string(),
+            "NotFound [E0404] (cause; no such user)\nHISTORY:\n"
+        );
+        assert_eq!(Failed.error().code(), None);
+    }
+
+    #[test]
+    fn fingerprint_works() {
+        let e0 = track!(Failed.cause("timeout after 10ms"));
+        let e1 = track!(Failed.cause("timeout after 200ms"));
+        assert_ne!(e0.fingerprint(), e1.fingerprint()); // different locations
+
+        let errors = [10, 200]
+            .iter()
+            .map(|n| track!(Failed.cause(format!("timeout after {}ms", n))))
+            .collect::<Vec<_>>();
+        assert_eq!(errors[0].fingerprint(), errors[1].fingerprint());
+
+        let e2 = track!(errors[0].clone());
+        assert_eq!(errors[0].fingerprint(), e2.fingerprint());
+
+        assert_ne!(
+            Failed.cause("foo").fingerprint(),
+            Failed.cause("bar").fingerprint()
+        );
+    }
+
+    #[cfg(feature = "std-errors")]
+    #[test]
+    fn from_std_errors_works() {
+        fn parse(s: &str) -> Result<u8, Failure> {
+            Ok(s.parse::<u8>()?)
+        }
+
+        let e = parse("foo").err().unwrap();
+        assert!(e.concrete_cause::<std::num::ParseIntError>().is_some());
+    }
+
+    #[cfg(feature = "backtrace")]
+    #[test]
+    fn backtrace_works() {
+        let e = Failed.error();
+        let s = format!("{:#}", e);
+        if let Some(backtrace) = e.backtrace() {
+            assert!(s.ends_with(&format!("HISTORY:\nBACKTRACE:\n{}", backtrace)));
+        } else {
+            assert_eq!(s, "Failed\nHISTORY:\n");
+        }
+        assert_eq!(e.to_string(), "Failed\nHISTORY:\n");
+    }
+
+    #[cfg(trackable_nightly)]
+    #[test]
+    fn provide_works() {
+        let e = track!(Failed.error());
+        let history = std::error::request_ref::<History>(&e).unwrap();
+        assert_eq!(history.events().len(), 1);
+
+        let origin = std::error::request_ref::<Location>(&e).unwrap();
+        assert_eq!(origin.file(), file!());
+    }
+
+    #[test]
+    fn line_diff_works() {
+        let diff = line_diff("a\nb\nc\nd", "a\nc\ne\nd");
+        assert_eq!(diff, "diff (- left, + right):\n  a\n- b\n  c\n+ e\n  d\n");
+    }
+
+    #[test]
+    fn lazy_context_is_evaluated_once() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let c = Arc::clone(&count);
+        let e0 = Failed.cause("foo").context("first").with_context(move || {
+            c.fetch_add(1, Ordering::SeqCst);
+            "second"
+        });
+        let e1 = e0.clone().context("third");
+        assert_eq!(count.load(Ordering::SeqCst), 0);
+
+        assert_eq!(e0.contexts(), ["first", "second"]);
+        assert_eq!(e1.contexts(), ["first", "second", "third"]);
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn multi_error_pluralizes_the_count() {
+        let mut errors = MultiError::new();
+        errors.push(Failed.cause("foo"));
+        assert!(errors.to_string().starts_with("1 error occurred\n"));
+
+        errors.push(Failed.cause("bar"));
+        assert!(errors.to_string().starts_with("2 errors occurred\n"));
+    }
+
+    #[test]
+    fn absorbed_cause_is_rendered_structurally() {
+        let inner: Failure = Failed.cause("oops").context("ctx").into();
+        let e = io::ErrorKind::NotFound.absorb(inner);
+        assert_eq!(
+            e.to_string().lines().next(),
+            Some("NotFound (cause; Failed (cause; oops))")
+        );
+        assert!(e.concrete_cause::<TrackableError<Failed>>().is_some());
+
+        // A cause which is not absorbed is rendered as is
+        let e = io::ErrorKind::NotFound.cause(Failed.cause("oops"));
+        assert!(e
+            .to_string()
+            .starts_with("NotFound (cause; Failed (cause; oops)\nHISTORY:\n)"));
+    }
+
+    #[test]
+    fn top_level_error_reports_full_history() {
+        let mut e = Failure::from(Failed.error());
+        for line in 1..4 {
+            e.track(|| Location::new("", "src/main.rs", line, ""));
+        }
+        let e = TopLevelError::from(e);
+
+        // The backtrace is captured if `RUST_BACKTRACE` is set
+        let expected = "Failed\nHISTORY:\n  [0] at src/main.rs:1\n  [1] at src/main.rs:2\n  \
+                        [2] at src/main.rs:3\n";
+        assert_eq!(e.to_string().split("BACKTRACE:").next(), Some(expected));
+        assert_eq!(
+            format!("{:?}", e).split("BACKTRACE:").next(),
+            Some(expected)
+        );
+    }
 }
@@ -0,0 +1,69 @@
+//! Integration with [eyre](https://docs.rs/eyre).
+//!
+//! This module is available only if the `trackable-eyre` feature is enabled.
+//!
+//! `TrackableError<K>` can be converted into `eyre::Report` by `Report::from`
+//! (or the `?` operator) if `K` is `Send + Sync + 'static`.
+//! By installing the handler in this module, the reports render the histories of
+//! trackable errors in the chain of sources.
+//!
+//! # Examples
+//!
+//! ```
+//! extern crate eyre;
+//! #[macro_use]
+//! extern crate trackable;
+//!
+//! use trackable::error::{Failed, ErrorKindExt};
+//!
+//! # fn main() {
+//! trackable::eyre_handler::install().unwrap();
+//!
+//! let e = track!(Failed.cause("oops"));
+//! let e = track!(track!(e));
+//! let e = eyre::Report::from(e);
+//! let e = e.wrap_err("failed to do something");
+//!
+//! // Each cause is rendered in the full form, including its whole history
+//! let report = format!("{:?}", e);
+//! assert!(report.starts_with(
+//!     "failed to do something\n\nCaused by (0):\n  Failed (cause; oops)\n  HISTORY:\n"
+//! ));
+//! for i in 0..3 {
+//!     assert!(report.contains(&format!("\n    [{}] at src/eyre_handler.rs:", i)));
+//! }
+//! assert!(report.ends_with("\n\nCaused by (1):\n  oops"));
+//! # }
+//! ```
+use eyre::{EyreHandler, InstallError};
+use std::error::Error;
+use std::fmt;
+
+/// An `EyreHandler` that renders the histories of trackable errors.
+#[derive(Debug, Default, Clone)]
+pub struct Handler;
+impl EyreHandler for Handler {
+    fn debug(&self, error: &(dyn Error + 'static), f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            return fmt::Debug::fmt(error, f);
+        }
+
+        write!(f, "{}", format!("{:#}", error).trim_end())?;
+        let mut source = error.source();
+        let mut i = 0;
+        while let Some(e) = source {
+            write!(f, "\n\nCaused by ({}):", i)?;
+            for line in format!("{:#}", e).trim_end().lines() {
+                write!(f, "\n  {}", line)?;
+            }
+            source = e.source();
+            i += 1;
+        }
+        Ok(())
+    }
+}
+
+/// Installs `Handler` as the global hook of `eyre`.
+pub fn install() -> Result<(), InstallError> {
+    eyre::set_hook(Box::new(|_| Box::new(Handler)))
+}
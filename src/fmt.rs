@@ -0,0 +1,454 @@
+//! Global configuration of the textual representation of histories and errors.
+//!
+//! The `Display` implementations of `Location`, `History` and `TrackableError` consult
+//! the process-wide [`Config`](struct.Config.html) which can be set by `set_config` function.
+//!
+//! # `TRACKABLE_FORMAT` environment variable
+//!
+//! The settings specified by the `TRACKABLE_FORMAT` environment variable take precedence over
+//! the ones set programmatically. The variable is read once, when the configuration is first used.
+//!
+//! The value is a comma-separated list of `key=value` settings:
+//!
+//! | Key           | Values                           | Setting                       |
+//! |---------------|----------------------------------|-------------------------------|
+//! | `frames`      | `all`, `<first>:<last>`          | `Config::with_frame_limit`    |
+//! | `strip-paths` | `true`, `false`                  | `Config::with_strip_paths`    |
+//! | `color`       | `true`, `false`                  | `Config::with_color`          |
+//! | `order`       | `oldest-first`, `newest-first`   | `Config::with_newest_first`   |
+//! | `timestamps`  | `true`, `false`                  | `Config::with_timestamps`     |
+//! | `hyperlinks`  | `true`, `false`                  | `Config::with_hyperlinks`     |
+//!
+//! For example, `TRACKABLE_FORMAT="frames=3:1,order=newest-first"`.
+//! The variable is ignored if its value is malformed.
+//!
+//! ```
+//! use trackable::{fmt, Location};
+//!
+//! std::env::set_var(fmt::ENV_VAR, "frames=many");
+//! let location = Location::new("foo", "src/foo.rs", 10, "");
+//! assert_eq!(location.timestamp(), None);
+//! assert_eq!(fmt::config(), fmt::Config::new());
+//! ```
+//!
+//! # Examples
+//!
+//! ```
+//! use trackable::{fmt, History, Location};
+//!
+//! fmt::set_config(fmt::Config::new().with_frame_limit(Some((1, 1))).with_newest_first(true));
+//!
+//! let mut history = History::new();
+//! for line in 1..5 {
+//!     history.add(Location::new("foo", "src/foo.rs", line, ""));
+//! }
+//! assert_eq!(format!("\n{}", history), r#"
+//! HISTORY:
+//!   [3] at src/foo.rs:4
+//!   [0] at src/foo.rs:1
+//!   ... and 2 more frames
+//! "#);
+//! ```
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{OnceLock, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use error::Failure;
+
+/// The name of the environment variable which overrides the global configuration.
+pub const ENV_VAR: &str = "TRACKABLE_FORMAT";
+
+/// Configuration of the textual representation of histories and errors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Config {
+    pub(crate) frame_limit: Option<(usize, usize)>,
+    pub(crate) strip_paths: bool,
+    pub(crate) color: bool,
+    pub(crate) newest_first: bool,
+    pub(crate) timestamps: bool,
+    pub(crate) hyperlinks: bool,
+}
+impl Config {
+    /// Makes a new `Config` instance with the default settings.
+    ///
+    /// By default, all events are rendered oldest-first without colors, timestamps and hyperlinks,
+    /// and the prefixes of the file names are stripped.
+    pub const fn new() -> Self {
+        Config {
+            frame_limit: None,
+            strip_paths: true,
+            color: false,
+            newest_first: false,
+            timestamps: false,
+            hyperlinks: false,
+        }
+    }
+
+    /// Sets the maximum number of the events rendered in a history as `(first, last)`.
+    ///
+    /// See `set_display_frame_limit` function for more details.
+    pub fn with_frame_limit(mut self, limit: Option<(usize, usize)>) -> Self {
+        self.frame_limit = limit;
+        self
+    }
+
+    /// Sets whether the prefixes specified by `set_path_prefixes` function are stripped from the file names.
+    pub fn with_strip_paths(mut self, enabled: bool) -> Self {
+        self.strip_paths = enabled;
+        self
+    }
+
+    /// Sets whether errors are rendered with ANSI colors.
+    ///
+    /// Even if this is enabled, the colors are used only if the `color` feature is enabled
+    /// and `color::enabled` function returns `true`.
+    pub fn with_color(mut self, enabled: bool) -> Self {
+        self.color = enabled;
+        self
+    }
+
+    /// Sets whether histories are rendered newest-first.
+    ///
+    /// See `set_newest_first_enabled` function for more details.
+    pub fn with_newest_first(mut self, enabled: bool) -> Self {
+        self.newest_first = enabled;
+        self
+    }
+
+    /// Sets whether the time of each location is recorded and rendered.
+    ///
+    /// If enabled, the locations created after that record the current time,
+    /// which is rendered in RFC 3339 format (UTC) at the end of each location
+    /// (e.g., `at src/foo.rs:10 [2024-01-02T03:04:05.678Z]`).
+    pub fn with_timestamps(mut self, enabled: bool) -> Self {
+        self.timestamps = enabled;
+        self
+    }
+
+    /// Sets whether the positions of locations are rendered as OSC 8 hyperlinks.
+    ///
+    /// See `set_hyperlinks_enabled` function for more details.
+    pub fn with_hyperlinks(mut self, enabled: bool) -> Self {
+        self.hyperlinks = enabled;
+        self
+    }
+
+    /// Returns the maximum number of the events rendered in a history as `(first, last)`.
+    pub fn frame_limit(&self) -> Option<(usize, usize)> {
+        self.frame_limit
+    }
+
+    /// Returns `true` if the prefixes of the file names are stripped.
+    pub fn strip_paths(&self) -> bool {
+        self.strip_paths
+    }
+
+    /// Returns `true` if errors are rendered with ANSI colors.
+    pub fn color(&self) -> bool {
+        self.color
+    }
+
+    /// Returns `true` if histories are rendered newest-first.
+    pub fn newest_first(&self) -> bool {
+        self.newest_first
+    }
+
+    /// Returns `true` if the time of each location is recorded and rendered.
+    pub fn timestamps(&self) -> bool {
+        self.timestamps
+    }
+
+    /// Returns `true` if the positions of locations are rendered as OSC 8 hyperlinks.
+    pub fn hyperlinks(&self) -> bool {
+        self.hyperlinks
+    }
+
+    /// Applies the settings written in the syntax of the `TRACKABLE_FORMAT` environment variable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::fmt::Config;
+    ///
+    /// let config = Config::new().with_overrides("frames=2:1, order=newest-first").unwrap();
+    /// assert_eq!(config.frame_limit(), Some((2, 1)));
+    /// assert!(config.newest_first());
+    ///
+    /// assert!(Config::new().with_overrides("frames=many").is_err());
+    /// ```
+    pub fn with_overrides(self, overrides: &str) -> Result<Self, Failure> {
+        let settings = track!(parse_settings(overrides).map_err(Failure::from_error))?;
+        Ok(self.apply(&settings))
+    }
+
+    fn apply(mut self, settings: &[Setting]) -> Self {
+        for setting in settings {
+            match *setting {
+                Setting::FrameLimit(limit) => self.frame_limit = limit,
+                Setting::StripPaths(enabled) => self.strip_paths = enabled,
+                Setting::Color(enabled) => self.color = enabled,
+                Setting::NewestFirst(enabled) => self.newest_first = enabled,
+                Setting::Timestamps(enabled) => self.timestamps = enabled,
+                Setting::Hyperlinks(enabled) => self.hyperlinks = enabled,
+            }
+        }
+        self
+    }
+}
+impl Default for Config {
+    fn default() -> Self {
+        Config::new()
+    }
+}
+
+static CONFIG: RwLock<Config> = RwLock::new(Config::new());
+
+static ENV_SETTINGS: OnceLock<Vec<Setting>> = OnceLock::new();
+
+// A cache of `config().timestamps()`, which is consulted every time a `Location` is created.
+static TIMESTAMPS: AtomicBool = AtomicBool::new(false);
+
+/// Sets the global configuration.
+///
+/// Note that the settings specified by the `TRACKABLE_FORMAT` environment variable take precedence.
+pub fn set_config(config: Config) {
+    *CONFIG.write().unwrap_or_else(|e| e.into_inner()) = config;
+    sync_timestamps();
+}
+
+/// Returns the effective global configuration
+/// (i.e., the one set by `set_config` with the overrides by the `TRACKABLE_FORMAT` environment variable).
+pub fn config() -> Config {
+    let config = *CONFIG.read().unwrap_or_else(|e| e.into_inner());
+    config.apply(ENV_SETTINGS.get_or_init(env_settings))
+}
+
+pub(crate) fn update_config<F>(f: F)
+where
+    F: FnOnce(&mut Config),
+{
+    f(&mut CONFIG.write().unwrap_or_else(|e| e.into_inner()));
+    sync_timestamps();
+}
+
+/// Returns `config().timestamps()` without taking any locks (once the environment variable has been read).
+pub(crate) fn timestamps_enabled() -> bool {
+    if ENV_SETTINGS.get().is_none() {
+        sync_timestamps();
+    }
+    TIMESTAMPS.load(Ordering::Relaxed)
+}
+
+fn sync_timestamps() {
+    TIMESTAMPS.store(config().timestamps(), Ordering::Relaxed);
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Setting {
+    FrameLimit(Option<(usize, usize)>),
+    StripPaths(bool),
+    Color(bool),
+    NewestFirst(bool),
+    Timestamps(bool),
+    Hyperlinks(bool),
+}
+
+// Note that this function is called while initializing `ENV_SETTINGS`,
+// so it (and `parse_settings`) must not create `Location`s (e.g., via `track!` macros),
+// which would consult the configuration again.
+fn env_settings() -> Vec<Setting> {
+    std::env::var(ENV_VAR)
+        .ok()
+        .and_then(|s| parse_settings(&s).ok())
+        .unwrap_or_default()
+}
+
+fn parse_settings(s: &str) -> Result<Vec<Setting>, String> {
+    let mut settings = Vec::new();
+    for item in s.split(',').map(str::trim).filter(|i| !i.is_empty()) {
+        let (key, value) = item
+            .split_once('=')
+            .ok_or_else(|| format!("Not a key-value pair: {:?}", item))?;
+        let (key, value) = (key.trim(), value.trim());
+        let bool_value = || parse_bool(key, value);
+        let setting = match key {
+            "frames" if value == "all" => Setting::FrameLimit(None),
+            "frames" => {
+                let limit = value
+                    .split_once(':')
+                    .and_then(|(a, b)| Some((a.parse().ok()?, b.parse().ok()?)))
+                    .ok_or_else(|| format!("Malformed frame limit: {:?}", value))?;
+                Setting::FrameLimit(Some(limit))
+            }
+            "strip-paths" => Setting::StripPaths(bool_value()?),
+            "color" => Setting::Color(bool_value()?),
+            "order" => match value {
+                "oldest-first" => Setting::NewestFirst(false),
+                "newest-first" => Setting::NewestFirst(true),
+                _ => return Err(format!("Unknown order: {:?}", value)),
+            },
+            "timestamps" => Setting::Timestamps(bool_value()?),
+            "hyperlinks" => Setting::Hyperlinks(bool_value()?),
+            _ => return Err(format!("Unknown key: {:?}", key)),
+        };
+        settings.push(setting);
+    }
+    Ok(settings)
+}
+
+fn parse_bool(key: &str, value: &str) -> Result<bool, String> {
+    match value {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err(format!("Not a boolean: key={:?}, value={:?}", key, value)),
+    }
+}
+
+/// ANSI escape sequences decorating the parts of a rendered error.
+///
+/// Every sequence is empty in the plain style (see the `color` module for the colored one).
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Style {
+    pub kind: &'static str,
+    pub index: &'static str,
+    pub position: &'static str,
+    pub message: &'static str,
+    pub note: &'static str,
+    pub reset: &'static str,
+}
+impl Style {
+    pub const PLAIN: Style = Style {
+        kind: "",
+        index: "",
+        position: "",
+        message: "",
+        note: "",
+        reset: "",
+    };
+}
+
+/// Renders a `SystemTime` in RFC 3339 format (UTC, millisecond precision).
+pub(crate) struct Timestamp(pub SystemTime);
+impl std::fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let millis = match self.0.duration_since(UNIX_EPOCH) {
+            Ok(d) => d.as_millis() as i64,
+            Err(e) => -(e.duration().as_millis() as i64),
+        };
+        let secs = millis.div_euclid(1000);
+        let (days, secs_of_day) = (secs.div_euclid(86_400), secs.rem_euclid(86_400));
+        let (year, month, day) = civil_from_days(days);
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+            year,
+            month,
+            day,
+            secs_of_day / 3600,
+            secs_of_day / 60 % 60,
+            secs_of_day % 60,
+            millis.rem_euclid(1000)
+        )
+    }
+}
+
+/// Parses a timestamp rendered by `Timestamp`.
+pub(crate) fn parse_timestamp(s: &str) -> Option<SystemTime> {
+    let s = s.strip_suffix('Z')?;
+    let (date, time) = s.split_once('T')?;
+    let mut date = date.splitn(3, '-').map(str::parse::<i64>);
+    let (year, month, day) = (date.next()?.ok()?, date.next()?.ok()?, date.next()?.ok()?);
+    let (hms, millis) = time.split_once('.')?;
+    let mut hms = hms.splitn(3, ':').map(str::parse::<i64>);
+    let (h, m, sec) = (hms.next()?.ok()?, hms.next()?.ok()?, hms.next()?.ok()?);
+    let millis = millis.parse::<i64>().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || h > 23 || m > 59 || sec > 59 {
+        return None;
+    }
+
+    let secs = days_from_civil(year, month, day) * 86_400 + h * 3600 + m * 60 + sec;
+    let millis = secs * 1000 + millis;
+    let duration = Duration::from_millis(millis.unsigned_abs());
+    if millis < 0 {
+        UNIX_EPOCH.checked_sub(duration)
+    } else {
+        UNIX_EPOCH.checked_add(duration)
+    }
+}
+
+// See http://howardhinnant.github.io/date_algorithms.html
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = year - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_settings_works() {
+        let config = Config::new()
+            .with_overrides(
+                "frames=3:1,strip-paths=false,color=true,order=newest-first,\
+                 timestamps=true,hyperlinks=true",
+            )
+            .unwrap();
+        assert_eq!(
+            config,
+            Config::new()
+                .with_frame_limit(Some((3, 1)))
+                .with_strip_paths(false)
+                .with_color(true)
+                .with_newest_first(true)
+                .with_timestamps(true)
+                .with_hyperlinks(true)
+        );
+
+        let config = config.with_overrides(" frames = all ,, ").unwrap();
+        assert_eq!(config.frame_limit(), None);
+
+        for s in ["frames=3", "color=yes", "order=random", "foo=bar", "color"] {
+            assert!(Config::new().with_overrides(s).is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn timestamp_roundtrip_works() {
+        for &(millis, text) in &[
+            (0_i64, "1970-01-01T00:00:00.000Z"),
+            (951_782_400_123, "2000-02-29T00:00:00.123Z"),
+            (1_704_164_645_678, "2024-01-02T03:04:05.678Z"),
+            (-1, "1969-12-31T23:59:59.999Z"),
+        ] {
+            let duration = Duration::from_millis(millis.unsigned_abs());
+            let time = if millis < 0 {
+                UNIX_EPOCH - duration
+            } else {
+                UNIX_EPOCH + duration
+            };
+            assert_eq!(Timestamp(time).to_string(), text);
+            assert_eq!(parse_timestamp(text), Some(time));
+        }
+        assert_eq!(parse_timestamp("2024-13-02T03:04:05.678Z"), None);
+        assert_eq!(parse_timestamp("thread 'main'"), None);
+    }
+}
@@ -0,0 +1,217 @@
+//! Trackable adapters for futures.
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use super::{Location, Trackable};
+
+/// A future which tracks the location where it is awaited.
+///
+/// When the inner future completes with a value that is in tracking
+/// (e.g., an `Err` value of `Result<_, TrackableError<_>>`),
+/// the location is tracked into the history of the value.
+///
+/// Typically, this is created via [track_await!](../macro.track_await.html) macro.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct TrackFuture<F> {
+    future: F,
+    location: Option<Location>,
+}
+impl<F> TrackFuture<F> {
+    /// Makes a new `TrackFuture` instance.
+    pub fn new(future: F, location: Location) -> Self {
+        TrackFuture {
+            future,
+            location: Some(location),
+        }
+    }
+
+    /// Returns the reference to the inner future.
+    pub fn get_ref(&self) -> &F {
+        &self.future
+    }
+}
+impl<F> Future for TrackFuture<F>
+where
+    F: Future,
+    F::Output: Trackable,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        // SAFETY: `future` is structurally pinned and never moved out of `self`.
+        let this = unsafe { self.get_unchecked_mut() };
+        let future = unsafe { Pin::new_unchecked(&mut this.future) };
+        let mut poll = future.poll(cx);
+        if poll.is_ready() {
+            if let Some(location) = this.location.take() {
+                poll.track(|| From::from(location));
+            }
+        }
+        poll
+    }
+}
+
+/// A future which may have already been completed (used by the `track_join!` macro).
+#[doc(hidden)]
+#[derive(Debug)]
+pub enum MaybeDone<F: Future> {
+    Pending(Pin<Box<F>>),
+    Done(Option<F::Output>),
+}
+impl<F: Future> MaybeDone<F> {
+    /// Makes a new `MaybeDone` instance.
+    pub fn new(future: F) -> Self {
+        MaybeDone::Pending(Box::pin(future))
+    }
+
+    /// Polls the inner future and returns `true` if it has been completed.
+    fn poll_done(&mut self, cx: &mut Context) -> bool {
+        if let MaybeDone::Pending(ref mut future) = *self {
+            match future.as_mut().poll(cx) {
+                Poll::Pending => return false,
+                Poll::Ready(output) => *self = MaybeDone::Done(Some(output)),
+            }
+        }
+        true
+    }
+
+    fn take_output(&mut self) -> F::Output {
+        match *self {
+            MaybeDone::Done(ref mut output) => output.take().expect("polled after completion"),
+            MaybeDone::Pending(_) => unreachable!(),
+        }
+    }
+}
+
+/// A helper trait for the `track_join!` macro.
+///
+/// This is implemented for tuples (up to 8 elements) of `MaybeDone`s.
+#[doc(hidden)]
+pub trait TrackJoin {
+    type Output;
+
+    /// Polls all the pending futures and returns the tuple of their outputs if all of them have been completed.
+    fn poll_join(&mut self, cx: &mut Context) -> Poll<Self::Output>;
+}
+macro_rules! impl_track_join {
+    ($($t:ident $i:tt),+) => {
+        impl<$($t: Future),+> TrackJoin for ($(MaybeDone<$t>,)+) {
+            type Output = ($($t::Output,)+);
+
+            fn poll_join(&mut self, cx: &mut Context) -> Poll<Self::Output> {
+                let mut done = true;
+                $(done &= self.$i.poll_done(cx);)+
+                if done {
+                    Poll::Ready(($(self.$i.take_output(),)+))
+                } else {
+                    Poll::Pending
+                }
+            }
+        }
+    };
+}
+impl_track_join!(A 0);
+impl_track_join!(A 0, B 1);
+impl_track_join!(A 0, B 1, C 2);
+impl_track_join!(A 0, B 1, C 2, D 3);
+impl_track_join!(A 0, B 1, C 2, D 3, F 4);
+impl_track_join!(A 0, B 1, C 2, D 3, F 4, G 5);
+impl_track_join!(A 0, B 1, C 2, D 3, F 4, G 5, H 6);
+impl_track_join!(A 0, B 1, C 2, D 3, F 4, G 5, H 6, I 7);
+
+#[cfg(test)]
+mod test {
+    use std::future::{self, Future};
+    use std::pin::Pin;
+    use std::ptr;
+    use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+    use error::{ErrorKindExt, Failed, Failure};
+    use Trackable;
+
+    fn noop_waker() -> Waker {
+        fn clone(_: *const ()) -> RawWaker {
+            RawWaker::new(ptr::null(), &VTABLE)
+        }
+        fn noop(_: *const ()) {}
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+        unsafe { Waker::from_raw(RawWaker::new(ptr::null(), &VTABLE)) }
+    }
+
+    #[test]
+    fn track_await_works() {
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        let ok: Result<(), Failure> = Ok(());
+        let mut f = track_await!(future::ready(ok));
+        assert!(Pin::new(&mut f).poll(&mut cx).is_ready());
+
+        let err: Result<(), Failure> = Err(Failed.error().into());
+        let mut f = track_await!(future::ready(err), "waiting {}", "foo");
+        match Pin::new(&mut f).poll(&mut cx) {
+            Poll::Ready(Err(e)) => {
+                let events = e.history().unwrap().events();
+                assert_eq!(events.len(), 1);
+                assert_eq!(events[0].message(), "waiting foo");
+            }
+            _ => panic!(),
+        }
+    }
+
+    /// A future which is completed at the second poll.
+    struct YieldOnce<T>(Option<T>, bool);
+    impl<T: Unpin> Future for YieldOnce<T> {
+        type Output = T;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<T> {
+            if self.1 {
+                Poll::Ready(self.0.take().unwrap())
+            } else {
+                self.1 = true;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+    }
+
+    #[test]
+    fn track_join_works() {
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        let a = YieldOnce(Some(Ok::<_, Failure>(1)), false);
+        let b = future::ready(Ok::<_, Failure>("foo"));
+        let mut f = track_join!(a, b);
+        assert!(Pin::new(&mut f).poll(&mut cx).is_pending());
+        match Pin::new(&mut f).poll(&mut cx) {
+            Poll::Ready(Ok(values)) => assert_eq!(values, (1, "foo")),
+            _ => panic!(),
+        }
+
+        let a = future::ready(Err::<(), Failure>(track!(Failed.error()).into()));
+        let b = YieldOnce(Some(Ok::<_, Failure>(2)), false);
+        let c = YieldOnce(
+            Some(Err::<(), Failure>(track!(Failed.error()).into())),
+            false,
+        );
+        let mut f = track_join!(a, b, c);
+        assert!(Pin::new(&mut f).poll(&mut cx).is_pending());
+        match Pin::new(&mut f).poll(&mut cx) {
+            Poll::Ready(Err(e)) => {
+                let history = e.history().unwrap();
+                assert_eq!(history.events().len(), 2);
+                assert_eq!(
+                    history.events()[1].message(),
+                    "branch #0 failed (succeeded: [1], failed: [0, 2])"
+                );
+                assert_eq!(history.branches().len(), 1);
+                assert_eq!(history.branches()[0].label(), "branch #2");
+                assert_eq!(history.branches()[0].history().events().len(), 1);
+            }
+            _ => panic!(),
+        }
+    }
+}
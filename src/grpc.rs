@@ -0,0 +1,228 @@
+//! Conversion from `TrackableError` to gRPC `Status` of [tonic](https://docs.rs/tonic).
+//!
+//! This module is available only if the `tonic` feature is enabled.
+use bytes::Bytes;
+use std::error::Error;
+use std::io;
+use tonic::{Code, Status};
+
+use error::{CommonErrorKind, DisplayKind, ErrorKind, Failed, TrackableError};
+use Trackable;
+
+/// An extension of `ErrorKind` trait for mapping error kinds to gRPC status codes.
+///
+/// `TrackableError<K>` can be converted into `tonic::Status` if `K` implements this trait.
+/// The message of the resulting status consists of the kind, the code and the cause of the error.
+/// The tracking history is not included since it may reveal the internals of the server
+/// (use [`status_with_debug_info`] to include it).
+///
+/// [`status_with_debug_info`]: fn.status_with_debug_info.html
+///
+/// # Examples
+///
+/// ```
+/// extern crate tonic;
+/// extern crate trackable;
+///
+/// use trackable::error::{ErrorKind, ErrorKindExt};
+/// use trackable::grpc::GrpcErrorKind;
+///
+/// #[derive(Debug)]
+/// enum MyErrorKind {
+///     NotFound,
+///     Other,
+/// }
+/// impl ErrorKind for MyErrorKind {}
+/// impl GrpcErrorKind for MyErrorKind {
+///     fn grpc_code(&self) -> tonic::Code {
+///         match *self {
+///             MyErrorKind::NotFound => tonic::Code::NotFound,
+///             MyErrorKind::Other => tonic::Code::Internal,
+///         }
+///     }
+/// }
+///
+/// # fn main() {
+/// let status = tonic::Status::from(MyErrorKind::NotFound.cause("no such user"));
+/// assert_eq!(status.code(), tonic::Code::NotFound);
+/// assert_eq!(status.message(), "NotFound (cause; no such user)");
+/// # }
+/// ```
+pub trait GrpcErrorKind: ErrorKind {
+    /// Returns the gRPC status code corresponding to this kind.
+    ///
+    /// The default implementation always returns `Code::Internal`.
+    fn grpc_code(&self) -> Code {
+        Code::Internal
+    }
+}
+impl GrpcErrorKind for Failed {}
+impl GrpcErrorKind for CommonErrorKind {
+    fn grpc_code(&self) -> Code {
+        match *self {
+            CommonErrorKind::InvalidInput => Code::InvalidArgument,
+            CommonErrorKind::NotFound => Code::NotFound,
+            CommonErrorKind::PermissionDenied => Code::PermissionDenied,
+            CommonErrorKind::Timeout => Code::DeadlineExceeded,
+            CommonErrorKind::Unavailable => Code::Unavailable,
+            CommonErrorKind::Internal => Code::Internal,
+            CommonErrorKind::Cancelled => Code::Cancelled,
+        }
+    }
+}
+impl GrpcErrorKind for io::ErrorKind {
+    fn grpc_code(&self) -> Code {
+        match *self {
+            io::ErrorKind::InvalidInput | io::ErrorKind::InvalidData => Code::InvalidArgument,
+            io::ErrorKind::PermissionDenied => Code::PermissionDenied,
+            io::ErrorKind::NotFound => Code::NotFound,
+            io::ErrorKind::AlreadyExists => Code::AlreadyExists,
+            io::ErrorKind::TimedOut => Code::DeadlineExceeded,
+            _ => Code::Internal,
+        }
+    }
+}
+
+impl<K: GrpcErrorKind> From<TrackableError<K>> for Status {
+    fn from(f: TrackableError<K>) -> Self {
+        Status::new(f.kind().grpc_code(), status_message(&f))
+    }
+}
+
+/// Converts `error` into a `Status` whose details contain the tracking history of the error.
+///
+/// The details are a binary-encoded `google.rpc.Status` message
+/// (i.e., the standard payload of the `grpc-status-details-bin` metadata),
+/// which has a `google.rpc.DebugInfo` message as its only detail.
+/// The stack entries of the `DebugInfo` are the events in the history (oldest first),
+/// and the detail is the cause of the error.
+///
+/// Note that the history reveals the internals of the server (e.g., file paths),
+/// so this should be used only if the clients are trusted.
+///
+/// # Examples
+///
+/// ```
+/// extern crate tonic;
+/// #[macro_use]
+/// extern crate trackable;
+///
+/// use trackable::error::{CommonErrorKind, ErrorKindExt};
+/// use trackable::grpc;
+///
+/// # fn main() {
+/// let status = grpc::status_with_debug_info(track!(CommonErrorKind::NotFound.cause("no such user")));
+/// assert_eq!(status.code(), tonic::Code::NotFound);
+/// assert_eq!(status.message(), "NotFound (cause; no such user)");
+/// assert!(!status.details().is_empty());
+///
+/// // The plain conversion does not include the details
+/// let status = tonic::Status::from(CommonErrorKind::NotFound.cause("no such user"));
+/// assert!(status.details().is_empty());
+/// # }
+/// ```
+pub fn status_with_debug_info<K, E>(error: E) -> Status
+where
+    K: GrpcErrorKind,
+    E: Into<TrackableError<K>>,
+{
+    let error = error.into();
+    let code = error.kind().grpc_code();
+    let message = status_message(&error);
+
+    let mut debug_info = Vec::new();
+    for event in error.history().map_or(&[][..], |h| h.events()) {
+        encode_bytes(&mut debug_info, 1, event.to_string().as_bytes());
+    }
+    if let Some(cause) = error.source() {
+        encode_bytes(&mut debug_info, 2, cause.to_string().as_bytes());
+    }
+
+    let mut any = Vec::new();
+    encode_bytes(&mut any, 1, DEBUG_INFO_TYPE_URL.as_bytes());
+    encode_bytes(&mut any, 2, &debug_info);
+
+    let mut details = Vec::new();
+    if code != Code::Ok {
+        encode_varint(&mut details, 1 << 3);
+        encode_varint(&mut details, i32::from(code) as u64);
+    }
+    encode_bytes(&mut details, 2, message.as_bytes());
+    encode_bytes(&mut details, 3, &any);
+    Status::with_details(code, message, Bytes::from(details))
+}
+
+const DEBUG_INFO_TYPE_URL: &str = "type.googleapis.com/google.rpc.DebugInfo";
+
+fn status_message<K: ErrorKind>(error: &TrackableError<K>) -> String {
+    let kind = error.kind();
+    let mut message = DisplayKind(kind).to_string();
+    if let Some(code) = kind.code() {
+        message.push_str(&format!(" [{}]", code));
+    }
+    if let Some(cause) = error.source() {
+        message.push_str(&format!(" (cause; {})", cause));
+    }
+    message
+}
+
+/// Encodes a length-delimited field of a protobuf message (empty values are omitted).
+fn encode_bytes(buf: &mut Vec<u8>, field: u64, value: &[u8]) {
+    if value.is_empty() {
+        return;
+    }
+    encode_varint(buf, (field << 3) | 2);
+    encode_varint(buf, value.len() as u64);
+    buf.extend_from_slice(value);
+}
+
+fn encode_varint(buf: &mut Vec<u8>, mut n: u64) {
+    while n >= 0x80 {
+        buf.push((n as u8) | 0x80);
+        n >>= 7;
+    }
+    buf.push(n as u8);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use error::ErrorKindExt;
+
+    #[test]
+    fn status_from_trackable_error_works() {
+        let status = Status::from(track!(io::ErrorKind::NotFound.cause("oops")));
+        assert_eq!(status.code(), Code::NotFound);
+        assert_eq!(status.message(), "NotFound (cause; oops)");
+        assert!(status.details().is_empty());
+    }
+
+    #[test]
+    fn status_with_debug_info_works() {
+        let e = io::ErrorKind::NotFound.cause("oops");
+        let e = track!(e, "foo");
+        let history = e.history().unwrap().events()[0].to_string();
+        let status = status_with_debug_info(e);
+        assert_eq!(status.code(), Code::NotFound);
+        assert_eq!(status.message(), "NotFound (cause; oops)");
+
+        let mut debug_info = vec![0x0a, history.len() as u8];
+        debug_info.extend_from_slice(history.as_bytes());
+        debug_info.extend_from_slice(b"\x12\x04oops");
+        let mut any = vec![0x0a, DEBUG_INFO_TYPE_URL.len() as u8];
+        any.extend_from_slice(DEBUG_INFO_TYPE_URL.as_bytes());
+        any.extend_from_slice(&[0x12, debug_info.len() as u8]);
+        any.extend_from_slice(&debug_info);
+        let mut expected = b"\x08\x05\x12\x16NotFound (cause; oops)".to_vec();
+        expected.extend_from_slice(&[0x1a, any.len() as u8]);
+        expected.extend_from_slice(&any);
+        assert_eq!(status.details(), &expected[..]);
+    }
+
+    #[test]
+    fn encode_varint_works() {
+        let mut buf = Vec::new();
+        encode_varint(&mut buf, 300);
+        assert_eq!(buf, [0xac, 0x02]);
+    }
+}
@@ -0,0 +1,96 @@
+//! HTTP status mapping for error kinds.
+//!
+//! This module is available only if the `http` feature is enabled.
+use std::io;
+
+use error::{CommonErrorKind, DisplayKind, ErrorKind, Failed, TrackableError};
+
+/// An extension of `ErrorKind` trait for mapping error kinds to HTTP status codes.
+///
+/// # Examples
+///
+/// ```
+/// use trackable::error::{ErrorKind, ErrorKindExt};
+/// use trackable::http::{self, HttpErrorKind};
+///
+/// #[derive(Debug)]
+/// enum MyErrorKind {
+///     NotFound,
+///     Other,
+/// }
+/// impl ErrorKind for MyErrorKind {}
+/// impl HttpErrorKind for MyErrorKind {
+///     fn status(&self) -> u16 {
+///         match *self {
+///             MyErrorKind::NotFound => 404,
+///             MyErrorKind::Other => 500,
+///         }
+///     }
+/// }
+///
+/// let error = MyErrorKind::NotFound.cause("no such user: `foo`");
+/// assert_eq!(http::response(&error), (404, "NotFound".to_owned()));
+/// ```
+pub trait HttpErrorKind: ErrorKind {
+    /// Returns the HTTP status code corresponding to this kind.
+    ///
+    /// The default implementation always returns `500` (Internal Server Error).
+    fn status(&self) -> u16 {
+        500
+    }
+}
+impl HttpErrorKind for Failed {}
+impl HttpErrorKind for CommonErrorKind {
+    fn status(&self) -> u16 {
+        match *self {
+            CommonErrorKind::InvalidInput => 400,
+            CommonErrorKind::PermissionDenied => 403,
+            CommonErrorKind::NotFound => 404,
+            CommonErrorKind::Timeout => 504,
+            CommonErrorKind::Unavailable => 503,
+            CommonErrorKind::Internal => 500,
+            CommonErrorKind::Cancelled => 499,
+        }
+    }
+}
+impl HttpErrorKind for io::ErrorKind {
+    fn status(&self) -> u16 {
+        match *self {
+            io::ErrorKind::InvalidInput | io::ErrorKind::InvalidData => 400,
+            io::ErrorKind::PermissionDenied => 403,
+            io::ErrorKind::NotFound => 404,
+            io::ErrorKind::AlreadyExists => 409,
+            io::ErrorKind::TimedOut => 504,
+            _ => 500,
+        }
+    }
+}
+
+/// Converts `error` into a pair of an HTTP status code and a sanitized response body.
+///
+/// The body consists of the kind (and the code if it has) of the error.
+/// The cause and the tracking history are not included in the body,
+/// because those may contain sensitive information.
+pub fn response<K: HttpErrorKind>(error: &TrackableError<K>) -> (u16, String) {
+    let kind = error.kind();
+    let mut body = DisplayKind(kind).to_string();
+    if let Some(code) = kind.code() {
+        body.push_str(&format!(" [{}]", code));
+    }
+    (kind.status(), body)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use error::ErrorKindExt;
+
+    #[test]
+    fn response_works() {
+        let error = track!(Failed.cause("secret"));
+        assert_eq!(response(&error), (500, "Failed".to_owned()));
+
+        let error = io::ErrorKind::NotFound.cause("/path/to/secret");
+        assert_eq!(response(&error), (404, "NotFound".to_owned()));
+    }
+}
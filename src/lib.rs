@@ -27,12 +27,23 @@
 //!     assert!(result.is_err());
 //!
 //!     let error = result.err().unwrap();
+//!
+//!     // `{}` renders a concise report that shows only the first and the last frames
 //!     assert_eq!(format!("\r{}", error).replace('\\', "/"), r#"
 //! Failed (cause; No such file or directory)
 //! HISTORY:
-//!   [0] at src/lib.rs:7
-//!   [1] at src/lib.rs:12
-//!   [2] at src/lib.rs:16
+//!   [0] at src/lib.rs:7:5
+//!   [2] at src/lib.rs:16:5
+//!   ... and 1 more frame
+//! "#);
+//!
+//!     // `{:#}` renders the full report
+//!     assert_eq!(format!("\r{:#}", error).replace('\\', "/"), r#"
+//! Failed (cause; No such file or directory)
+//! HISTORY:
+//!   [0] at src/lib.rs:7:5 in rust_out::foo
+//!   [1] at src/lib.rs:12:5 in rust_out::bar
+//!   [2] at src/lib.rs:16:5 in rust_out::baz
 //! "#);
 //! }
 //! ```
@@ -41,21 +52,131 @@
 //! but you can easily define your own trackable error types.
 //! See the documentaion of [error](error/index.html) module for more details.
 #![warn(missing_docs)]
+#![cfg_attr(trackable_nightly, feature(error_generic_member_access))]
 
-#[cfg(feature = "serialize")]
-extern crate serde;
-#[cfg(feature = "serialize")]
+#[cfg(feature = "tonic")]
+extern crate bytes;
+#[cfg(feature = "cbor")]
+extern crate ciborium;
+#[cfg(feature = "trackable-eyre")]
+extern crate eyre;
+#[cfg(feature = "msgpack")]
+extern crate rmp_serde;
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+pub extern crate serde;
+#[cfg(feature = "smallvec")]
+extern crate smallvec;
+#[cfg(feature = "serde")]
 #[macro_use]
 extern crate serde_derive;
+#[cfg(feature = "tonic")]
+extern crate tonic;
 #[macro_use]
 extern crate trackable_derive;
+#[cfg(feature = "source-snippets")]
+#[doc(hidden)]
+pub extern crate trackable_snippet;
 
+use std::any::Any;
 use std::borrow::Cow;
-use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
 use std::task::Poll;
+use std::time::SystemTime;
 
-#[doc(hidden)]
-pub use trackable_derive::*;
+pub use trackable_derive::{ErrorKind, TrackableError};
+
+/// Tracks every error propagated by `?` in the annotated function.
+///
+/// Each `$expr?` in the function body is expanded to the code that converts the error
+/// into the error type of the function (as `?` does) and then tracks the location of the `?`
+/// if the resulting value is `Trackable`.
+/// Thus, the histories of the errors are complete without wrapping each propagation point by `track!`.
+///
+/// `?` operators in closures, async blocks and macro invocations (e.g., `println!("{}", foo()?)`)
+/// are left as they are.
+/// If the function returns `Option<_>`, the function is left as it is.
+///
+/// Note that `#[trackable(..)]` is also used as the helper attribute of the derive macros.
+/// Both can be used together since derive helper attributes take precedence
+/// on the items that derive `TrackableError` or `ErrorKind`.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate trackable;
+///
+/// use trackable::error::Failure;
+///
+/// #[trackable]
+/// fn parse(s: &str) -> Result<u8, Failure> {
+///     let n = s.parse::<u8>().map_err(Failure::from_error)?;
+///     Ok(n)
+/// }
+///
+/// #[trackable]
+/// fn double(s: &str) -> Result<u8, Failure> {
+///     let n = parse(s)?;
+///     Ok(n.saturating_mul(2))
+/// }
+///
+/// fn main() {
+///     assert_eq!(double("3").ok(), Some(6));
+///
+///     let e = double("foo").err().unwrap();
+///     assert_eq!(format!("\n{}", e).replace('\\', "/"), r#"
+/// Failed (cause; invalid digit found in string)
+/// HISTORY:
+///   [0] at src/lib.rs:8:57
+///   [1] at src/lib.rs:14:21
+/// "#);
+/// }
+/// ```
+pub use trackable_derive::trackable;
+
+/// Reports the error returned by the annotated `main` function in a readable form.
+///
+/// If the function returns an error, the kind, the cause and the history of the error are
+/// printed to the standard error (by [`result::report`]) and the process exits with a non-zero code,
+/// instead of printing the `Debug` representation of the error.
+/// The return type of the function can be any `Result` whose error type implements `Display`.
+///
+/// If `color` is specified (i.e., `#[trackable::main(color)]`), the report is colored
+/// when the standard error is a terminal (see [`color::enabled`]).
+/// This requires the `color` feature.
+///
+/// This can be combined with [`#[trackable]`](attr.trackable.html) to track every `?` in the function.
+///
+/// [`result::report`]: result/fn.report.html
+/// [`color::enabled`]: color/fn.enabled.html
+///
+/// # Examples
+///
+/// ```no_run
+/// extern crate trackable;
+///
+/// use trackable::error::Failure;
+/// use trackable::trackable;
+///
+/// #[trackable::main]
+/// #[trackable]
+/// fn main() -> Result<(), Failure> {
+///     std::fs::File::open("/path/to/non_existent_file").map_err(Failure::from_error)?;
+///     Ok(())
+/// }
+/// ```
+///
+/// The above program exits with the code `1` after printing a report like the following:
+///
+/// ```text
+/// Error: Failed (cause; No such file or directory (os error 2))
+/// HISTORY:
+///   [0] at src/main.rs:9:83
+/// ```
+pub use trackable_derive::main;
 
 #[macro_use]
 mod macros;
@@ -65,8 +186,24 @@ mod trackable {
     pub use super::*;
 }
 
+#[cfg(any(feature = "msgpack", feature = "cbor"))]
+pub mod binary;
+#[cfg(feature = "color")]
+pub mod color;
 pub mod error;
+#[cfg(feature = "trackable-eyre")]
+pub mod eyre_handler;
+pub mod fmt;
+pub mod future;
+#[cfg(feature = "tonic")]
+pub mod grpc;
+#[cfg(feature = "http")]
+pub mod http;
+pub mod panic;
+mod parse;
+pub mod report;
 pub mod result;
+pub mod retry;
 
 /// This trait allows to track an instance of an implementation type.
 ///
@@ -109,9 +246,9 @@ pub mod result;
 ///
 ///     assert_eq!(format!("\n{}", o.history).replace('\\', "/"), r#"
 /// HISTORY:
-///   [0] at src/lib.rs:23
-///   [1] at src/lib.rs:24 -- Hello
-///   [2] at src/lib.rs:25 -- Hello World!
+///   [0] at src/lib.rs:23:13
+///   [1] at src/lib.rs:24:13 -- Hello
+///   [2] at src/lib.rs:25:13 -- Hello World!
 /// "#);
 /// }
 /// ```
@@ -132,6 +269,38 @@ pub trait Trackable {
         }
     }
 
+    /// Adds the location into the tail of the history of this instance.
+    ///
+    /// If the location is identical to the last event of the history (e.g., `track!` in a loop),
+    /// the repeat count of the event is incremented instead of adding a new event.
+    ///
+    /// Typically, this is called via [track!](macro.track.html) macro.
+    #[inline]
+    fn track_location<F>(&mut self, f: F)
+    where
+        F: FnOnce() -> Location,
+        Self::Event: HistoryEvent,
+    {
+        if let Some(h) = self.history_mut() {
+            h.add_location(f())
+        }
+    }
+
+    /// Adds the location of the caller of this method into the tail of the history of this instance.
+    ///
+    /// Unlike [track!](macro.track.html) macro, this can record the location of the caller of
+    /// a function annotated with `#[track_caller]`.
+    /// Note that the module path of the resulting location is always empty.
+    #[track_caller]
+    fn track_here(&mut self) {
+        let caller = std::panic::Location::caller();
+        self.track(|| {
+            From::from(
+                Location::new("", caller.file(), caller.line(), "").with_column(caller.column()),
+            )
+        });
+    }
+
     /// Returns `true` if it is being tracked, otherwise `false`.
     #[inline]
     fn in_tracking(&self) -> bool {
@@ -197,15 +366,304 @@ impl<T: Trackable> Trackable for Poll<T> {
     }
 }
 
+/// An extension of `Result` which provides methods for tracking.
+pub trait ResultExt: Sized {
+    /// The error type of this result.
+    type Error;
+
+    /// Tracks the location of the caller of this method if this is an `Err` value.
+    ///
+    /// This is useful in method chains and helper functions annotated with `#[track_caller]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::{ResultExt, Trackable};
+    /// use trackable::error::{Failed, Failure, ErrorKindExt};
+    ///
+    /// #[track_caller]
+    /// fn check(n: u32) -> Result<u32, Failure> {
+    ///     let result = if n > 0 { Ok(n) } else { Err(Failed.error().into()) };
+    ///     result.track_loc()
+    /// }
+    ///
+    /// let e = check(0).err().unwrap();
+    /// assert_eq!(e.history().unwrap().events()[0].line(), line!() - 1);
+    /// ```
+    #[track_caller]
+    fn track_loc(self) -> Self;
+
+    /// Adds a context message to the error if this is an `Err` value.
+    ///
+    /// `f` is called only if this is an `Err` value,
+    /// so building the message costs nothing on the success path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::ResultExt;
+    /// use trackable::error::{Failed, Failure, ErrorKindExt};
+    ///
+    /// let path = "/etc/foo.conf";
+    /// let result: Result<(), Failure> = Err(Failed.cause("permission denied").into());
+    /// let e = result.with_context(|| format!("while reading {}", path)).err().unwrap();
+    /// assert_eq!(e.contexts(), ["while reading /etc/foo.conf"]);
+    ///
+    /// let result: Result<(), Failure> = Ok(());
+    /// assert!(result.with_context(|| -> String { unreachable!() }).is_ok());
+    /// ```
+    fn with_context<C, F>(self, f: F) -> Self
+    where
+        Self::Error: error::ContextualError,
+        C: std::fmt::Display,
+        F: FnOnce() -> C;
+}
+impl<T, E: Trackable> ResultExt for Result<T, E> {
+    type Error = E;
+
+    #[track_caller]
+    fn track_loc(mut self) -> Self {
+        if let Err(ref mut e) = self {
+            e.track_here();
+        }
+        self
+    }
+
+    fn with_context<C, F>(mut self, f: F) -> Self
+    where
+        E: error::ContextualError,
+        C: std::fmt::Display,
+        F: FnOnce() -> C,
+    {
+        if let Err(ref mut e) = self {
+            e.add_context(f());
+        }
+        self
+    }
+}
+
+/// Strips the trailing segments added by `trackable_function_name!` from a type name.
+#[doc(hidden)]
+pub fn strip_function_name(name: &'static str) -> &'static str {
+    let mut name = name.trim_end_matches("::__trackable_function");
+    while name.ends_with("::{{closure}}") {
+        name = &name[..name.len() - "::{{closure}}".len()];
+    }
+    name
+}
+
+/// Helper traits for the `track_dbg!` macro and the `#[trackable]` attribute.
+///
+/// The method resolution selects `TrackDbgTrackable` if the target value is `Trackable`
+/// (since it does not need auto-referencing), and `TrackDbgOther` otherwise.
+#[doc(hidden)]
+pub trait TrackDbgTrackable {
+    #[inline]
+    fn trackable_dbg_tag(&self) -> TrackDbgTag {
+        TrackDbgTag
+    }
+}
+impl<T: Trackable> TrackDbgTrackable for T {}
+
+#[doc(hidden)]
+pub trait TrackDbgOther {
+    #[inline]
+    fn trackable_dbg_tag(&self) -> SkipDbgTag {
+        SkipDbgTag
+    }
+}
+impl<T: ?Sized> TrackDbgOther for &T {}
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct TrackDbgTag;
+impl TrackDbgTag {
+    #[inline]
+    pub fn track<T, F>(self, mut target: T, f: F) -> T
+    where
+        T: Trackable,
+        T::Event: HistoryEvent,
+        F: FnOnce() -> Location,
+    {
+        target.track_location(f);
+        target
+    }
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct SkipDbgTag;
+impl SkipDbgTag {
+    #[inline]
+    pub fn track<T, F>(self, target: T, _f: F) -> T
+    where
+        F: FnOnce() -> Location,
+    {
+        target
+    }
+}
+
+/// A helper trait for the `track_zip!` macro.
+///
+/// This is implemented for tuples (up to 8 elements) of `Result`s that have the same error type.
+#[doc(hidden)]
+pub trait TrackZip {
+    type Output;
+    type Error;
+
+    /// Returns the tuple of the `Ok` values, or the first error with
+    /// the indices of the succeeded and the failed results.
+    ///
+    /// The histories of the other errors are merged into the history of the first error as branches.
+    fn track_zip(self) -> Result<Self::Output, TrackZipError<Self::Error>>;
+}
+
+/// The first error and the indices of the succeeded and the failed results.
+#[doc(hidden)]
+pub type TrackZipError<E> = (E, Vec<usize>, Vec<usize>);
+
+/// Merges the history of the error of the `index`-th result into the history of `first`.
+fn merge_zipped_error<E: Trackable>(first: &mut E, mut other: E, index: usize) {
+    if let (Some(history), Some(other)) = (first.history_mut(), other.history_mut()) {
+        let other = std::mem::take(other);
+        history.merge_branch(format!("branch #{}", index), other);
+    }
+}
+macro_rules! impl_track_zip {
+    ($($t:ident $i:tt),+) => {
+        impl<E: Trackable, $($t),+> TrackZip for ($(Result<$t, E>,)+) {
+            type Output = ($($t,)+);
+            type Error = E;
+
+            #[allow(clippy::vec_init_then_push)]
+            fn track_zip(self) -> Result<Self::Output, TrackZipError<E>> {
+                let mut error = None;
+                let mut succeeded = Vec::new();
+                let mut failed = Vec::new();
+                let values = ($(match self.$i {
+                    Ok(v) => {
+                        succeeded.push($i);
+                        Some(v)
+                    }
+                    Err(e) => {
+                        failed.push($i);
+                        match error {
+                            None => error = Some(e),
+                            Some(ref mut first) => merge_zipped_error(first, e, $i),
+                        }
+                        None
+                    }
+                },)+);
+                if let Some(e) = error {
+                    Err((e, succeeded, failed))
+                } else {
+                    Ok(($(values.$i.expect("never fails"),)+))
+                }
+            }
+        }
+    };
+}
+impl_track_zip!(A 0);
+impl_track_zip!(A 0, B 1);
+impl_track_zip!(A 0, B 1, C 2);
+impl_track_zip!(A 0, B 1, C 2, D 3);
+impl_track_zip!(A 0, B 1, C 2, D 3, F 4);
+impl_track_zip!(A 0, B 1, C 2, D 3, F 4, G 5);
+impl_track_zip!(A 0, B 1, C 2, D 3, F 4, G 5, H 6);
+impl_track_zip!(A 0, B 1, C 2, D 3, F 4, G 5, H 6, I 7);
+
+// The storage of the events of a history.
+//
+// With the `smallvec` feature, short histories are stored inline and adding
+// their first few events does not allocate.
+#[cfg(feature = "smallvec")]
+type Events<Event> = smallvec::SmallVec<[Event; 4]>;
+#[cfg(not(feature = "smallvec"))]
+type Events<Event> = Vec<Event>;
+
+/// An event which can be recorded in a [history](struct.History.html).
+///
+/// The default methods treat an event as an opaque value.
+/// An event type which records a [location](struct.Location.html) can expose it by overriding them,
+/// so that `History::add_location` can deduplicate the repeated locations
+/// and the rendered history can show their threads and snippets.
+///
+/// # Examples
+///
+/// ```
+/// use trackable::{HistoryEvent, Location};
+///
+/// enum Event {
+///     Location(Location),
+///     Message(String),
+/// }
+/// impl HistoryEvent for Event {
+///     fn location(&self) -> Option<&Location> {
+///         match *self {
+///             Event::Location(ref l) => Some(l),
+///             Event::Message(_) => None,
+///         }
+///     }
+///     fn location_mut(&mut self) -> Option<&mut Location> {
+///         match *self {
+///             Event::Location(ref mut l) => Some(l),
+///             Event::Message(_) => None,
+///         }
+///     }
+/// }
+///
+/// let event = Event::Location(Location::new("foo", "src/foo.rs", 10, ""));
+/// assert_eq!(event.location().map(|l| l.line()), Some(10));
+/// assert!(Event::Message("bar".to_owned()).location().is_none());
+/// ```
+pub trait HistoryEvent {
+    /// Returns the location recorded by this event.
+    #[inline]
+    fn location(&self) -> Option<&Location> {
+        None
+    }
+
+    /// Returns the mutable reference of the location recorded by this event.
+    #[inline]
+    fn location_mut(&mut self) -> Option<&mut Location> {
+        None
+    }
+}
+impl HistoryEvent for Location {
+    #[inline]
+    fn location(&self) -> Option<&Location> {
+        Some(self)
+    }
+
+    #[inline]
+    fn location_mut(&mut self) -> Option<&mut Location> {
+        Some(self)
+    }
+}
+impl HistoryEvent for String {}
+impl HistoryEvent for &str {}
+impl HistoryEvent for Cow<'_, str> {}
+
+macro_rules! impl_history_event {
+    ($($t:ty),*) => {
+        $(impl HistoryEvent for $t {})*
+    };
+}
+impl_history_event!(bool, char, i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
 /// The tracking history of a target.
 ///
 /// A history is a sequence of the tracked events.
 ///
+/// If the `smallvec` feature is enabled, the first four events are stored inline
+/// instead of in a heap-allocated buffer.
+/// Note that this makes a history (and an error which contains it) larger.
+///
 /// # Examples
 ///
 /// ```
 /// use std::fmt::{Display, Formatter, Result};
-/// use trackable::History;
+/// use trackable::{History, HistoryEvent};
 ///
 /// struct Event(&'static str);
 /// impl Display for Event {
@@ -213,6 +671,7 @@ impl<T: Trackable> Trackable for Poll<T> {
 ///         write!(f, "event: {}", self.0)
 ///     }
 /// }
+/// impl HistoryEvent for Event {}
 ///
 /// let mut history = History::new();
 /// history.add(Event("foo"));
@@ -225,131 +684,1677 @@ impl<T: Trackable> Trackable for Poll<T> {
 /// "#);
 /// ```
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
-pub struct History<Event>(Vec<Event>);
+pub struct History<Event> {
+    events: Events<Event>,
+    state: Option<Box<HistoryState>>,
+    branches: Vec<Branch<Event>>,
+}
 impl<Event> History<Event> {
     /// Makes an empty history.
+    ///
+    /// The maximum number of the events is initialized by the value of `history_limit` function.
     #[inline]
     pub fn new() -> Self {
-        History(Vec::new())
-    }
-
-    /// Adds an event to the tail of this history.
-    #[inline]
-    pub fn add(&mut self, event: Event) {
-        self.0.push(event);
+        History::with_capacity(0)
     }
 
-    /// Returns the tracked events in this history.
+    /// Makes an empty history with the specified capacity.
+    ///
+    /// The maximum number of the events is initialized by the value of `history_limit` function.
     #[inline]
-    pub fn events(&self) -> &[Event] {
-        &self.0[..]
-    }
-}
-impl<Event: fmt::Display> fmt::Display for History<Event> {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        writeln!(f, "HISTORY:")?;
-        for (i, e) in self.events().iter().enumerate() {
-            writeln!(f, "  [{}] {}", i, e)?;
+    pub fn with_capacity(capacity: usize) -> Self {
+        History {
+            events: Events::with_capacity(capacity),
+            state: history_limit().map(HistoryState::with_limit),
+            branches: Vec::new(),
         }
-        Ok(())
-    }
-}
-impl<Event> Default for History<Event> {
-    #[inline]
-    fn default() -> Self {
-        History::new()
     }
-}
 
-/// The location of interest in source code files.
-///
-/// Typically this is created in the macros which defined in this crate.
-#[derive(Debug, Clone)]
-#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
-pub struct Location {
-    module_path: Cow<'static, str>,
-    file: Cow<'static, str>,
-    line: u32,
-    message: Cow<'static, str>,
-}
-impl Location {
-    /// Makes a new `Location` instance.
+    /// Makes an empty history that holds at most `limit` events.
+    ///
+    /// If the limit is exceeded, the first `limit / 2` events and
+    /// the last `limit - limit / 2` events are kept, and the events in the middle are omitted.
     ///
     /// # Examples
     ///
     /// ```
-    /// use trackable::Location;
+    /// use trackable::History;
     ///
-    /// let location = Location::new(module_path!(), file!(), line!(), "Hello".to_string());
-    /// assert_eq!(location.message(), "Hello");
+    /// let mut history = History::with_capacity_limit(4);
+    /// for i in 0..10 {
+    ///     history.add(i);
+    /// }
+    /// assert_eq!(history.events(), [0, 1, 8, 9]);
+    /// assert_eq!(history.omitted(), 6);
+    /// assert_eq!(format!("\n{}", history), r#"
+    /// HISTORY:
+    ///   [0] 0
+    ///   [1] 1
+    ///   ... 6 events omitted ...
+    ///   [8] 8
+    ///   [9] 9
+    /// "#);
     /// ```
     #[inline]
-    pub fn new<M, F, T>(module_path: M, file: F, line: u32, message: T) -> Self
-    where
-        M: Into<Cow<'static, str>>,
-        F: Into<Cow<'static, str>>,
-        T: Into<Cow<'static, str>>,
-    {
-        Location {
-            module_path: module_path.into(),
-            file: file.into(),
-            line,
-            message: message.into(),
+    pub fn with_capacity_limit(limit: usize) -> Self {
+        History {
+            events: Events::with_capacity(limit),
+            state: Some(HistoryState::with_limit(limit)),
+            branches: Vec::new(),
         }
     }
 
-    /// Gets the crate name of this location.
+    /// Adds an event to the tail of this history.
+    ///
+    /// If this history already holds the maximum number of events,
+    /// the oldest event after the kept head events is dropped.
     #[inline]
-    pub fn crate_name(&self) -> &str {
-        if let Some(module_path_end) = self.module_path.find(':') {
-            &self.module_path[..module_path_end]
-        } else {
-            self.module_path.as_ref()
+    pub fn add(&mut self, event: Event) {
+        if let Some(ref mut state) = self.state {
+            if let Some(limit) = state.limit {
+                if self.events.len() >= limit {
+                    state.omitted += 1;
+                    if limit == 0 {
+                        return;
+                    }
+                    state.omitted += state.remove_event(limit / 2);
+                    state.omitted_at = limit / 2;
+                    self.events.remove(limit / 2);
+                    shift_branches(&mut self.branches, limit / 2);
+                }
+            }
         }
+        self.events.push(event);
     }
 
-    /// Gets the module path of this location.
+    /// Returns the tracked events in this history.
+    ///
+    /// Note that the omitted events (if any) are not contained in the result,
+    /// and each compressed cycle (if any) is contained only once.
     #[inline]
-    pub fn module_path(&self) -> &str {
-        self.module_path.as_ref()
+    pub fn events(&self) -> &[Event] {
+        &self.events[..]
     }
 
-    /// Gets the file name of this location.
+    /// Returns an iterator over the tracked events in this history.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::{History, Location};
+    ///
+    /// let mut history = History::new();
+    /// history.add(Location::new("foo", "src/foo.rs", 10, "hello"));
+    /// history.add(Location::new("foo", "src/foo.rs", 20, "world"));
+    ///
+    /// assert_eq!(history.len(), 2);
+    /// assert_eq!(history.get(1).map(|e| e.line()), Some(20));
+    /// assert_eq!(
+    ///     history.iter().map(|e| e.message()).collect::<Vec<_>>(),
+    ///     ["hello", "world"]
+    /// );
+    /// ```
     #[inline]
-    pub fn file(&self) -> &str {
-        self.file.as_ref()
+    pub fn iter(&self) -> std::slice::Iter<'_, Event> {
+        self.events.iter()
     }
 
-    /// Gets the line of this location.
+    /// Returns the number of the tracked events in this history.
     #[inline]
-    pub fn line(&self) -> u32 {
-        self.line
+    pub fn len(&self) -> usize {
+        self.events.len()
     }
 
-    /// Gets the message left at this location.
+    /// Returns `true` if this history has no events, otherwise `false`.
     #[inline]
-    pub fn message(&self) -> &str {
-        self.message.as_ref()
-    }
-}
-impl fmt::Display for Location {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "at {}:{}", self.file(), self.line())?;
-        if !self.message().is_empty() {
-            write!(f, " -- {}", self.message())?;
-        }
-        Ok(())
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use error::Failure;
+    /// Returns the event at the given index.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&Event> {
+        self.events.get(index)
+    }
 
-    #[test]
-    fn it_works() {
+    /// Shortens this history, keeping the first `len` events and dropping the rest.
+    ///
+    /// Compressed cycles that contain the dropped events are also dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::History;
+    ///
+    /// let mut history = History::new();
+    /// for i in 0..5 {
+    ///     history.add(i);
+    /// }
+    ///
+    /// history.truncate(3);
+    /// assert_eq!(history.events(), [0, 1, 2]);
+    ///
+    /// assert_eq!(history.pop(), Some(2));
+    /// assert_eq!(history.events(), [0, 1]);
+    ///
+    /// history.clear();
+    /// assert!(history.is_empty());
+    /// ```
+    pub fn truncate(&mut self, len: usize) {
+        self.events.truncate(len);
+        if let Some(ref mut state) = self.state {
+            state.truncate(len);
+        }
+        self.branches.retain(|b| b.at <= len);
+    }
+
+    /// Removes the last event from this history and returns it.
+    pub fn pop(&mut self) -> Option<Event> {
+        let event = self.events.pop()?;
+        let len = self.events.len();
+        if let Some(ref mut state) = self.state {
+            state.truncate(len);
+        }
+        self.branches.retain(|b| b.at <= len);
+        Some(event)
+    }
+
+    /// Removes all events from this history.
+    ///
+    /// The capacity limit of this history is retained.
+    pub fn clear(&mut self) {
+        self.events.clear();
+        if let Some(ref mut state) = self.state {
+            state.omitted = 0;
+            state.omitted_at = 0;
+            state.cycles.clear();
+        }
+        self.branches.clear();
+    }
+
+    /// Retains only the events specified by the predicate.
+    ///
+    /// Compressed cycles that contain the removed events are also dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::{History, Location};
+    ///
+    /// let mut history = History::new();
+    /// history.add(Location::new("my_app::handler", "src/handler.rs", 10, ""));
+    /// history.add(Location::new("framework::router", "router.rs", 20, ""));
+    /// history.add(Location::new("my_app", "src/main.rs", 30, ""));
+    ///
+    /// history.retain(|e| e.crate_name() != "framework");
+    /// assert_eq!(format!("\n{}", history), r#"
+    /// HISTORY:
+    ///   [0] at src/handler.rs:10
+    ///   [1] at src/main.rs:30
+    /// "#);
+    /// ```
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&Event) -> bool,
+    {
+        let mut removed = Vec::new();
+        let mut index = 0;
+        self.events.retain(|e| {
+            let keep = f(e);
+            if !keep {
+                removed.push(index);
+            }
+            index += 1;
+            keep
+        });
+        for &index in removed.iter().rev() {
+            if let Some(ref mut state) = self.state {
+                state.remove_event(index);
+            }
+            shift_branches(&mut self.branches, index);
+        }
+    }
+
+    /// Merges the history of a concurrent sub-operation into this history as a labeled branch.
+    ///
+    /// The branch is attached after the last event of this history,
+    /// and rendered as an indented sub-history.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::{History, Location};
+    ///
+    /// let mut replica0 = History::new();
+    /// replica0.add(Location::new("foo", "src/replica.rs", 10, ""));
+    /// replica0.add(Location::new("foo", "src/replica.rs", 20, "timeout"));
+    ///
+    /// let mut replica1 = History::new();
+    /// replica1.add(Location::new("foo", "src/replica.rs", 30, "refused"));
+    ///
+    /// let mut history = History::new();
+    /// history.add(Location::new("foo", "src/client.rs", 5, "writing"));
+    /// history.merge_branch("replica-0", replica0);
+    /// history.merge_branch("replica-1", replica1);
+    /// history.add(Location::new("foo", "src/client.rs", 8, ""));
+    ///
+    /// assert_eq!(history.branches().len(), 2);
+    /// assert_eq!(format!("\n{}", history), r#"
+    /// HISTORY:
+    ///   [0] at src/client.rs:5 -- writing
+    ///   branch 'replica-0':
+    ///     [0] at src/replica.rs:10
+    ///     [1] at src/replica.rs:20 -- timeout
+    ///   branch 'replica-1':
+    ///     [0] at src/replica.rs:30 -- refused
+    ///   [1] at src/client.rs:8
+    /// "#);
+    /// ```
+    pub fn merge_branch<L>(&mut self, label: L, history: History<Event>)
+    where
+        L: Into<Cow<'static, str>>,
+    {
+        self.branches.push(Branch {
+            label: label.into(),
+            at: self.events.len(),
+            history,
+        });
+    }
+
+    /// Returns the branches merged into this history.
+    #[inline]
+    pub fn branches(&self) -> &[Branch<Event>] {
+        &self.branches
+    }
+
+    /// Returns the maximum number of the events held by this history.
+    #[inline]
+    pub fn capacity_limit(&self) -> Option<usize> {
+        self.state.as_ref().and_then(|s| s.limit)
+    }
+
+    /// Returns the number of the events omitted due to the capacity limit.
+    #[inline]
+    pub fn omitted(&self) -> usize {
+        self.state.as_ref().map_or(0, |s| s.omitted)
+    }
+
+    /// Returns the compressed cycles in this history.
+    ///
+    /// See `History::add_location` for more details.
+    #[inline]
+    pub fn cycles(&self) -> &[Cycle] {
+        self.state.as_ref().map_or(&[], |s| &s.cycles[..])
+    }
+
+    // Returns the index from which the events can be deduplicated or compressed.
+    fn cycle_end(&self) -> usize {
+        let end = self.cycles().last().map_or(0, |c| c.start + c.len);
+        end.max(self.branches.last().map_or(0, |b| b.at))
+    }
+}
+impl<Event: From<Location> + HistoryEvent> History<Event> {
+    /// Adds the location to the tail of this history.
+    ///
+    /// If the location is identical to the last event,
+    /// the repeat count of the event is incremented instead of adding a new event.
+    ///
+    /// In addition, if the tail of this history consists of two consecutive occurrences of
+    /// the same sequence of locations (up to `MAX_CYCLE_LEN` events),
+    /// those are compressed into a single [cycle](struct.Cycle.html).
+    /// Further iterations of the cycle only increment its iteration count.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::{History, Location};
+    ///
+    /// let mut history = History::<Location>::new();
+    /// for _ in 0..3 {
+    ///     history.add_location(Location::new("foo", "src/foo.rs", 10, "retrying"));
+    /// }
+    /// history.add_location(Location::new("foo", "src/foo.rs", 12, ""));
+    ///
+    /// assert_eq!(history.events().len(), 2);
+    /// assert_eq!(history.events()[0].repeat_count(), 3);
+    /// assert_eq!(format!("\n{}", history), r#"
+    /// HISTORY:
+    ///   [0] at src/foo.rs:10 -- retrying (x3)
+    ///   [1] at src/foo.rs:12
+    /// "#);
+    ///
+    /// let mut history = History::<Location>::new();
+    /// history.add_location(Location::new("foo", "src/foo.rs", 1, "start"));
+    /// for _ in 0..5 {
+    ///     history.add_location(Location::new("foo", "src/foo.rs", 10, ""));
+    ///     history.add_location(Location::new("foo", "src/foo.rs", 20, ""));
+    ///     history.add_location(Location::new("foo", "src/foo.rs", 30, ""));
+    /// }
+    ///
+    /// assert_eq!(history.events().len(), 4);
+    /// assert_eq!(history.cycles()[0].iterations(), 5);
+    /// assert_eq!(format!("\n{}", history), r#"
+    /// HISTORY:
+    ///   [0] at src/foo.rs:1 -- start
+    ///   [1] at src/foo.rs:10
+    ///   [2] at src/foo.rs:20
+    ///   [3] at src/foo.rs:30
+    ///   ... [1] to [3] repeated (x5) ...
+    /// "#);
+    /// ```
+    pub fn add_location(&mut self, location: Location) {
+        if self.events.len() > self.cycle_end() {
+            if let Some(last) = self.events.last_mut().and_then(HistoryEvent::location_mut) {
+                if last.is_repeated_by(&location) {
+                    last.repeated += 1;
+                    self.compress_cycles();
+                    return;
+                }
+            }
+        }
+        self.add(Event::from(location));
+        self.compress_cycles();
+    }
+
+    fn compress_cycles(&mut self) {
+        let len = self.events.len();
+        if let Some(c) = self.cycles().last().cloned() {
+            let end = c.start + c.len;
+            if len == end + c.len && self.is_repeated(c.start, end, c.len) {
+                self.events.truncate(end);
+                if let Some(c) = self.state.as_mut().and_then(|s| s.cycles.last_mut()) {
+                    c.iterations += 1;
+                }
+                return;
+            }
+        }
+
+        let start = self.cycle_end();
+        for cycle_len in 2..=MAX_CYCLE_LEN {
+            if len < start + cycle_len * 2 {
+                break;
+            }
+            let (a, b) = (len - cycle_len * 2, len - cycle_len);
+            if self.is_repeated(a, b, cycle_len) {
+                self.events.truncate(b);
+                self.state
+                    .get_or_insert_with(Default::default)
+                    .cycles
+                    .push(Cycle {
+                        start: a,
+                        len: cycle_len,
+                        iterations: 2,
+                    });
+                return;
+            }
+        }
+    }
+
+    fn is_repeated(&self, a: usize, b: usize, len: usize) -> bool {
+        (0..len).all(
+            |i| match (self.events[a + i].location(), self.events[b + i].location()) {
+                (Some(x), Some(y)) => x.is_repeated_by(y) && x.repeated == y.repeated,
+                _ => false,
+            },
+        )
+    }
+}
+impl<'a, Event> IntoIterator for &'a History<Event> {
+    type Item = &'a Event;
+    type IntoIter = std::slice::Iter<'a, Event>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+impl<Event: std::fmt::Display + HistoryEvent> History<Event> {
+    /// Renders this history decorated by `style`.
+    ///
+    /// If `limit` is `Some((first, last))`, only the first `first` events and the last `last` events are rendered.
+    pub(crate) fn fmt_styled(
+        &self,
+        f: &mut std::fmt::Formatter,
+        style: &fmt::Style,
+        limit: Option<(usize, usize)>,
+    ) -> std::fmt::Result {
+        writeln!(f, "HISTORY:")?;
+        self.fmt_events(f, "  ", style, limit)
+    }
+
+    fn fmt_events(
+        &self,
+        f: &mut std::fmt::Formatter,
+        indent: &str,
+        style: &fmt::Style,
+        limit: Option<(usize, usize)>,
+    ) -> std::fmt::Result {
+        let omitted = self.omitted();
+        let head = self.state.as_ref().map_or(0, |s| s.omitted_at);
+        let index = |i: usize| {
+            if omitted > 0 && i >= head {
+                i + omitted
+            } else {
+                i
+            }
+        };
+        let mut lines = Vec::new();
+        let mut cycles = self.cycles().iter().peekable();
+        let mut branches = self.branches.iter().peekable();
+        let mut thread = None;
+        for (i, e) in self.events().iter().enumerate() {
+            while let Some(b) = branches.next_if(|b| b.at == i) {
+                lines.push(Line::Branch(b));
+            }
+            if omitted > 0 && i == head {
+                lines.push(Line::Omitted);
+            }
+            let current = e.location().and_then(Location::thread);
+            let new_thread = current.filter(|_| current != thread);
+            thread = current;
+            lines.push(Line::Event(index(i), e, new_thread));
+            if let Some(c) = cycles.next_if(|c| c.start + c.len == i + 1) {
+                lines.push(Line::Cycle(index(c.start), index(i), c.iterations));
+            }
+        }
+        lines.extend(branches.map(Line::Branch));
+        if omitted > 0 && self.events.len() <= head {
+            lines.push(Line::Omitted);
+        }
+        if newest_first_enabled() {
+            lines.reverse();
+        }
+
+        let frames = lines
+            .iter()
+            .filter(|l| matches!(l, Line::Event(..)))
+            .count();
+        let mut hidden = 0;
+        if let Some((first, last)) = limit.filter(|&(a, b)| frames > a + b) {
+            hidden = frames - first - last;
+            let mut frame = 0;
+            lines.retain(|l| {
+                if let Line::Event(..) = *l {
+                    frame += 1;
+                }
+                let ordinal = frame.max(1) - 1;
+                ordinal < first || ordinal >= first + hidden
+            });
+        }
+
+        for line in lines {
+            match line {
+                Line::Event(i, e, thread) => {
+                    write!(f, "{}{}[{}]{} ", indent, style.index, i, style.reset)?;
+                    if let Some(location) = e.location() {
+                        location.fmt_styled(f, style)?;
+                    } else if f.alternate() {
+                        write!(f, "{:#}", e)?;
+                    } else {
+                        write!(f, "{}", e)?;
+                    }
+                    if let Some(name) = thread {
+                        write!(f, " [thread '{}']", name)?;
+                    }
+                    writeln!(f)?;
+                    let snippet = e.location().map_or("", Location::snippet);
+                    if f.alternate() && !snippet.is_empty() {
+                        writeln!(
+                            f,
+                            "{}      {}| {}{}",
+                            indent, style.note, snippet, style.reset
+                        )?;
+                    }
+                }
+                Line::Cycle(start, end, iterations) => writeln!(
+                    f,
+                    "{}{}... [{}] to [{}] repeated (x{}) ...{}",
+                    indent, style.note, start, end, iterations, style.reset
+                )?,
+                Line::Branch(b) => b.fmt_indented(f, indent, style, limit)?,
+                Line::Omitted => writeln!(
+                    f,
+                    "{}{}... {} events omitted ...{}",
+                    indent, style.note, omitted, style.reset
+                )?,
+            }
+        }
+        if hidden > 0 {
+            let plural = if hidden == 1 { "" } else { "s" };
+            writeln!(
+                f,
+                "{}{}... and {} more frame{}{}",
+                indent, style.note, hidden, plural, style.reset
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// A line (or a block of lines) in the rendered form of a history.
+enum Line<'a, Event> {
+    Event(usize, &'a Event, Option<&'a str>),
+    Cycle(usize, usize, usize),
+    Branch(&'a Branch<Event>),
+    Omitted,
+}
+
+impl<Event: std::fmt::Display + HistoryEvent> std::fmt::Display for History<Event> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        // The precision (e.g., `{:.1}`) overrides the global limit
+        let limit = f.precision().map(|n| (n, n)).or_else(display_frame_limit);
+        self.fmt_styled(f, &fmt::Style::PLAIN, limit)
+    }
+}
+
+/// A history of a concurrent sub-operation merged into another [history](struct.History.html).
+///
+/// See `History::merge_branch` for more details.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Branch<Event> {
+    label: Cow<'static, str>,
+    at: usize,
+    history: History<Event>,
+}
+impl<Event> Branch<Event> {
+    /// Returns the label of this branch.
+    #[inline]
+    pub fn label(&self) -> &str {
+        self.label.as_ref()
+    }
+
+    /// Returns the number of the events of the parent history preceding this branch.
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.at
+    }
+
+    /// Returns the history of this branch.
+    #[inline]
+    pub fn history(&self) -> &History<Event> {
+        &self.history
+    }
+}
+impl<Event: std::fmt::Display + HistoryEvent> Branch<Event> {
+    fn fmt_indented(
+        &self,
+        f: &mut std::fmt::Formatter,
+        indent: &str,
+        style: &fmt::Style,
+        limit: Option<(usize, usize)>,
+    ) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{}{}branch '{}':{}",
+            indent, style.note, self.label, style.reset
+        )?;
+        self.history
+            .fmt_events(f, &format!("{}  ", indent), style, limit)
+    }
+}
+
+/// The maximum number of the events in a cycle detected by `History::add_location`.
+pub const MAX_CYCLE_LEN: usize = 8;
+
+/// A sequence of events that was repeated consecutively in a [history](struct.History.html).
+///
+/// Only the first iteration of the sequence is held by the history.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Cycle {
+    start: usize,
+    len: usize,
+    iterations: usize,
+}
+impl Cycle {
+    /// Returns the index of the first event of this cycle.
+    #[inline]
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// Returns the number of the events in this cycle.
+    #[allow(clippy::len_without_is_empty)]
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns how many times the events in this cycle were repeated.
+    #[inline]
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct HistoryState {
+    #[cfg_attr(feature = "serde", serde(default))]
+    limit: Option<usize>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    omitted: usize,
+    #[cfg_attr(feature = "serde", serde(default))]
+    omitted_at: usize,
+    #[cfg_attr(feature = "serde", serde(default))]
+    cycles: Vec<Cycle>,
+}
+impl HistoryState {
+    fn with_limit(limit: usize) -> Box<Self> {
+        Box::new(HistoryState {
+            limit: Some(limit),
+            ..Default::default()
+        })
+    }
+
+    // Returns the number of the events lost by dropping the cycle that contains the removed event.
+    fn remove_event(&mut self, index: usize) -> usize {
+        let mut lost = 0;
+        self.cycles.retain_mut(|c| {
+            if index < c.start {
+                c.start -= 1;
+                true
+            } else if index < c.start + c.len {
+                lost += c.len * (c.iterations - 1);
+                false
+            } else {
+                true
+            }
+        });
+        if index < self.omitted_at {
+            self.omitted_at -= 1;
+        }
+        lost
+    }
+
+    fn truncate(&mut self, len: usize) {
+        self.cycles.retain(|c| c.start + c.len <= len);
+        self.omitted_at = self.omitted_at.min(len);
+    }
+}
+
+fn shift_branches<Event>(branches: &mut [Branch<Event>], removed_index: usize) {
+    for b in branches.iter_mut().filter(|b| b.at > removed_index) {
+        b.at -= 1;
+    }
+}
+
+static THREAD_CAPTURE_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Sets whether the current thread is recorded in each `Location` created after this call.
+///
+/// If enabled, the thread is rendered in a history when it differs from the one of the previous event.
+///
+/// The default value is `false`.
+///
+/// # Examples
+///
+/// ```
+/// use trackable::{History, Location};
+///
+/// trackable::set_thread_capture_enabled(true);
+///
+/// let mut history = History::new();
+/// history.add(Location::new("foo", "src/foo.rs", 10, "").with_thread("main"));
+/// history.add(Location::new("foo", "src/foo.rs", 20, "").with_thread("main"));
+/// history.add(Location::new("foo", "src/foo.rs", 30, "").with_thread("worker-1"));
+///
+/// assert_eq!(format!("\n{}", history), r#"
+/// HISTORY:
+///   [0] at src/foo.rs:10 [thread 'main']
+///   [1] at src/foo.rs:20
+///   [2] at src/foo.rs:30 [thread 'worker-1']
+/// "#);
+///
+/// let location = std::thread::Builder::new()
+///     .name("worker-2".to_owned())
+///     .spawn(|| Location::new("foo", "src/foo.rs", 40, ""))
+///     .unwrap()
+///     .join()
+///     .unwrap();
+/// assert_eq!(location.thread(), Some("worker-2"));
+/// ```
+pub fn set_thread_capture_enabled(enabled: bool) {
+    THREAD_CAPTURE_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns `true` if the current thread is recorded in each newly created `Location`.
+pub fn thread_capture_enabled() -> bool {
+    THREAD_CAPTURE_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Sets whether histories are rendered newest-first.
+///
+/// If enabled, the most recent event is rendered at the top of a history,
+/// while the index of each event is preserved.
+///
+/// The default value is `false`.
+///
+/// This is a part of the global [format configuration](fmt/struct.Config.html), so it can be overridden by
+/// the `TRACKABLE_FORMAT` environment variable.
+///
+/// # Examples
+///
+/// ```
+/// use trackable::{History, Location};
+///
+/// trackable::set_newest_first_enabled(true);
+///
+/// let mut history = History::new();
+/// history.add(Location::new("foo", "src/foo.rs", 10, ""));
+/// history.add(Location::new("foo", "src/foo.rs", 20, ""));
+/// history.add(Location::new("foo", "src/foo.rs", 30, "last"));
+///
+/// assert_eq!(format!("\n{}", history), r#"
+/// HISTORY:
+///   [2] at src/foo.rs:30 -- last
+///   [1] at src/foo.rs:20
+///   [0] at src/foo.rs:10
+/// "#);
+/// ```
+pub fn set_newest_first_enabled(enabled: bool) {
+    fmt::update_config(|c| c.newest_first = enabled);
+}
+
+/// Returns `true` if histories are rendered newest-first.
+///
+/// This is a shorthand of `fmt::config().newest_first()`.
+pub fn newest_first_enabled() -> bool {
+    fmt::config().newest_first()
+}
+
+/// Sets the maximum number of the events rendered in a history.
+///
+/// If `Some((first, last))` is specified and a history has more than `first + last` events,
+/// only the first `first` events and the last `last` events are rendered,
+/// followed by a footer line which reports the number of the hidden events.
+/// Note that the hidden events are still available via `History::events` method.
+///
+/// The default value is `None` (i.e., all events are rendered).
+///
+/// This is a part of the global [format configuration](fmt/struct.Config.html), so it can be overridden by
+/// the `TRACKABLE_FORMAT` environment variable.
+///
+/// The limit can also be specified at each format call site by the precision:
+/// `format!("{:.1}", history)` renders only the first and the last events regardless of this setting.
+///
+/// Note that this limit is applied to the extended report of `TrackableError` (i.e., `{:#}`),
+/// while the concise one (i.e., `{}`) always renders only the first and the last events.
+///
+/// # Examples
+///
+/// ```
+/// use trackable::History;
+///
+/// trackable::set_display_frame_limit(Some((2, 1)));
+///
+/// let mut history = History::new();
+/// for i in 0..6 {
+///     history.add(i);
+/// }
+///
+/// assert_eq!(format!("\n{}", history), r#"
+/// HISTORY:
+///   [0] 0
+///   [1] 1
+///   [5] 5
+///   ... and 3 more frames
+/// "#);
+/// assert_eq!(history.len(), 6);
+/// ```
+pub fn set_display_frame_limit(limit: Option<(usize, usize)>) {
+    fmt::update_config(|c| c.frame_limit = limit);
+}
+
+/// Returns the maximum number of the events rendered in a history as `(first, last)`.
+///
+/// This is a shorthand of `fmt::config().frame_limit()`.
+/// See `set_display_frame_limit` for more details.
+pub fn display_frame_limit() -> Option<(usize, usize)> {
+    fmt::config().frame_limit()
+}
+
+static HISTORY_LIMIT: AtomicUsize = AtomicUsize::new(usize::MAX);
+
+/// Sets the maximum number of the events held by each `History` created after this call.
+///
+/// `None` means that histories grow without bound.
+/// The limit of an individual history can be specified by `History::with_capacity_limit`.
+///
+/// The default value is `None`.
+///
+/// # Examples
+///
+/// ```
+/// use trackable::History;
+///
+/// trackable::set_history_limit(Some(2));
+///
+/// let mut history = History::new();
+/// for i in 0..10 {
+///     history.add(i);
+/// }
+/// assert_eq!(history.events(), [0, 9]);
+/// assert_eq!(history.omitted(), 8);
+/// ```
+pub fn set_history_limit(limit: Option<usize>) {
+    HISTORY_LIMIT.store(limit.unwrap_or(usize::MAX), Ordering::Relaxed);
+}
+
+/// Returns the maximum number of the events held by each newly created `History`.
+pub fn history_limit() -> Option<usize> {
+    match HISTORY_LIMIT.load(Ordering::Relaxed) {
+        usize::MAX => None,
+        limit => Some(limit),
+    }
+}
+
+static PATH_PREFIXES: RwLock<Option<Vec<String>>> = RwLock::new(None);
+
+/// Sets the prefixes stripped from the file names when rendering locations.
+///
+/// If a prefix ends with `registry/src/`, the following directory (i.e., the registry index)
+/// is also stripped.
+///
+/// The default prefixes are the value of the `CARGO_MANIFEST_DIR` environment variable
+/// (if it is set at runtime) and the registry source directory under `CARGO_HOME`.
+/// Stripping can be disabled by `fmt::Config::with_strip_paths`.
+///
+/// # Examples
+///
+/// ```
+/// use trackable::Location;
+///
+/// trackable::set_path_prefixes(vec!["/build/", "/home/foo/.cargo/registry/src/"]);
+///
+/// let location = Location::new("foo", "/build/src/foo.rs", 10, "");
+/// assert_eq!(location.to_string(), "at src/foo.rs:10");
+///
+/// let file = "/home/foo/.cargo/registry/src/index.crates.io-6f17d22bba15001f/bar-1.0.0/src/lib.rs";
+/// let location = Location::new("bar", file, 20, "");
+/// assert_eq!(location.to_string(), "at bar-1.0.0/src/lib.rs:20");
+/// assert_eq!(location.file(), file);
+/// ```
+pub fn set_path_prefixes<I, T>(prefixes: I)
+where
+    I: IntoIterator<Item = T>,
+    T: Into<String>,
+{
+    let prefixes = prefixes.into_iter().map(Into::into).collect();
+    *PATH_PREFIXES.write().unwrap_or_else(|e| e.into_inner()) = Some(prefixes);
+}
+
+/// Returns the prefixes stripped from the file names when rendering locations.
+///
+/// See `set_path_prefixes` for more details.
+pub fn path_prefixes() -> Vec<String> {
+    with_path_prefixes(|prefixes| prefixes.to_vec())
+}
+
+fn with_path_prefixes<F, T>(f: F) -> T
+where
+    F: FnOnce(&[String]) -> T,
+{
+    {
+        let prefixes = PATH_PREFIXES.read().unwrap_or_else(|e| e.into_inner());
+        if let Some(ref prefixes) = *prefixes {
+            return f(prefixes);
+        }
+    }
+    let mut prefixes = PATH_PREFIXES.write().unwrap_or_else(|e| e.into_inner());
+    f(prefixes.get_or_insert_with(default_path_prefixes))
+}
+
+fn default_path_prefixes() -> Vec<String> {
+    let mut prefixes = Vec::new();
+    if let Some(dir) = std::env::var_os("CARGO_MANIFEST_DIR") {
+        let dir = Path::new(&dir).join("");
+        prefixes.push(dir.to_string_lossy().into_owned());
+    }
+    let cargo_home = std::env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cargo")));
+    if let Some(cargo_home) = cargo_home {
+        let dir = cargo_home.join("registry").join("src").join("");
+        prefixes.push(dir.to_string_lossy().into_owned());
+    }
+    prefixes
+}
+
+pub(crate) fn strip_path_prefix(file: &str) -> &str {
+    const SEPARATORS: [char; 2] = ['/', '\\'];
+    if !fmt::config().strip_paths() {
+        return file;
+    }
+    with_path_prefixes(|prefixes| {
+        for prefix in prefixes {
+            if let Some(rest) = file.strip_prefix(prefix.as_str()) {
+                let dir = prefix.trim_end_matches(SEPARATORS);
+                if dir.ends_with("registry/src") || dir.ends_with("registry\\src") {
+                    if let Some(i) = rest.find(SEPARATORS) {
+                        return &rest[i + 1..];
+                    }
+                }
+                return rest;
+            }
+        }
+        file
+    })
+}
+
+/// Sets whether the positions of locations are rendered as [OSC 8] hyperlinks.
+///
+/// If enabled, the `path:line:column` part of each rendered location is wrapped in
+/// an OSC 8 escape sequence which links to the `file://` URL of the (unstripped) file.
+/// Terminals supporting OSC 8 make the position clickable, and the others ignore the sequence.
+/// Relative file names are resolved against the current directory.
+///
+/// Note that this crate does not detect whether the terminal supports OSC 8.
+/// Without this setting, the positions are still rendered in the `path:line:column` shape
+/// which most terminals and IDEs recognize as a link.
+///
+/// The default value is `false`.
+///
+/// This is a part of the global [format configuration](fmt/struct.Config.html), so it can be overridden by
+/// the `TRACKABLE_FORMAT` environment variable.
+///
+/// # Examples
+///
+/// ```
+/// use trackable::Location;
+///
+/// trackable::set_hyperlinks_enabled(true);
+///
+/// let location = Location::new("foo", "/build/my app/src/foo.rs", 10, "hello").with_column(5);
+/// assert_eq!(
+///     location.to_string(),
+///     "at \x1b]8;;file:///build/my%20app/src/foo.rs\x1b\\/build/my app/src/foo.rs:10:5\x1b]8;;\x1b\\ -- hello"
+/// );
+/// ```
+///
+/// [OSC 8]: https://gist.github.com/egmontkob/eb114294efbcd5adb1944c9f3cb5feda
+pub fn set_hyperlinks_enabled(enabled: bool) {
+    fmt::update_config(|c| c.hyperlinks = enabled);
+}
+
+/// Returns `true` if the positions of locations are rendered as OSC 8 hyperlinks.
+///
+/// This is a shorthand of `fmt::config().hyperlinks()`.
+pub fn hyperlinks_enabled() -> bool {
+    fmt::config().hyperlinks()
+}
+
+fn file_url(file: &str) -> String {
+    let path = Path::new(file);
+    let path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir().map_or_else(|_| path.to_path_buf(), |dir| dir.join(path))
+    };
+    let path = path.to_string_lossy().replace('\\', "/");
+
+    let mut url = String::from("file://");
+    if !path.starts_with('/') {
+        // e.g., `C:/foo.rs` on Windows
+        url.push('/');
+    }
+    for b in path.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+                url.push(char::from(b))
+            }
+            _ => url.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    url
+}
+
+impl<Event> Default for History<Event> {
+    #[inline]
+    fn default() -> Self {
+        History::new()
+    }
+}
+
+// In human-readable formats, a history is serialized as the plain sequence of its events
+// unless it has extra state (i.e., a limit, omitted events, cycles or branches), so that
+// the serialized form of an ordinary history stays compatible with the older versions.
+// Both forms are accepted when deserializing.
+//
+// Other formats may not be self-describing (e.g., bincode), so a history is always
+// serialized there as a struct with all of its fields.
+#[cfg(feature = "serde")]
+mod impl_history_serde {
+    use std::fmt;
+    use std::marker::PhantomData;
+
+    use serde::de::{self, IgnoredAny, MapAccess, SeqAccess, Visitor};
+    use serde::ser::SerializeStruct;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::{Branch, Events, History, HistoryState};
+
+    const FIELDS: &[&str] = &["events", "state", "branches"];
+
+    impl<Event: Serialize> Serialize for History<Event> {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            // Non-self-describing formats (e.g., bincode) always get the full struct,
+            // since they can neither skip fields nor guess between two shapes.
+            let compact = serializer.is_human_readable();
+            if compact && self.state.is_none() && self.branches.is_empty() {
+                return self.events[..].serialize(serializer);
+            }
+            let mut s = serializer.serialize_struct("History", 3)?;
+            s.serialize_field("events", &self.events[..])?;
+            if compact && self.state.is_none() {
+                s.skip_field("state")?;
+            } else {
+                s.serialize_field("state", &self.state)?;
+            }
+            if compact && self.branches.is_empty() {
+                s.skip_field("branches")?;
+            } else {
+                s.serialize_field("branches", &self.branches)?;
+            }
+            s.end()
+        }
+    }
+
+    #[derive(Deserialize)]
+    #[serde(field_identifier, rename_all = "lowercase")]
+    enum Field {
+        Events,
+        State,
+        Branches,
+        #[serde(other)]
+        Unknown,
+    }
+
+    struct HistoryVisitor<Event> {
+        human_readable: bool,
+        _event: PhantomData<Event>,
+    }
+    impl<'de, Event: Deserialize<'de>> Visitor<'de> for HistoryVisitor<Event> {
+        type Value = History<Event>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "a sequence of events or a History struct")
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: SeqAccess<'de>,
+        {
+            if self.human_readable {
+                // The plain form: a bare sequence of events.
+                let mut events = Events::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+                while let Some(event) = seq.next_element()? {
+                    events.push(event);
+                }
+                return Ok(History {
+                    events,
+                    state: None,
+                    branches: Vec::new(),
+                });
+            }
+
+            // The struct form of non-self-describing formats: all fields in order.
+            let events: Vec<Event> = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+            let state = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+            let branches = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::invalid_length(2, &self))?;
+            Ok(History {
+                events: events.into_iter().collect(),
+                state,
+                branches,
+            })
+        }
+
+        fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+        where
+            A: MapAccess<'de>,
+        {
+            let mut events: Option<Vec<Event>> = None;
+            let mut state: Option<Option<Box<HistoryState>>> = None;
+            let mut branches: Option<Vec<Branch<Event>>> = None;
+            while let Some(key) = map.next_key()? {
+                match key {
+                    Field::Events => {
+                        if events.is_some() {
+                            return Err(de::Error::duplicate_field("events"));
+                        }
+                        events = Some(map.next_value()?);
+                    }
+                    Field::State => {
+                        if state.is_some() {
+                            return Err(de::Error::duplicate_field("state"));
+                        }
+                        state = Some(map.next_value()?);
+                    }
+                    Field::Branches => {
+                        if branches.is_some() {
+                            return Err(de::Error::duplicate_field("branches"));
+                        }
+                        branches = Some(map.next_value()?);
+                    }
+                    Field::Unknown => {
+                        map.next_value::<IgnoredAny>()?;
+                    }
+                }
+            }
+            Ok(History {
+                events: events
+                    .ok_or_else(|| de::Error::missing_field("events"))?
+                    .into_iter()
+                    .collect(),
+                state: state.unwrap_or_default(),
+                branches: branches.unwrap_or_default(),
+            })
+        }
+    }
+
+    impl<'de, Event: Deserialize<'de>> Deserialize<'de> for History<Event> {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let human_readable = deserializer.is_human_readable();
+            let visitor = HistoryVisitor {
+                human_readable,
+                _event: PhantomData,
+            };
+            if human_readable {
+                deserializer.deserialize_any(visitor)
+            } else {
+                deserializer.deserialize_struct("History", FIELDS, visitor)
+            }
+        }
+    }
+}
+
+/// A user-defined event which can be attached to a [location](struct.Location.html).
+///
+/// This trait is implemented for all `Debug + Display + Send + Sync + 'static` types.
+///
+/// See [annotate!](macro.annotate.html) macro for more details.
+pub trait Annotation: Any + std::fmt::Debug + std::fmt::Display + Send + Sync {}
+impl<T: Any + std::fmt::Debug + std::fmt::Display + Send + Sync> Annotation for T {}
+
+/// The location of interest in source code files.
+///
+/// Typically this is created in the macros which defined in this crate.
+///
+/// The string components are held as `Cow<'static, str>`,
+/// so `&'static str` values (e.g., `module_path!()` and `file!()`) are stored without allocation,
+/// while dynamically constructed values can be stored as owned strings.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Location {
+    module_path: Cow<'static, str>,
+    file: Cow<'static, str>,
+    line: u32,
+    #[cfg_attr(feature = "serde", serde(default))]
+    column: u32,
+    #[cfg_attr(feature = "serde", serde(default))]
+    function: Cow<'static, str>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    crate_version: Cow<'static, str>,
+    message: Cow<'static, str>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    fields: Vec<Field>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    repeated: u32,
+    #[cfg_attr(feature = "serde", serde(default))]
+    thread: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    timestamp: Option<SystemTime>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    snippet: Cow<'static, str>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    annotation: Option<Arc<dyn Annotation>>,
+}
+impl Location {
+    /// Makes a new `Location` instance.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::Location;
+    ///
+    /// let location = Location::new(module_path!(), file!(), line!(), "Hello".to_string());
+    /// assert_eq!(location.message(), "Hello");
+    /// ```
+    #[inline]
+    pub fn new<M, F, T>(module_path: M, file: F, line: u32, message: T) -> Self
+    where
+        M: Into<Cow<'static, str>>,
+        F: Into<Cow<'static, str>>,
+        T: Into<Cow<'static, str>>,
+    {
+        Location {
+            module_path: module_path.into(),
+            file: file.into(),
+            line,
+            column: 0,
+            function: Cow::Borrowed(""),
+            crate_version: Cow::Borrowed(""),
+            message: message.into(),
+            fields: Vec::new(),
+            repeated: 0,
+            thread: if thread_capture_enabled() {
+                let thread = std::thread::current();
+                Some(match thread.name() {
+                    Some(name) => name.to_owned(),
+                    None => format!("{:?}", thread.id()),
+                })
+            } else {
+                None
+            },
+            timestamp: if fmt::timestamps_enabled() {
+                Some(SystemTime::now())
+            } else {
+                None
+            },
+            snippet: Cow::Borrowed(""),
+            annotation: None,
+        }
+    }
+
+    /// Sets the column of this location.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::Location;
+    ///
+    /// let location = Location::new("foo", "src/foo.rs", 10, "").with_column(27);
+    /// assert_eq!(location.column(), 27);
+    /// assert_eq!(location.to_string(), "at src/foo.rs:10:27");
+    /// ```
+    #[inline]
+    pub fn with_column(mut self, column: u32) -> Self {
+        self.column = column;
+        self
+    }
+
+    /// Sets the name of the function which encloses this location.
+    ///
+    /// The `track!` family macros set this automatically.
+    /// It is rendered only in the alternate form (i.e., `{:#}`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::Location;
+    ///
+    /// let location = Location::new("foo", "src/foo.rs", 10, "")
+    ///     .with_function("foo::sync::flush_buffers");
+    /// assert_eq!(location.function(), "foo::sync::flush_buffers");
+    /// assert_eq!(location.to_string(), "at src/foo.rs:10");
+    /// assert_eq!(format!("{:#}", location), "at src/foo.rs:10 in foo::sync::flush_buffers");
+    /// ```
+    #[inline]
+    pub fn with_function<T>(mut self, function: T) -> Self
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        self.function = function.into();
+        self
+    }
+
+    /// Sets the version of the crate which contains this location.
+    ///
+    /// The `track!` family macros set this automatically to the value of
+    /// the `CARGO_PKG_VERSION` environment variable at compile time (if it is set).
+    #[inline]
+    pub fn with_crate_version<T>(mut self, version: T) -> Self
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        self.crate_version = version.into();
+        self
+    }
+
+    /// Gets the name of the crate which contains this location.
+    ///
+    /// This is the first segment of the module path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::Location;
+    ///
+    /// let location = Location::new("foo::bar::baz", "src/bar.rs", 10, "").with_crate_version("1.2.3");
+    /// assert_eq!(location.crate_name(), "foo");
+    /// assert_eq!(location.crate_version(), "1.2.3");
+    /// ```
+    #[inline]
+    pub fn crate_name(&self) -> &str {
+        if let Some(module_path_end) = self.module_path.find(':') {
+            &self.module_path[..module_path_end]
+        } else {
+            self.module_path.as_ref()
+        }
+    }
+
+    /// Gets the version of the crate which contains this location.
+    ///
+    /// An empty string means that the version is unknown.
+    #[inline]
+    pub fn crate_version(&self) -> &str {
+        self.crate_version.as_ref()
+    }
+
+    /// Gets the module path of this location.
+    #[inline]
+    pub fn module_path(&self) -> &str {
+        self.module_path.as_ref()
+    }
+
+    /// Gets the file name of this location.
+    #[inline]
+    pub fn file(&self) -> &str {
+        self.file.as_ref()
+    }
+
+    /// Gets the line of this location.
+    #[inline]
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+
+    /// Gets the column of this location.
+    ///
+    /// `0` means that the column is unknown.
+    #[inline]
+    pub fn column(&self) -> u32 {
+        self.column
+    }
+
+    /// Gets the name of the function which encloses this location.
+    ///
+    /// An empty string means that the function is unknown
+    /// (e.g., the location was captured via `#[track_caller]`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use]
+    /// extern crate trackable;
+    ///
+    /// use trackable::Trackable;
+    /// use trackable::error::{ErrorKindExt, Failed, Failure};
+    ///
+    /// fn flush_buffers() -> Failure {
+    ///     let e: Failure = Failed.error().into();
+    ///     track!(e)
+    /// }
+    ///
+    /// fn main() {
+    ///     let e = flush_buffers();
+    ///     let function = e.history().unwrap().events()[0].function();
+    ///     assert!(function.ends_with("::flush_buffers"));
+    /// }
+    /// ```
+    #[inline]
+    pub fn function(&self) -> &str {
+        self.function.as_ref()
+    }
+
+    /// Gets the message left at this location.
+    #[inline]
+    pub fn message(&self) -> &str {
+        self.message.as_ref()
+    }
+
+    /// Sets the name of the thread on which this location was tracked.
+    #[inline]
+    pub fn with_thread<T>(mut self, thread: T) -> Self
+    where
+        T: Into<String>,
+    {
+        self.thread = Some(thread.into());
+        self
+    }
+
+    /// Gets the name (or the ID if it is unnamed) of the thread on which this location was tracked.
+    ///
+    /// This is `None` unless thread capturing was enabled via `set_thread_capture_enabled` function
+    /// when this location was created.
+    #[inline]
+    pub fn thread(&self) -> Option<&str> {
+        self.thread.as_deref()
+    }
+
+    /// Sets the time at which this location was tracked.
+    #[inline]
+    pub fn with_timestamp(mut self, timestamp: SystemTime) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Gets the time at which this location was tracked.
+    ///
+    /// This is `None` unless timestamps were enabled via `fmt::Config::with_timestamps`
+    /// when this location was created.
+    #[inline]
+    pub fn timestamp(&self) -> Option<SystemTime> {
+        self.timestamp
+    }
+
+    /// Sets the source line text of this location.
+    ///
+    /// If the `source-snippets` feature is enabled,
+    /// `track!` macro embeds the line of the tracked expression at compile time,
+    /// so that it can be shown without the source tree at runtime.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::{History, Location};
+    ///
+    /// let mut history = History::new();
+    /// history.add(Location::new("foo", "src/foo.rs", 10, "").with_snippet("let n = parse(s)?;"));
+    /// assert_eq!(format!("\n{:#}", history), r#"
+    /// HISTORY:
+    ///   [0] at src/foo.rs:10
+    ///         | let n = parse(s)?;
+    /// "#);
+    /// ```
+    #[inline]
+    pub fn with_snippet<T>(mut self, snippet: T) -> Self
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        self.snippet = snippet.into();
+        self
+    }
+
+    /// Gets the source line text of this location.
+    ///
+    /// An empty string means that the snippet was not captured
+    /// (e.g., the `source-snippets` feature is disabled).
+    #[inline]
+    pub fn snippet(&self) -> &str {
+        self.snippet.as_ref()
+    }
+
+    /// Attaches a user-defined event to this location.
+    ///
+    /// The message of this location is replaced with the `Display` representation of the annotation.
+    ///
+    /// Typically, this is called via [annotate!](macro.annotate.html) macro.
+    pub fn with_annotation<T: Annotation>(mut self, annotation: T) -> Self {
+        self.message = Cow::Owned(annotation.to_string());
+        self.annotation = Some(Arc::new(annotation));
+        self
+    }
+
+    /// Gets the user-defined event attached to this location.
+    ///
+    /// Note that annotations are not serialized (only their messages are).
+    #[inline]
+    pub fn annotation(&self) -> Option<&dyn Annotation> {
+        self.annotation.as_deref()
+    }
+
+    /// Gets the user-defined event attached to this location if it has the type `T`.
+    pub fn annotation_as<T: Annotation>(&self) -> Option<&T> {
+        let annotation: &dyn Any = self.annotation()?;
+        annotation.downcast_ref()
+    }
+
+    /// Gets the number of the consecutive occurrences of this location in a history.
+    ///
+    /// See `History::add_location` for more details.
+    #[inline]
+    pub fn repeat_count(&self) -> u32 {
+        self.repeated + 1
+    }
+
+    /// Gets the key-value fields attached to this location.
+    #[inline]
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+
+    /// Gets the value of the field which has the given key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use trackable::Location;
+    ///
+    /// let mut location = Location::new(module_path!(), file!(), line!(), "");
+    /// location.add_field("user_id", "42");
+    /// assert_eq!(location.field("user_id"), Some("42"));
+    /// assert_eq!(location.field("retries"), None);
+    /// ```
+    pub fn field(&self, key: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|f| f.key() == key)
+            .map(|f| f.value())
+    }
+
+    /// Attaches a key-value field to this location.
+    ///
+    /// Typically, this is called via `track!($target, { $key = $value, .. })` form.
+    #[inline]
+    pub fn add_field<K, V>(&mut self, key: K, value: V)
+    where
+        K: Into<Cow<'static, str>>,
+        V: Into<String>,
+    {
+        self.fields.push(Field {
+            key: key.into(),
+            value: value.into(),
+        });
+    }
+
+    fn is_repeated_by(&self, other: &Location) -> bool {
+        self.line == other.line
+            && self.column == other.column
+            && self.file == other.file
+            && self.module_path == other.module_path
+            && self.message == other.message
+            && self.fields == other.fields
+            && self.thread == other.thread
+    }
+
+    /// Renders this location decorated by `style`.
+    pub(crate) fn fmt_styled(
+        &self,
+        f: &mut std::fmt::Formatter,
+        style: &fmt::Style,
+    ) -> std::fmt::Result {
+        let mut position = format!("{}:{}", strip_path_prefix(self.file()), self.line());
+        if self.column != 0 {
+            position.push_str(&format!(":{}", self.column));
+        }
+        if hyperlinks_enabled() {
+            let url = file_url(self.file());
+            write!(
+                f,
+                "at {}\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\{}",
+                style.position, url, position, style.reset
+            )?;
+        } else {
+            write!(f, "at {}{}{}", style.position, position, style.reset)?;
+        }
+        if f.alternate() && !self.function.is_empty() {
+            write!(f, " in {}", self.function)?;
+        }
+        if !self.message().is_empty() {
+            write!(f, " -- {}{}{}", style.message, self.message(), style.reset)?;
+        }
+        for (i, field) in self.fields.iter().enumerate() {
+            match (i, self.message().is_empty()) {
+                (0, true) => write!(f, " -- ")?,
+                (0, false) => write!(f, "; ")?,
+                _ => write!(f, ", ")?,
+            }
+            write!(f, "{}", field)?;
+        }
+        if self.repeated > 0 {
+            write!(f, " (x{})", self.repeat_count())?;
+        }
+        if let Some(timestamp) = self.timestamp.filter(|_| fmt::config().timestamps()) {
+            write!(f, " [{}]", fmt::Timestamp(timestamp))?;
+        }
+        Ok(())
+    }
+}
+impl std::fmt::Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.fmt_styled(f, &fmt::Style::PLAIN)
+    }
+}
+
+/// A key-value field attached to a [location](struct.Location.html).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Field {
+    key: Cow<'static, str>,
+    value: String,
+}
+impl Field {
+    /// Gets the key of this field.
+    #[inline]
+    pub fn key(&self) -> &str {
+        self.key.as_ref()
+    }
+
+    /// Gets the value of this field.
+    ///
+    /// The value is the debugging representation of the value passed to `track!`.
+    #[inline]
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+impl std::fmt::Display for Field {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}={}", self.key, self.value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use error::Failure;
+
+    #[test]
+    fn it_works() {
         fn foo() -> Result<(), Failure> {
             track!(std::fs::File::open("/path/to/non_existent_file")
                 .map_err(|e| Failure::from_error(format!("{:?}", e.kind()))))?;
@@ -373,10 +2378,318 @@ mod test {
             r#"
 Failed (cause; NotFound)
 HISTORY:
-  [0] at src/lib.rs:354
-  [1] at src/lib.rs:359
-  [2] at src/lib.rs:363
+  [0] at src/lib.rs:2359:13
+  [2] at src/lib.rs:2368:13
+  ... and 1 more frame
 "#
         );
+
+        // The extended report contains all the events
+        assert_eq!(format!("{:#}", error).matches("\n  [").count(), 3);
+    }
+
+    #[cfg(feature = "smallvec")]
+    #[test]
+    fn short_histories_are_stored_inline() {
+        let mut history = History::new();
+        for i in 0..4 {
+            history.add(i);
+        }
+        assert!(!history.events.spilled());
+
+        history.add(4);
+        assert!(history.events.spilled());
+        assert_eq!(history.events(), [0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn track_works_with_borrowed_events() {
+        enum Event<'a> {
+            Location(Box<Location>),
+            Message(&'a str),
+        }
+        impl<'a> From<Location> for Event<'a> {
+            fn from(l: Location) -> Self {
+                Event::Location(Box::new(l))
+            }
+        }
+        impl<'a> HistoryEvent for Event<'a> {
+            fn location(&self) -> Option<&Location> {
+                match *self {
+                    Event::Location(ref l) => Some(l),
+                    Event::Message(_) => None,
+                }
+            }
+            fn location_mut(&mut self) -> Option<&mut Location> {
+                match *self {
+                    Event::Location(ref mut l) => Some(l),
+                    Event::Message(_) => None,
+                }
+            }
+        }
+        impl<'a> std::fmt::Display for Event<'a> {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                match *self {
+                    Event::Location(ref l) => l.fmt(f),
+                    Event::Message(m) => write!(f, "{}", m),
+                }
+            }
+        }
+
+        struct Op<'a>(History<Event<'a>>);
+        impl<'a> Trackable for Op<'a> {
+            type Event = Event<'a>;
+            fn history(&self) -> Option<&History<Self::Event>> {
+                Some(&self.0)
+            }
+            fn history_mut(&mut self) -> Option<&mut History<Self::Event>> {
+                Some(&mut self.0)
+            }
+        }
+
+        fn run<'a>(message: &'a str) -> Op<'a> {
+            let mut op = Op(History::new());
+            for _ in 0..3 {
+                op = track!(op);
+            }
+            op.0.add(Event::Message(message));
+            op
+        }
+
+        let message = String::from("done");
+        let op = run(&message);
+        assert_eq!(op.0.events().len(), 2);
+        assert_eq!(op.0.events()[0].location().unwrap().repeat_count(), 3);
+        assert!(op.0.to_string().ends_with("  [1] done\n"));
+    }
+
+    #[test]
+    fn cycle_compression_works() {
+        let location = |line| Location::new("foo", "src/foo.rs", line, "");
+        let mut history = History::<Location>::new();
+        for _ in 0..3 {
+            history.add_location(location(1));
+            history.add_location(location(2));
+            history.add_location(location(2));
+        }
+        history.add_location(location(1));
+        history.add_location(location(3));
+        assert_eq!(history.events().len(), 4);
+        assert_eq!(
+            history.cycles(),
+            [Cycle {
+                start: 0,
+                len: 2,
+                iterations: 3
+            }]
+        );
+        assert_eq!(
+            history.to_string(),
+            "HISTORY:\n  [0] at src/foo.rs:1\n  [1] at src/foo.rs:2 (x2)\n  \
+             ... [0] to [1] repeated (x3) ...\n  [2] at src/foo.rs:1\n  [3] at src/foo.rs:3\n"
+        );
+
+        let mut history = History::<Location>::with_capacity_limit(5);
+        history.add_location(location(0));
+        for _ in 0..4 {
+            history.add_location(location(1));
+            history.add_location(location(2));
+        }
+        history.add_location(location(3));
+        history.add_location(location(4));
+        assert_eq!(history.cycles()[0].iterations(), 4);
+        assert_eq!(history.omitted(), 0);
+
+        history.add_location(location(5));
+        let lines = history
+            .events()
+            .iter()
+            .map(|e| e.line())
+            .collect::<Vec<_>>();
+        assert_eq!(lines, [0, 1, 3, 4, 5]);
+        assert!(history.cycles().is_empty());
+        assert_eq!(history.omitted(), 7);
+    }
+
+    #[test]
+    fn history_mutation_keeps_cycles_consistent() {
+        let location = |line| Location::new("foo", "src/foo.rs", line, "");
+        let mut history = History::<Location>::new();
+        history.add_location(location(0));
+        for _ in 0..3 {
+            history.add_location(location(1));
+            history.add_location(location(2));
+        }
+        history.add_location(location(3));
+        assert_eq!(history.cycles()[0].start(), 1);
+
+        history.retain(|e| e.line() != 0);
+        assert_eq!(history.cycles()[0].start(), 0);
+
+        history.retain(|e| e.line() != 2);
+        assert!(history.cycles().is_empty());
+        assert_eq!(history.len(), 2);
+
+        history.add_location(location(4));
+        history.add_location(location(5));
+        history.add_location(location(4));
+        history.add_location(location(5));
+        assert_eq!(history.cycles().len(), 1);
+        assert!(history.pop().is_some());
+        assert!(history.cycles().is_empty());
+    }
+
+    #[test]
+    fn history_mutation_keeps_branches_consistent() {
+        let location = |line| Location::new("foo", "src/foo.rs", line, "");
+        let mut history = History::<Location>::new();
+        history.add_location(location(0));
+        history.add_location(location(1));
+        history.merge_branch("a", History::new());
+        history.add_location(location(1));
+        history.add_location(location(2));
+        assert_eq!(history.len(), 4);
+        assert_eq!(history.branches()[0].position(), 2);
+
+        history.retain(|e| e.line() != 0);
+        assert_eq!(history.branches()[0].position(), 1);
+
+        history.truncate(1);
+        assert_eq!(history.branches().len(), 1);
+        assert!(history.pop().is_some());
+        assert!(history.branches().is_empty());
+    }
+
+    #[test]
+    fn location_borrows_static_strs() {
+        use error::{ErrorKindExt, Failed};
+
+        let e: Failure = track!(Failure::from(Failed.error()));
+        let location = &e.history().unwrap().events()[0];
+        assert!(matches!(location.module_path, Cow::Borrowed(_)));
+        assert!(matches!(location.file, Cow::Borrowed(_)));
+        assert!(matches!(location.function, Cow::Borrowed(_)));
+        assert!(matches!(location.message, Cow::Owned(ref m) if m.capacity() == 0));
+    }
+
+    #[test]
+    fn history_limit_works() {
+        let mut history = History::with_capacity_limit(0);
+        history.add("foo");
+        assert!(history.events().is_empty());
+        assert_eq!(
+            history.to_string(),
+            "HISTORY:\n  ... 1 events omitted ...\n"
+        );
+
+        let mut history = History::with_capacity_limit(1);
+        history.add("foo");
+        history.add("bar");
+        assert_eq!(history.events(), ["bar"]);
+        assert_eq!(
+            history.to_string(),
+            "HISTORY:\n  ... 1 events omitted ...\n  [1] bar\n"
+        );
+
+        let mut history = History::with_capacity_limit(3);
+        for i in 0..5 {
+            history.add(i);
+        }
+        assert_eq!(history.events(), [0, 3, 4]);
+        assert_eq!(history.omitted(), 2);
+    }
+
+    #[test]
+    fn trackable_attribute_works() {
+        use error::{BoxError, ErrorKindExt, Failed};
+
+        struct Parser;
+        impl Parser {
+            #[trackable]
+            fn parse(&self, s: &str) -> Result<u8, Failure> {
+                let n = s.parse::<u8>().map_err(Failure::from_error)?;
+                Ok(n)
+            }
+        }
+
+        #[trackable]
+        fn sum(inputs: &[&str]) -> Result<u8, Failure> {
+            let parse = |s: &str| -> Result<u8, Failure> {
+                let n = Parser.parse(s)?;
+                Ok(n)
+            };
+            let mut sum = 0;
+            for s in inputs {
+                sum += parse(s)?;
+            }
+            Ok(sum)
+        }
+
+        #[trackable]
+        fn boxed() -> Result<(), BoxError> {
+            Err(Failed.cause("boxed"))?;
+            Ok(())
+        }
+
+        assert_eq!(sum(&["1", "2"]).ok(), Some(3));
+
+        // The `?` in the closure is not tracked
+        let e = sum(&["1", "foo"]).err().unwrap();
+        let history = e.history().unwrap().events();
+        assert_eq!(history.len(), 2);
+        assert!(history[0].function().ends_with("Parser::parse"));
+        assert!(history[1].function().ends_with("sum"));
+
+        // Non-trackable errors are propagated as they are
+        assert_eq!(
+            boxed().err().unwrap().to_string(),
+            "Failed (cause; boxed)\nHISTORY:\n"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn history_serde_is_compatible_with_plain_sequence() {
+        extern crate serde_json;
+
+        let mut history = History::new();
+        history.add(1);
+        history.add(2);
+        assert_eq!(serde_json::to_string(&history).unwrap(), "[1,2]");
+
+        let decoded: History<u32> = serde_json::from_str("[1,2]").unwrap();
+        assert_eq!(decoded.events(), [1, 2]);
+
+        let mut history = History::with_capacity_limit(2);
+        for i in 0..5 {
+            history.add(i);
+        }
+        let json = serde_json::to_string(&history).unwrap();
+        let decoded: History<u32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.events(), [0, 4]);
+        assert_eq!(decoded.omitted(), 3);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn history_serde_works_with_non_self_describing_formats() {
+        extern crate bincode;
+
+        let mut history = History::new();
+        history.add(1);
+        history.add(2);
+        let bytes = bincode::serialize(&history).unwrap();
+        let decoded: History<u32> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(decoded.events(), [1, 2]);
+        assert_eq!(decoded.omitted(), 0);
+
+        let mut history = History::with_capacity_limit(2);
+        for i in 0..5 {
+            history.add(i);
+        }
+        let bytes = bincode::serialize(&history).unwrap();
+        let decoded: History<u32> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(decoded.events(), [0, 4]);
+        assert_eq!(decoded.omitted(), 3);
     }
 }
@@ -4,6 +4,14 @@
 ///
 /// If `$target.in_tracking()` is `false`, it will simply return the value of `$target` untouched.
 ///
+/// Key-value fields can be attached to the tracked location by using
+/// `track!($target, { $key = $value, .. })` form (optionally followed by a message).
+/// The values are recorded in their debugging representations.
+///
+/// The message (and the format arguments) are evaluated lazily.
+/// That is, those are evaluated only if `$target` is in tracking
+/// (e.g., `$target` is an `Err` value of `Result<_, TrackableError<_>>`).
+///
 /// # Examples
 ///
 /// ```
@@ -11,6 +19,7 @@
 /// # extern crate trackable;
 /// #
 /// # fn main() {
+/// use trackable::Trackable;
 /// use trackable::error::{Failed, ErrorKindExt};
 ///
 /// // Makes a `TrackableError` value
@@ -26,12 +35,12 @@
 /// let e = Some(e);
 /// let e = track!(e, "Hello {}", "World!");
 ///
-/// assert_eq!(format!("\n{}", e.unwrap().err().unwrap()).replace('\\', "/"), r#"
-/// Failed (cause; something wrong)
+/// let e = e.unwrap().err().unwrap();
+/// assert_eq!(format!("\n{}", e.history().unwrap()).replace('\\', "/"), r#"
 /// HISTORY:
-///   [0] at src/macros.rs:10
-///   [1] at src/macros.rs:15 -- message="This is a note about this location"
-///   [2] at src/macros.rs:19 -- Hello World!
+///   [0] at src/macros.rs:10:9
+///   [1] at src/macros.rs:15:9 -- message="This is a note about this location"
+///   [2] at src/macros.rs:19:9 -- Hello World!
 /// "#);
 /// # }
 /// ```
@@ -41,10 +50,12 @@ macro_rules! track {
         {
             use $crate::Trackable;
             let mut target = $target;
-            target.track(|| {
-                let location = $crate::Location::new(
-                    module_path!(), file!(), line!(), String::new());
-                From::from(location)
+            target.track_location(|| {
+                $crate::Location::new(module_path!(), file!(), line!(), String::new())
+                    .with_column(column!())
+                    .with_function($crate::trackable_function_name!())
+                    .with_crate_version(option_env!("CARGO_PKG_VERSION").unwrap_or(""))
+                    .with_snippet($crate::trackable_source_snippet!($target))
             });
             target
         }
@@ -52,13 +63,38 @@ macro_rules! track {
     ($target:expr; $($value:expr),+) => {
         $crate::track!($target, $crate::trackable_prepare_values_fmt!($($value),+), $($value),+)
     };
+    ($target:expr, { $($key:ident = $value:expr),+ $(,)* }) => {
+        $crate::track!($target, { $($key = $value),+ }, "")
+    };
+    ($target:expr, { $($key:ident = $value:expr),+ $(,)* }, $message:expr) => {
+        {
+            use $crate::Trackable;
+            let mut target = $target;
+            target.track_location(|| {
+                let mut location = $crate::Location::new(module_path!(), file!(), line!(), $message)
+                    .with_column(column!())
+                    .with_function($crate::trackable_function_name!())
+                    .with_crate_version(option_env!("CARGO_PKG_VERSION").unwrap_or(""))
+                    .with_snippet($crate::trackable_source_snippet!($target));
+                $(location.add_field(stringify!($key), format!("{:?}", $value));)+
+                location
+            });
+            target
+        }
+    };
+    ($target:expr, { $($key:ident = $value:expr),+ $(,)* }, $($format_arg:tt)+) => {
+        $crate::track!($target, { $($key = $value),+ }, format!($($format_arg)+))
+    };
     ($target:expr, $message:expr) => {
         {
             use $crate::Trackable;
             let mut target = $target;
-            target.track(|| {
-                let location = $crate::Location::new(module_path!(), file!(), line!(), $message);
-                From::from(location)
+            target.track_location(|| {
+                $crate::Location::new(module_path!(), file!(), line!(), $message)
+                    .with_column(column!())
+                    .with_function($crate::trackable_function_name!())
+                    .with_crate_version(option_env!("CARGO_PKG_VERSION").unwrap_or(""))
+                    .with_snippet($crate::trackable_source_snippet!($target))
             });
             target
         }
@@ -73,7 +109,17 @@ macro_rules! track {
     };
 }
 
-/// The abbreviation of `track!($target.map_err(Failure::from_error), ..)`.
+/// Error trackable variant of the standard `dbg!` macro.
+///
+/// Like `dbg!`, this prints the location, the expression and the debugging representation of
+/// the value of `$target` to the standard error, and then returns the value.
+///
+/// In addition, if the value implements `Trackable` and is in tracking
+/// (e.g., it is an `Err` value of `Result<_, TrackableError<_>>`),
+/// the current location is tracked into the history with the stringified expression as the message.
+/// Thus, the intermediate points observed by this macro are also recorded in the error report.
+///
+/// `$target` can be evaluated to any value which implements `Debug` trait.
 ///
 /// # Examples
 ///
@@ -82,50 +128,58 @@ macro_rules! track {
 /// # extern crate trackable;
 /// #
 /// # fn main() {
-/// use std::sync::mpsc;
-/// use trackable::error::{Failed, ErrorKindExt};
+/// use trackable::error::{Failed, Failure, ErrorKindExt};
 ///
-/// let rx = mpsc::channel::<()>().1;
-/// let result = track_any_err!(rx.recv(), "sender dropped");
+/// let result: Result<(), Failure> = Err(Failed.cause("something wrong").into());
+/// let result = track_dbg!(result);
 ///
 /// assert_eq!(format!("\n{}", result.err().unwrap()).replace('\\', "/"), r#"
-/// Failed (cause; receiving on a closed channel)
+/// Failed (cause; something wrong)
 /// HISTORY:
-///   [0] at src/macros.rs:10 -- sender dropped
+///   [0] at src/macros.rs:8:14 -- dbg: result
 /// "#);
+///
+/// // Non-trackable values are just printed
+/// let n: u32 = track_dbg!(1 + 2);
+/// assert_eq!(n, 3);
 /// # }
 /// ```
 #[macro_export]
-macro_rules! track_any_err {
-    ($target:expr) => {
-        $target.map_err(|e| $crate::track!($crate::error::Failure::from_error(e)))
-    };
-    ($target:expr; $($arg:tt)*) => {
-        $target.map_err(|e| $crate::track!($crate::error::Failure::from_error(e); $($arg)*))
-    };
-    ($target:expr, $($arg:tt)*) => {
-        $target.map_err(|e| $crate::track!($crate::error::Failure::from_error(e), $($arg)*))
-    };
-}
-
-/// The abbreviation of `$target.map_err(|e| track!(e, ..))`.
-#[macro_export]
-macro_rules! track_err {
-    ($target:expr) => {
-        $target.map_err(|e| $crate::track!(e))
-    };
-    ($target:expr; $($arg:tt)*) => {
-        $target.map_err(|e| $crate::track!(e; $($arg)*))
-    };
-    ($target:expr, $($arg:tt)*) => {
-        $target.map_err(|e| $crate::track!(e, $($arg)*))
-    };
+macro_rules! track_dbg {
+    ($target:expr) => {{
+        #[allow(unused_imports)]
+        use $crate::{TrackDbgOther as _, TrackDbgTrackable as _};
+        let target = $target;
+        eprintln!(
+            "[{}:{}] {} = {:#?}",
+            file!(),
+            line!(),
+            stringify!($target),
+            target
+        );
+        (&target).trackable_dbg_tag().track(target, || {
+            $crate::Location::new(
+                module_path!(),
+                file!(),
+                line!(),
+                concat!("dbg: ", stringify!($target)),
+            )
+            .with_column(column!())
+            .with_function($crate::trackable_function_name!())
+            .with_crate_version(option_env!("CARGO_PKG_VERSION").unwrap_or(""))
+            .with_snippet($crate::trackable_source_snippet!($target))
+        })
+    }};
 }
 
-/// Error trackable variant of the standard `assert!` macro.
+/// Records a user-defined event into the history of `$target`.
 ///
-/// This is a simple wrapper of the `track_panic!` macro.
-/// It will call `track_panic!($error_kind, $($format_arg)+)` if `$cond` is evaluated to `false`.
+/// The current location is tracked with `$annotation` attached (see `Location::with_annotation`).
+/// The annotation is rendered as the message of the location,
+/// and can be retrieved later with its concrete type via `Location::annotation_as` method.
+///
+/// `$annotation` must be a value which implements the [Annotation](trait.Annotation.html) trait
+/// (i.e., `Debug + Display + Send + Sync + 'static`).
 ///
 /// # Examples
 ///
@@ -134,133 +188,154 @@ macro_rules! track_err {
 /// # extern crate trackable;
 /// #
 /// # fn main() {
-/// use trackable::error::{Failed, Failure};
+/// use std::fmt;
+/// use trackable::Trackable;
+/// use trackable::error::{Failed, Failure, ErrorKindExt};
 ///
-/// fn add_positive_f32(a: f32, b: f32) -> Result<f32, Failure> {
-///     track_assert!(a > 0.0 && b > 0.0, Failed; a, b);
-///     Ok(a + b)
+/// #[derive(Debug)]
+/// struct Checkpoint {
+///     stage: &'static str,
+///     rows: usize,
+/// }
+/// impl fmt::Display for Checkpoint {
+///     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+///         write!(f, "checkpoint {} ({} rows)", self.stage, self.rows)
+///     }
 /// }
 ///
-/// let r = add_positive_f32(3.0, 2.0); // Ok
-/// assert_eq!(r.ok(), Some(5.0));
+/// let e: Failure = Failed.error().into();
+/// let e = annotate!(e, Checkpoint { stage: "load", rows: 42 });
 ///
-/// let r = add_positive_f32(1.0, -2.0); // Err
-/// assert!(r.is_err());
-/// assert_eq!(format!("\n{}", r.err().unwrap()).replace('\\', "/"), r#"
-/// Failed (cause; assertion failed: `a > 0.0 && b > 0.0`; a=1.0, b=-2.0)
+/// let location = &e.history().unwrap().events()[0];
+/// assert_eq!(location.annotation_as::<Checkpoint>().map(|c| c.rows), Some(42));
+/// assert_eq!(format!("\n{}", e).replace('\\', "/"), r#"
+/// Failed
 /// HISTORY:
-///   [0] at src/macros.rs:9
+///   [0] at src/macros.rs:21:9 -- checkpoint load (42 rows)
 /// "#);
 /// # }
 /// ```
 #[macro_export]
-macro_rules! track_assert {
-    ($cond:expr, $error_kind:expr) => {
-        if ! $cond {
-            $crate::track_panic!($error_kind, "assertion failed: `{}`", stringify!($cond))
-        }
-    };
-    ($cond:expr, $error_kind:expr; $($value:expr),+) => {
-        track_assert!($cond, $error_kind, $crate::trackable_prepare_values_fmt!($($value),+), $($value),+)
-    };
-    ($cond:expr, $error_kind:expr, $message:expr) => {
-        track_assert!($cond, $error_kind, $message,)
-    };
-    ($cond:expr, $error_kind:expr, $message:expr; $($value:expr),+) => {
-        track_assert!($cond, $error_kind,
-                      concat!($message, "; ", $crate::trackable_prepare_values_fmt!($($value),+)), $($value),+)
-    };
-    ($cond:expr, $error_kind:expr, $fmt:expr, $($arg:tt)*) => {
-        if ! $cond {
-            $crate::track_panic!($error_kind,
-                         concat!("assertion failed: `{}`; ", $fmt),
-                         stringify!($cond), $($arg)*)
-        }
-    };
+macro_rules! annotate {
+    ($target:expr, $annotation:expr) => {{
+        use $crate::Trackable;
+        let mut target = $target;
+        target.track_location(|| {
+            $crate::Location::new(module_path!(), file!(), line!(), "")
+                .with_column(column!())
+                .with_function($crate::trackable_function_name!())
+                .with_crate_version(option_env!("CARGO_PKG_VERSION").unwrap_or(""))
+                .with_annotation($annotation)
+        });
+        target
+    }};
 }
 
-/// Error trackable variant of the standard `assert_ne!` macro.
+/// Conditional variant of the `track!` macro.
 ///
-/// Conceptually, `track_assert_eq!(left, right, error_kind)` is equivalent to
-/// `track_assert!(left == right, error_kind)`.
+/// This tracks the current location into the history of `$target` only if `$cond` is evaluated to `true`.
+/// Otherwise, it will simply return the value of `$target` untouched.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// # fn main() {
+/// use trackable::Trackable;
+/// use trackable::error::{Failed, ErrorKindExt};
+///
+/// let verbose = false;
+/// let e = track_if!(verbose, Failed.error(), "verbose annotation");
+/// assert_eq!(e.history().unwrap().events().len(), 0);
+///
+/// let verbose = true;
+/// let e = track_if!(verbose, e, "verbose annotation");
+/// assert_eq!(e.history().unwrap().events().len(), 1);
+/// # }
+/// ```
 #[macro_export]
-macro_rules! track_assert_eq {
-    ($left:expr, $right:expr, $error_kind:expr) => {
+macro_rules! track_if {
+    ($cond:expr, $target:expr) => {
         {
-            let left = &$left;
-            let right = &$right;
-            $crate::track_assert!(left == right, $error_kind,
-                          "assertion failed: `(left == right)` (left: `{:?}`, right: `{:?}`)",
-                          left, right)
+            let target = $target;
+            if $cond {
+                $crate::track!(target)
+            } else {
+                target
+            }
         }
     };
-    ($left:expr, $right:expr, $error_kind:expr; $($value:expr),+) => {
-        track_assert_eq!($left, $right, $error_kind,
-                         $crate::trackable_prepare_values_fmt!($($value),+), $($value),+)
-    };
-    ($left:expr, $right:expr, $error_kind:expr, $message:expr) => {
-        track_assert_eq!($left, $right, $error_kind, $message,)
-    };
-    ($left:expr, $right:expr, $error_kind:expr, $message:expr; $($value:expr),+) => {
-        track_assert_eq!($left, $right, $error_kind,
-                         concat!($message, "; ", $crate::trackable_prepare_values_fmt!($($value),+)), $($value),+)
+    ($cond:expr, $target:expr; $($arg:tt)*) => {
+        {
+            let target = $target;
+            if $cond {
+                $crate::track!(target; $($arg)*)
+            } else {
+                target
+            }
+        }
     };
-    ($left:expr, $right:expr, $error_kind:expr, $fmt:expr, $($arg:tt)*) => {
+    ($cond:expr, $target:expr, $($arg:tt)*) => {
         {
-            let left = &$left;
-            let right = &$right;
-            $crate::track_assert!(
-                left == right, $error_kind,
-                concat!("assertion failed: `(left == right)` (left: `{:?}`, right: `{:?}`): ",
-                        $fmt),
-                left, right, $($arg)*)
+            let target = $target;
+            if $cond {
+                $crate::track!(target, $($arg)*)
+            } else {
+                target
+            }
         }
     };
 }
 
-/// Error trackable variant of the standard `assert_ne!` macro.
+/// Measures the elapsed time of the evaluation of `$target` and tracks it.
 ///
-/// Conceptually, `track_assert_ne!(left, right, error_kind)` is equivalent to
-/// `track_assert!(left != right, error_kind)`.
+/// If the value of `$target` is in tracking (e.g., it is an `Err` value of `Result<_, TrackableError<_>>`),
+/// the current location is tracked into the history with a message that contains the elapsed duration.
+/// Thus, histories can also be used as coarse latency traces of the failing path.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// # fn main() {
+/// use trackable::Trackable;
+/// use trackable::error::{Failed, Failure, ErrorKindExt};
+///
+/// let result: Result<(), Failure> = track_time!(Err(Failed.error().into()), "foo");
+/// let history = result.err().unwrap().history().unwrap().clone();
+/// assert!(history.events()[0].message().starts_with("foo; elapsed="));
+/// # }
+/// ```
 #[macro_export]
-macro_rules! track_assert_ne {
-    ($left:expr, $right:expr, $error_kind:expr) => {
+macro_rules! track_time {
+    ($target:expr) => {
         {
-            let left = &$left;
-            let right = &$right;
-            $crate::track_assert!(left != right, $error_kind,
-                          "assertion failed: `(left != right)` (left: `{:?}`, right: `{:?}`)",
-                          left, right)
+            let start = ::std::time::Instant::now();
+            let target = $target;
+            let elapsed = start.elapsed();
+            $crate::track!(target, "elapsed={:?}", elapsed)
         }
     };
-    ($left:expr, $right:expr, $error_kind:expr; $($value:expr),+) => {
-        track_assert_ne!($left, $right, $error_kind,
-                         $crate::trackable_prepare_values_fmt!($($value),+), $($value),+)
-    };
-    ($left:expr, $right:expr, $error_kind:expr, $message:expr) => {
-        track_assert_ne!($left, $right, $error_kind, $message,)
-    };
-    ($left:expr, $right:expr, $error_kind:expr, $message:expr; $($value:expr),+) => {
-        track_assert_ne!($left, $right, $error_kind,
-                         concat!($message, "; ", $crate::trackable_prepare_values_fmt!($($value),+)), $($value),+)
-    };
-    ($left:expr, $right:expr, $error_kind:expr, $fmt:expr, $($arg:tt)*) => {
+    ($target:expr, $($format_arg:tt)+) => {
         {
-            let left = &$left;
-            let right = &$right;
-            $crate::track_assert!(
-                left != right, $error_kind,
-                concat!("assertion failed: `(left != right)` (left: `{:?}`, right: `{:?}`): ",
-                        $fmt),
-                left, right, $($arg)*)
+            let start = ::std::time::Instant::now();
+            let target = $target;
+            let elapsed = start.elapsed();
+            $crate::track!(target, "{}; elapsed={:?}", format_args!($($format_arg)+), elapsed)
         }
     };
 }
 
-/// Trackable assertion for `Option` values.
+/// Debug-only variant of the `track!` macro.
 ///
-/// This is a simple wrapper of the `track_panic!` macro.
-/// It will call `track_panic!` if `$expr` is evaluated to `None`.
+/// Like the standard `debug_assert!` macro, this is equivalent to `track!` in debug builds
+/// (i.e., if `debug_assertions` is enabled).
+/// Otherwise, it will simply return the value of `$target` untouched
+/// without evaluating the message and the format arguments.
 ///
 /// # Examples
 ///
@@ -269,80 +344,97 @@ macro_rules! track_assert_ne {
 /// # extern crate trackable;
 /// #
 /// # fn main() {
-/// use trackable::error::{Failed, Failure};
-///
-/// fn trackable_checked_sub(a: u32, b: u32) -> Result<u32, Failure> {
-///     let n = track_assert_some!(a.checked_sub(b), Failed);
-///     Ok(n)
-/// }
+/// use trackable::Trackable;
+/// use trackable::error::{Failed, ErrorKindExt};
 ///
-/// let r = trackable_checked_sub(10, 2); // Ok
-/// assert_eq!(r.ok(), Some(8));
+/// let e = debug_track!(Failed.error(), "Hello {}", "World!");
 ///
-/// let r = trackable_checked_sub(2, 10); // Err
-/// assert!(r.is_err());
-/// assert_eq!(format!("\n{}", r.err().unwrap()).replace('\\', "/"), r#"
-/// Failed (cause; assertion failed: `a.checked_sub(b).is_some()`)
-/// HISTORY:
-///   [0] at src/macros.rs:9
-/// "#);
+/// let expected = if cfg!(debug_assertions) { 1 } else { 0 };
+/// assert_eq!(e.history().unwrap().events().len(), expected);
 /// # }
 /// ```
 #[macro_export]
-macro_rules! track_assert_some {
-    ($expr:expr, $error_kind:expr) => {
-        if let Some(v) = $expr {
-            v
+macro_rules! debug_track {
+    ($target:expr) => {
+        if cfg!(debug_assertions) {
+            $crate::track!($target)
         } else {
-            $crate::track_panic!($error_kind, "assertion failed: `{}.is_some()`", stringify!($expr))
+            $target
         }
     };
-    ($expr:expr, $error_kind:expr; $($value:expr),+) => {
-        track_assert_some!($expr, $error_kind,
-                           $crate::trackable_prepare_values_fmt!($($value),+), $($value),+)
-    };
-    ($expr:expr, $error_kind:expr, $message:expr) => {
-        track_assert_some!($expr, $error_kind, $message,)
-    };
-    ($expr:expr, $error_kind:expr, $message:expr; $($value:expr),+) => {
-        track_assert_some!($expr, $error_kind,
-                           concat!($message, "; ", $crate::trackable_prepare_values_fmt!($($value),+)), $($value),+)
+    ($target:expr; $($arg:tt)*) => {
+        if cfg!(debug_assertions) {
+            $crate::track!($target; $($arg)*)
+        } else {
+            $target
+        }
     };
-    ($expr:expr, $error_kind:expr, $fmt:expr, $($arg:tt)*) => {
-        if let Some(v) = $expr {
-            v
+    ($target:expr, $($arg:tt)*) => {
+        if cfg!(debug_assertions) {
+            $crate::track!($target, $($arg)*)
         } else {
-            $crate::track_panic!($error_kind,
-                         concat!("assertion failed: `{}.is_some()`; ", $fmt),
-                         stringify!($expr), $($arg)*)
+            $target
         }
     };
 }
 
-/// Error trackable variant of the standard `panic!` macro.
+/// The abbreviation of `track!($target.map_err(Failure::from_error), ..)`.
 ///
-/// This returns an `TrackableError` object as the result value of the calling function,
-/// instead of aborting the current thread.
+/// `$target` must be evaluated to a `Result<T, E>` value where `E` can be converted into `BoxError`
+/// (e.g., any `std::error::Error + Send + Sync` type).
+/// The original error is kept as the cause of the resulting `Failure`,
+/// and it can be retrieved by using `TrackableError::concrete_cause` method.
 ///
-/// Conceptually, `track_panic!(error)` is equivalent to the following code:
+/// # Examples
 ///
 /// ```
 /// # #[macro_use]
 /// # extern crate trackable;
 /// #
-/// # use trackable::error::{Failed, Failure};
-/// # fn main() { let _ = foo(); }
-/// # fn foo() -> Result<(), Failure> {
-/// use trackable::Trackable;
-/// use trackable::error::TrackableError;
+/// # fn main() {
+/// use std::sync::mpsc;
+/// use trackable::error::{Failed, ErrorKindExt};
 ///
-/// # let error = Failed;
-/// let e = TrackableError::from(error); // Converts to `TrackableError`
-/// let e = track!(e);                   // Tracks this location
-/// Err(e)?;                             // Returns from the current function
-/// # Ok(())
+/// let rx = mpsc::channel::<()>().1;
+/// let result = track_any_err!(rx.recv(), "sender dropped");
+///
+/// let error = result.err().unwrap();
+/// assert!(error.concrete_cause::<mpsc::RecvError>().is_some());
+/// assert_eq!(format!("\n{}", error).replace('\\', "/"), r#"
+/// Failed (cause; receiving on a closed channel)
+/// HISTORY:
+///   [0] at src/macros.rs:10:14 -- sender dropped
+/// "#);
 /// # }
 /// ```
+#[macro_export]
+macro_rules! track_any_err {
+    ($target:expr) => {
+        $target.map_err(|e| $crate::track!($crate::trackable_any_err_to_failure!(e)))
+    };
+    ($target:expr; $($arg:tt)*) => {
+        $target.map_err(|e| $crate::track!($crate::trackable_any_err_to_failure!(e); $($arg)*))
+    };
+    ($target:expr, $($arg:tt)*) => {
+        $target.map_err(|e| $crate::track!($crate::trackable_any_err_to_failure!(e), $($arg)*))
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! trackable_any_err_to_failure {
+    ($e:expr) => {
+        $crate::error::Failure::from(
+            $crate::error::IntoTrackableError::<$crate::error::Failed>::into_trackable_error($e),
+        )
+    };
+}
+
+/// Tracks the result of an I/O operation.
+///
+/// `$expr` must be evaluated to a `std::io::Result<T>` value.
+/// This converts the `std::io::Error` into `Failure` (the original error is kept as the cause),
+/// and then tracks the current location with the stringified expression as the message.
 ///
 /// # Examples
 ///
@@ -351,7 +443,1412 @@ macro_rules! track_assert_some {
 /// # extern crate trackable;
 /// #
 /// # fn main() {
-/// use trackable::error::{Failed, Failure};
+/// use std::fs::File;
+/// use std::io;
+/// use trackable::error::Failure;
+///
+/// let path = "/path/to/non_existent_file";
+/// let result: Result<File, Failure> = track_io!(File::open(path));
+///
+/// let error = result.err().unwrap();
+/// let cause = error.concrete_cause::<io::Error>().unwrap();
+/// assert_eq!(cause.kind(), io::ErrorKind::NotFound);
+/// assert!(error.to_string().ends_with(" -- File::open(path)\n"));
+///
+/// let result = track_io!(File::open(path), "path={:?}", path);
+/// assert!(result.err().unwrap().to_string()
+///         .ends_with(" -- File::open(path); path=\"/path/to/non_existent_file\"\n"));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! track_io {
+    ($expr:expr) => {
+        {
+            let result: ::std::io::Result<_> = $expr;
+            $crate::track_any_err!(result, stringify!($expr))
+        }
+    };
+    ($expr:expr, $($format_arg:tt)+) => {
+        {
+            let result: ::std::io::Result<_> = $expr;
+            $crate::track_any_err!(result, "{}; {}", stringify!($expr), format_args!($($format_arg)+))
+        }
+    };
+}
+
+/// Fetches the environment variable `$name` from the current process.
+///
+/// This is a trackable variant of `std::env::var` and evaluated to `Result<String, Failure>`.
+/// If the variable is not present or not valid unicode,
+/// the resulting error has a cause message that contains the name of the variable.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// # fn main() {
+/// let result = track_env!("TRACKABLE_NON_EXISTENT_VARIABLE");
+/// assert_eq!(format!("\n{}", result.err().unwrap()).replace('\\', "/"), r#"
+/// Failed (cause; environment variable "TRACKABLE_NON_EXISTENT_VARIABLE": environment variable not found)
+/// HISTORY:
+///   [0] at src/macros.rs:5:14
+/// "#);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! track_env {
+    ($name:expr) => {
+        {
+            let name = &$name;
+            ::std::env::var(name).map_err(|e| {
+                let e = format!("environment variable {:?}: {}", name, e);
+                $crate::track!($crate::error::Failure::from_error(e))
+            })
+        }
+    };
+    ($name:expr; $($arg:tt)*) => {
+        {
+            let name = &$name;
+            ::std::env::var(name).map_err(|e| {
+                let e = format!("environment variable {:?}: {}", name, e);
+                $crate::track!($crate::error::Failure::from_error(e); $($arg)*)
+            })
+        }
+    };
+    ($name:expr, $($arg:tt)*) => {
+        {
+            let name = &$name;
+            ::std::env::var(name).map_err(|e| {
+                let e = format!("environment variable {:?}: {}", name, e);
+                $crate::track!($crate::error::Failure::from_error(e), $($arg)*)
+            })
+        }
+    };
+}
+
+/// Tracks the result of a lock acquisition (e.g., `Mutex::lock` or `RwLock::write`).
+///
+/// This converts the error of `$expr` (e.g., `PoisonError`) into `Failure`
+/// which has the stringified expression in the cause message,
+/// and then tracks the current location into the history of the error.
+///
+/// Note that the guard contained in the `PoisonError` is dropped.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// # fn main() {
+/// use std::panic;
+/// use std::sync::Mutex;
+///
+/// let mutex = Mutex::new(0);
+/// *track_lock!(mutex.lock()).unwrap() += 1;
+///
+/// let _ = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+///     let _guard = mutex.lock().unwrap();
+///     panic!("poisoning");
+/// }));
+/// let result = track_lock!(mutex.lock());
+/// assert_eq!(format!("\n{}", result.err().unwrap()).replace('\\', "/"), r#"
+/// Failed (cause; `mutex.lock()` failed: poisoned lock: another task failed inside)
+/// HISTORY:
+///   [0] at src/macros.rs:15:14
+/// "#);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! track_lock {
+    ($expr:expr) => {
+        $expr.map_err(|e| {
+            let e = format!("`{}` failed: {}", stringify!($expr), e);
+            $crate::track!($crate::error::Failure::from_error(e))
+        })
+    };
+    ($expr:expr; $($arg:tt)*) => {
+        $expr.map_err(|e| {
+            let e = format!("`{}` failed: {}", stringify!($expr), e);
+            $crate::track!($crate::error::Failure::from_error(e); $($arg)*)
+        })
+    };
+    ($expr:expr, $($arg:tt)*) => {
+        $expr.map_err(|e| {
+            let e = format!("`{}` failed: {}", stringify!($expr), e);
+            $crate::track!($crate::error::Failure::from_error(e), $($arg)*)
+        })
+    };
+}
+
+/// The abbreviation of `$target.map_err(|e| track!(e, ..))`.
+#[macro_export]
+macro_rules! track_err {
+    ($target:expr) => {
+        $target.map_err(|e| $crate::track!(e))
+    };
+    ($target:expr; $($arg:tt)*) => {
+        $target.map_err(|e| $crate::track!(e; $($arg)*))
+    };
+    ($target:expr, $($arg:tt)*) => {
+        $target.map_err(|e| $crate::track!(e, $($arg)*))
+    };
+}
+
+/// The abbreviation of `$target.map_err(|e| track!(<$error>::from(e), ..))`.
+///
+/// This converts the error of `$target` into `$error` type by using the `From` trait,
+/// and then tracks the current location into the history of the converted error.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// # fn main() {
+/// use std::fs::File;
+/// use trackable::Trackable;
+/// use trackable::error::IoError;
+///
+/// let result = track_map_err!(File::open("/path/to/non_existent_file"), IoError; "open");
+///
+/// let error = result.err().unwrap();
+/// assert_eq!(*error.kind(), std::io::ErrorKind::NotFound);
+/// assert_eq!(error.history().unwrap().events().len(), 1);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! track_map_err {
+    ($target:expr, $error:ty) => {
+        $target.map_err(|e| $crate::track!(<$error as From<_>>::from(e)))
+    };
+    ($target:expr, $error:ty; $($arg:tt)*) => {
+        $target.map_err(|e| $crate::track!(<$error as From<_>>::from(e); $($arg)*))
+    };
+    ($target:expr, $error:ty, $($arg:tt)*) => {
+        $target.map_err(|e| $crate::track!(<$error as From<_>>::from(e), $($arg)*))
+    };
+}
+
+/// Collects the results yielded by `$iter` into `Result<Vec<_>, _>` and tracks the error (if any).
+///
+/// `$iter` must be evaluated to a value which implements `IntoIterator<Item = Result<T, E>>`
+/// where `E` implements [Trackable](trait.Trackable.html).
+///
+/// Like `Iterator::collect`, this stops at the first `Err` item,
+/// and then the current location is tracked into the history of the error.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// # fn main() {
+/// use trackable::Trackable;
+/// use trackable::error::{Failed, Failure};
+///
+/// fn half(n: u32) -> Result<u32, Failure> {
+///     track_assert_eq!(n % 2, 0, Failed);
+///     Ok(n / 2)
+/// }
+///
+/// let result = track_all!(vec![2, 4, 6].into_iter().map(half));
+/// assert_eq!(result.ok(), Some(vec![1, 2, 3]));
+///
+/// let result = track_all!(vec![2, 3, 5].into_iter().map(half), "collecting");
+/// let history = format!("{}", result.err().unwrap().history().unwrap());
+/// assert_eq!(history.lines().count(), 3);
+/// assert!(history.ends_with("-- collecting\n"));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! track_all {
+    ($iter:expr) => {
+        $crate::track!(::std::iter::IntoIterator::into_iter($iter).collect::<Result<Vec<_>, _>>())
+    };
+    ($iter:expr; $($arg:tt)*) => {
+        $crate::track!(::std::iter::IntoIterator::into_iter($iter).collect::<Result<Vec<_>, _>>(); $($arg)*)
+    };
+    ($iter:expr, $($arg:tt)*) => {
+        $crate::track!(::std::iter::IntoIterator::into_iter($iter).collect::<Result<Vec<_>, _>>(), $($arg)*)
+    };
+}
+
+/// Zips the results of multiple (e.g., concurrently executed) operations into a single result.
+///
+/// Each `$result` must be evaluated to a `Result<_, E>` value where `E` implements
+/// [Trackable](trait.Trackable.html) (all the results must have the same error type).
+/// Up to eight results are supported.
+///
+/// If all of the results are `Ok`, this macro will be evaluated to `Ok` of the tuple of the values.
+/// Otherwise, the first error is returned and the current location is tracked into its history
+/// with a message that notes which branches failed and which succeeded.
+/// The histories of the other errors are merged into the history of the returned error
+/// as branches labelled `branch #$index`.
+///
+/// For futures, use [`track_join!`](macro.track_join.html) instead.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// # fn main() {
+/// use trackable::Trackable;
+/// use trackable::error::{Failed, Failure, ErrorKindExt};
+///
+/// let a: Result<u8, Failure> = Ok(1);
+/// let b: Result<&str, Failure> = Ok("foo");
+/// assert_eq!(track_zip!(a, b).ok(), Some((1, "foo")));
+///
+/// let a: Result<u8, Failure> = Ok(1);
+/// let b: Result<&str, Failure> = Err(Failed.cause("something wrong").into());
+/// let c: Result<(), Failure> = Err(track!(Failed.cause("timeout")).into());
+/// let e = track_zip!(a, b, c).err().unwrap();
+/// assert_eq!(e.to_string().lines().next(), Some("Failed (cause; something wrong)"));
+/// assert_eq!(format!("\n{}", e.history().unwrap()).replace('\\', "/"), r#"
+/// HISTORY:
+///   branch 'branch #2':
+///     [0] at src/macros.rs:14:34
+///   [0] at src/macros.rs:15:9 -- branch #1 failed (succeeded: [0], failed: [1, 2])
+/// "#);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! track_zip {
+    (@tuple $results:expr) => {
+        match $crate::TrackZip::track_zip($results) {
+            Ok(values) => Ok(values),
+            Err((e, succeeded, failed)) => Err($crate::track!(
+                e,
+                "branch #{} failed (succeeded: {:?}, failed: {:?})",
+                failed[0], succeeded, failed
+            )),
+        }
+    };
+    ($($result:expr),+ $(,)*) => {
+        $crate::track_zip!(@tuple ($($result,)+))
+    };
+}
+
+/// Joins multiple futures and zips their results in the same way as the `track_zip!` macro.
+///
+/// Each `$future` must be evaluated to a value which implements `Future` and
+/// its output must be a `Result<_, E>` value where `E` implements [Trackable](trait.Trackable.html)
+/// (all the outputs must have the same error type).
+/// Up to eight futures are supported.
+///
+/// This macro is evaluated to a future which polls the given futures concurrently and
+/// completes when all of them are completed (e.g., `track_join!(fetch(a), fetch(b)).await?`).
+/// The output of the future is the same as the result of
+/// [`track_zip!`](macro.track_zip.html) for the outputs of the given futures.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// # use std::sync::Arc;
+/// # use std::task::{Wake, Waker};
+/// # struct NoopWaker;
+/// # impl Wake for NoopWaker {
+/// #     fn wake(self: Arc<Self>) {}
+/// # }
+/// # fn main() {
+/// use std::future::{self, Future};
+/// use std::pin::Pin;
+/// use std::task::{Context, Poll};
+/// use trackable::error::{Failed, Failure, ErrorKindExt};
+///
+/// # let waker = Waker::from(Arc::new(NoopWaker));
+/// let mut cx = Context::from_waker(&waker);
+///
+/// let a = future::ready(Ok::<_, Failure>(1));
+/// let b = future::ready(Err::<(), Failure>(Failed.cause("something wrong").into()));
+/// let mut joined = track_join!(a, b);
+/// match Pin::new(&mut joined).poll(&mut cx) {
+///     Poll::Ready(Err(e)) => assert_eq!(format!("\n{}", e).replace('\\', "/"), r#"
+/// Failed (cause; something wrong)
+/// HISTORY:
+///   [0] at src/macros.rs:21:18 -- branch #1 failed (succeeded: [0], failed: [1])
+/// "#),
+///     _ => unreachable!(),
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! track_join {
+    ($($future:expr),+ $(,)*) => {
+        {
+            let mut futures = ($($crate::future::MaybeDone::new($future),)+);
+            ::std::future::poll_fn(move |cx| {
+                $crate::future::TrackJoin::poll_join(&mut futures, cx)
+                    .map(|results| $crate::track_zip!(@tuple results))
+            })
+        }
+    };
+}
+
+/// Makes a future which tracks the current location when the given future is completed.
+///
+/// `$future` must be evaluated to a value which implements `Future` and
+/// its output must implement [Trackable](trait.Trackable.html).
+///
+/// The resulting [`TrackFuture`](future/struct.TrackFuture.html) should be awaited
+/// in place of the original future (e.g., `track_await!(fetch(url)).await?`).
+/// If the output of the future is in tracking (e.g., it is an `Err` value of `Result<_, TrackableError<_>>`),
+/// the location of this macro is tracked into the history of the output.
+/// Thus, error histories in async code also show the await points.
+///
+/// Additional arguments compatible to `track!` macro can be specified.
+/// Note that, unlike `track!`, the message is evaluated eagerly.
+#[macro_export]
+macro_rules! track_await {
+    ($future:expr) => {
+        $crate::future::TrackFuture::new(
+            $future,
+            $crate::Location::new(module_path!(), file!(), line!(), String::new())
+                .with_column(column!())
+                .with_function($crate::trackable_function_name!())
+                .with_crate_version(option_env!("CARGO_PKG_VERSION").unwrap_or(""))
+                .with_snippet($crate::trackable_source_snippet!($future)))
+    };
+    ($future:expr; $($value:expr),+) => {
+        $crate::track_await!($future, $crate::trackable_prepare_values_fmt!($($value),+), $($value),+)
+    };
+    ($future:expr, $message:expr) => {
+        $crate::future::TrackFuture::new(
+            $future,
+            $crate::Location::new(module_path!(), file!(), line!(), $message)
+                .with_column(column!())
+                .with_function($crate::trackable_function_name!())
+                .with_crate_version(option_env!("CARGO_PKG_VERSION").unwrap_or(""))
+                .with_snippet($crate::trackable_source_snippet!($future)))
+    };
+    ($future:expr, $message:expr; $($value:expr),+) => {
+        $crate::track_await!($future, concat!($message, "; ", $crate::trackable_prepare_values_fmt!($($value),+)), $($value),+)
+    };
+    ($future:expr, $($format_arg:tt)+) => {
+        $crate::track_await!($future, format!($($format_arg)+))
+    };
+}
+
+/// Error trackable variant of the standard `assert!` macro.
+///
+/// This is a simple wrapper of the `track_panic!` macro.
+/// It will call `track_panic!($error_kind, $($format_arg)+)` if `$cond` is evaluated to `false`.
+///
+/// `$error_kind` can be omitted if the default error kind is declared by
+/// the [`trackable_default_kind!`](macro.trackable_default_kind.html) macro.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// # fn main() {
+/// use trackable::error::{Failed, Failure};
+///
+/// fn add_positive_f32(a: f32, b: f32) -> Result<f32, Failure> {
+///     track_assert!(a > 0.0 && b > 0.0, Failed; a, b);
+///     Ok(a + b)
+/// }
+///
+/// let r = add_positive_f32(3.0, 2.0); // Ok
+/// assert_eq!(r.ok(), Some(5.0));
+///
+/// let r = add_positive_f32(1.0, -2.0); // Err
+/// assert!(r.is_err());
+/// assert_eq!(format!("\n{}", r.err().unwrap()).replace('\\', "/"), r#"
+/// Failed (cause; assertion failed: `a > 0.0 && b > 0.0`; a=1.0, b=-2.0)
+/// HISTORY:
+///   [0] at src/macros.rs:9:5
+/// "#);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! track_assert {
+    ($cond:expr) => {
+        track_assert!($cond, trackable_default_error_kind())
+    };
+    ($cond:expr, $message:literal $($rest:tt)*) => {
+        track_assert!($cond, trackable_default_error_kind(), $message $($rest)*)
+    };
+    ($cond:expr, $error_kind:expr) => {
+        if ! $cond {
+            $crate::track_panic!($error_kind, "assertion failed: `{}`", stringify!($cond))
+        }
+    };
+    ($cond:expr, $error_kind:expr; $($value:expr),+) => {
+        track_assert!($cond, $error_kind, $crate::trackable_prepare_values_fmt!($($value),+), $($crate::error::TruncatedDebug(&$value)),+)
+    };
+    ($cond:expr, $error_kind:expr, $message:expr) => {
+        track_assert!($cond, $error_kind, $message,)
+    };
+    ($cond:expr, $error_kind:expr, $message:expr; $($value:expr),+) => {
+        track_assert!($cond, $error_kind,
+                      concat!($message, "; ", $crate::trackable_prepare_values_fmt!($($value),+)), $($crate::error::TruncatedDebug(&$value)),+)
+    };
+    ($cond:expr, $error_kind:expr, $fmt:expr, $($arg:tt)*) => {
+        if ! $cond {
+            $crate::track_panic!($error_kind,
+                         concat!("assertion failed: `{}`; ", $fmt),
+                         stringify!($cond), $($arg)*)
+        }
+    };
+}
+
+/// Debug-only variant of the `track_assert!` macro.
+///
+/// Like the standard `debug_assert!` macro, this is equivalent to `track_assert!` in debug builds
+/// (i.e., if `debug_assertions` is enabled).
+/// Otherwise, the condition is not evaluated and this macro does nothing.
+#[macro_export]
+macro_rules! debug_track_assert {
+    ($($arg:tt)*) => {
+        if cfg!(debug_assertions) {
+            $crate::track_assert!($($arg)*);
+        }
+    };
+}
+
+/// Error trackable variant of the standard `assert_ne!` macro.
+///
+/// Conceptually, `track_assert_eq!(left, right, error_kind)` is equivalent to
+/// `track_assert!(left == right, error_kind)`.
+#[macro_export]
+macro_rules! track_assert_eq {
+    ($left:expr, $right:expr, $error_kind:expr) => {
+        {
+            let left = &$left;
+            let right = &$right;
+            $crate::track_assert!(left == right, $error_kind,
+                          "assertion failed: `(left == right)` (left: `{:?}`, right: `{:?}`)",
+                          $crate::error::TruncatedDebug(left), $crate::error::TruncatedDebug(right))
+        }
+    };
+    ($left:expr, $right:expr, $error_kind:expr; $($value:expr),+) => {
+        track_assert_eq!($left, $right, $error_kind,
+                         $crate::trackable_prepare_values_fmt!($($value),+), $($crate::error::TruncatedDebug(&$value)),+)
+    };
+    ($left:expr, $right:expr, $error_kind:expr, $message:expr) => {
+        track_assert_eq!($left, $right, $error_kind, $message,)
+    };
+    ($left:expr, $right:expr, $error_kind:expr, $message:expr; $($value:expr),+) => {
+        track_assert_eq!($left, $right, $error_kind,
+                         concat!($message, "; ", $crate::trackable_prepare_values_fmt!($($value),+)), $($crate::error::TruncatedDebug(&$value)),+)
+    };
+    ($left:expr, $right:expr, $error_kind:expr, $fmt:expr, $($arg:tt)*) => {
+        {
+            let left = &$left;
+            let right = &$right;
+            $crate::track_assert!(
+                left == right, $error_kind,
+                concat!("assertion failed: `(left == right)` (left: `{:?}`, right: `{:?}`): ",
+                        $fmt),
+                $crate::error::TruncatedDebug(left), $crate::error::TruncatedDebug(right), $($arg)*)
+        }
+    };
+}
+
+/// Variant of the `track_assert_eq!` macro which renders a diff of the operands on failure.
+///
+/// The cause message of the resulting error contains a line-by-line diff of
+/// the pretty-printed debugging representations (i.e., `{:#?}`) of `left` and `right`.
+/// This is useful for comparing large structs or multi-line strings.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// # fn main() {
+/// use trackable::error::{Failed, Failure};
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Point {
+///     x: i32,
+///     y: i32,
+/// }
+///
+/// fn check(p: Point) -> Result<(), Failure> {
+///     track_assert_eq_diff!(p, Point { x: 1, y: 2 }, Failed);
+///     Ok(())
+/// }
+///
+/// let e = check(Point { x: 1, y: 3 }).err().unwrap();
+/// assert_eq!(format!("\n{}", e).replace('\\', "/"), r#"
+/// Failed (cause; assertion failed: `(left == right)`
+/// diff (- left, + right):
+///   Point {
+///       x: 1,
+/// -     y: 3,
+/// +     y: 2,
+///   }
+/// )
+/// HISTORY:
+///   [0] at src/macros.rs:14:5
+/// "#);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! track_assert_eq_diff {
+    ($left:expr, $right:expr, $error_kind:expr) => {
+        track_assert_eq_diff!($left, $right, $error_kind, "")
+    };
+    ($left:expr, $right:expr, $error_kind:expr; $($value:expr),+) => {
+        track_assert_eq_diff!($left, $right, $error_kind,
+                              $crate::trackable_prepare_values_fmt!($($value),+), $($crate::error::TruncatedDebug(&$value)),+)
+    };
+    ($left:expr, $right:expr, $error_kind:expr, $message:expr) => {
+        track_assert_eq_diff!($left, $right, $error_kind, $message,)
+    };
+    ($left:expr, $right:expr, $error_kind:expr, $message:expr; $($value:expr),+) => {
+        track_assert_eq_diff!($left, $right, $error_kind,
+                              concat!($message, "; ", $crate::trackable_prepare_values_fmt!($($value),+)), $($crate::error::TruncatedDebug(&$value)),+)
+    };
+    ($left:expr, $right:expr, $error_kind:expr, $fmt:expr, $($arg:tt)*) => {
+        {
+            let left = &$left;
+            let right = &$right;
+            if !(*left == *right) {
+                let message = format!($fmt, $($arg)*);
+                let diff = $crate::error::line_diff(&format!("{:#?}", $crate::error::TruncatedDebug(left)),
+                                                    &format!("{:#?}", $crate::error::TruncatedDebug(right)));
+                $crate::track_panic!($error_kind, "assertion failed: `(left == right)`{}{}\n{}",
+                                     if message.is_empty() { "" } else { ": " }, message, diff)
+            }
+        }
+    };
+}
+
+/// Error trackable variant of the standard `assert_ne!` macro.
+///
+/// Conceptually, `track_assert_ne!(left, right, error_kind)` is equivalent to
+/// `track_assert!(left != right, error_kind)`.
+#[macro_export]
+macro_rules! track_assert_ne {
+    ($left:expr, $right:expr, $error_kind:expr) => {
+        {
+            let left = &$left;
+            let right = &$right;
+            $crate::track_assert!(left != right, $error_kind,
+                          "assertion failed: `(left != right)` (left: `{:?}`, right: `{:?}`)",
+                          $crate::error::TruncatedDebug(left), $crate::error::TruncatedDebug(right))
+        }
+    };
+    ($left:expr, $right:expr, $error_kind:expr; $($value:expr),+) => {
+        track_assert_ne!($left, $right, $error_kind,
+                         $crate::trackable_prepare_values_fmt!($($value),+), $($crate::error::TruncatedDebug(&$value)),+)
+    };
+    ($left:expr, $right:expr, $error_kind:expr, $message:expr) => {
+        track_assert_ne!($left, $right, $error_kind, $message,)
+    };
+    ($left:expr, $right:expr, $error_kind:expr, $message:expr; $($value:expr),+) => {
+        track_assert_ne!($left, $right, $error_kind,
+                         concat!($message, "; ", $crate::trackable_prepare_values_fmt!($($value),+)), $($crate::error::TruncatedDebug(&$value)),+)
+    };
+    ($left:expr, $right:expr, $error_kind:expr, $fmt:expr, $($arg:tt)*) => {
+        {
+            let left = &$left;
+            let right = &$right;
+            $crate::track_assert!(
+                left != right, $error_kind,
+                concat!("assertion failed: `(left != right)` (left: `{:?}`, right: `{:?}`): ",
+                        $fmt),
+                $crate::error::TruncatedDebug(left), $crate::error::TruncatedDebug(right), $($arg)*)
+        }
+    };
+}
+
+/// Greater-than-or-equal comparison variant of the `track_assert!` macro.
+///
+/// Conceptually, `track_assert_ge!(left, right, error_kind)` is equivalent to
+/// `track_assert!(left >= right, error_kind)`, but the values of both operands are
+/// included in the cause message of the resulting error.
+#[macro_export]
+macro_rules! track_assert_ge {
+    ($left:expr, $right:expr, $error_kind:expr) => {
+        {
+            let left = &$left;
+            let right = &$right;
+            if !(left >= right) {
+                $crate::track_panic!($error_kind,
+                                     "assertion failed: `(left >= right)` (left: `{:?}`, right: `{:?}`)",
+                                     $crate::error::TruncatedDebug(left), $crate::error::TruncatedDebug(right))
+            }
+        }
+    };
+    ($left:expr, $right:expr, $error_kind:expr; $($value:expr),+) => {
+        track_assert_ge!($left, $right, $error_kind,
+                         $crate::trackable_prepare_values_fmt!($($value),+), $($crate::error::TruncatedDebug(&$value)),+)
+    };
+    ($left:expr, $right:expr, $error_kind:expr, $message:expr) => {
+        track_assert_ge!($left, $right, $error_kind, $message,)
+    };
+    ($left:expr, $right:expr, $error_kind:expr, $message:expr; $($value:expr),+) => {
+        track_assert_ge!($left, $right, $error_kind,
+                         concat!($message, "; ", $crate::trackable_prepare_values_fmt!($($value),+)), $($crate::error::TruncatedDebug(&$value)),+)
+    };
+    ($left:expr, $right:expr, $error_kind:expr, $fmt:expr, $($arg:tt)*) => {
+        {
+            let left = &$left;
+            let right = &$right;
+            if !(left >= right) {
+                $crate::track_panic!(
+                    $error_kind,
+                    concat!("assertion failed: `(left >= right)` (left: `{:?}`, right: `{:?}`): ",
+                            $fmt),
+                    $crate::error::TruncatedDebug(left), $crate::error::TruncatedDebug(right), $($arg)*)
+            }
+        }
+    };
+}
+
+/// Greater-than comparison variant of the `track_assert!` macro.
+///
+/// Conceptually, `track_assert_gt!(left, right, error_kind)` is equivalent to
+/// `track_assert!(left > right, error_kind)`, but the values of both operands are
+/// included in the cause message of the resulting error.
+#[macro_export]
+macro_rules! track_assert_gt {
+    ($left:expr, $right:expr, $error_kind:expr) => {
+        {
+            let left = &$left;
+            let right = &$right;
+            if !(left > right) {
+                $crate::track_panic!($error_kind,
+                                     "assertion failed: `(left > right)` (left: `{:?}`, right: `{:?}`)",
+                                     $crate::error::TruncatedDebug(left), $crate::error::TruncatedDebug(right))
+            }
+        }
+    };
+    ($left:expr, $right:expr, $error_kind:expr; $($value:expr),+) => {
+        track_assert_gt!($left, $right, $error_kind,
+                         $crate::trackable_prepare_values_fmt!($($value),+), $($crate::error::TruncatedDebug(&$value)),+)
+    };
+    ($left:expr, $right:expr, $error_kind:expr, $message:expr) => {
+        track_assert_gt!($left, $right, $error_kind, $message,)
+    };
+    ($left:expr, $right:expr, $error_kind:expr, $message:expr; $($value:expr),+) => {
+        track_assert_gt!($left, $right, $error_kind,
+                         concat!($message, "; ", $crate::trackable_prepare_values_fmt!($($value),+)), $($crate::error::TruncatedDebug(&$value)),+)
+    };
+    ($left:expr, $right:expr, $error_kind:expr, $fmt:expr, $($arg:tt)*) => {
+        {
+            let left = &$left;
+            let right = &$right;
+            if !(left > right) {
+                $crate::track_panic!(
+                    $error_kind,
+                    concat!("assertion failed: `(left > right)` (left: `{:?}`, right: `{:?}`): ",
+                            $fmt),
+                    $crate::error::TruncatedDebug(left), $crate::error::TruncatedDebug(right), $($arg)*)
+            }
+        }
+    };
+}
+
+/// Less-than-or-equal comparison variant of the `track_assert!` macro.
+///
+/// Conceptually, `track_assert_le!(left, right, error_kind)` is equivalent to
+/// `track_assert!(left <= right, error_kind)`, but the values of both operands are
+/// included in the cause message of the resulting error.
+#[macro_export]
+macro_rules! track_assert_le {
+    ($left:expr, $right:expr, $error_kind:expr) => {
+        {
+            let left = &$left;
+            let right = &$right;
+            if !(left <= right) {
+                $crate::track_panic!($error_kind,
+                                     "assertion failed: `(left <= right)` (left: `{:?}`, right: `{:?}`)",
+                                     $crate::error::TruncatedDebug(left), $crate::error::TruncatedDebug(right))
+            }
+        }
+    };
+    ($left:expr, $right:expr, $error_kind:expr; $($value:expr),+) => {
+        track_assert_le!($left, $right, $error_kind,
+                         $crate::trackable_prepare_values_fmt!($($value),+), $($crate::error::TruncatedDebug(&$value)),+)
+    };
+    ($left:expr, $right:expr, $error_kind:expr, $message:expr) => {
+        track_assert_le!($left, $right, $error_kind, $message,)
+    };
+    ($left:expr, $right:expr, $error_kind:expr, $message:expr; $($value:expr),+) => {
+        track_assert_le!($left, $right, $error_kind,
+                         concat!($message, "; ", $crate::trackable_prepare_values_fmt!($($value),+)), $($crate::error::TruncatedDebug(&$value)),+)
+    };
+    ($left:expr, $right:expr, $error_kind:expr, $fmt:expr, $($arg:tt)*) => {
+        {
+            let left = &$left;
+            let right = &$right;
+            if !(left <= right) {
+                $crate::track_panic!(
+                    $error_kind,
+                    concat!("assertion failed: `(left <= right)` (left: `{:?}`, right: `{:?}`): ",
+                            $fmt),
+                    $crate::error::TruncatedDebug(left), $crate::error::TruncatedDebug(right), $($arg)*)
+            }
+        }
+    };
+}
+
+/// Less-than comparison variant of the `track_assert!` macro.
+///
+/// Conceptually, `track_assert_lt!(left, right, error_kind)` is equivalent to
+/// `track_assert!(left < right, error_kind)`, but the values of both operands are
+/// included in the cause message of the resulting error.
+#[macro_export]
+macro_rules! track_assert_lt {
+    ($left:expr, $right:expr, $error_kind:expr) => {
+        {
+            let left = &$left;
+            let right = &$right;
+            if !(left < right) {
+                $crate::track_panic!($error_kind,
+                                     "assertion failed: `(left < right)` (left: `{:?}`, right: `{:?}`)",
+                                     $crate::error::TruncatedDebug(left), $crate::error::TruncatedDebug(right))
+            }
+        }
+    };
+    ($left:expr, $right:expr, $error_kind:expr; $($value:expr),+) => {
+        track_assert_lt!($left, $right, $error_kind,
+                         $crate::trackable_prepare_values_fmt!($($value),+), $($crate::error::TruncatedDebug(&$value)),+)
+    };
+    ($left:expr, $right:expr, $error_kind:expr, $message:expr) => {
+        track_assert_lt!($left, $right, $error_kind, $message,)
+    };
+    ($left:expr, $right:expr, $error_kind:expr, $message:expr; $($value:expr),+) => {
+        track_assert_lt!($left, $right, $error_kind,
+                         concat!($message, "; ", $crate::trackable_prepare_values_fmt!($($value),+)), $($crate::error::TruncatedDebug(&$value)),+)
+    };
+    ($left:expr, $right:expr, $error_kind:expr, $fmt:expr, $($arg:tt)*) => {
+        {
+            let left = &$left;
+            let right = &$right;
+            if !(left < right) {
+                $crate::track_panic!(
+                    $error_kind,
+                    concat!("assertion failed: `(left < right)` (left: `{:?}`, right: `{:?}`): ",
+                            $fmt),
+                    $crate::error::TruncatedDebug(left), $crate::error::TruncatedDebug(right), $($arg)*)
+            }
+        }
+    };
+}
+
+/// Trackable assertion for approximate equality of floating point numbers.
+///
+/// Conceptually, `track_assert_almost_eq!(left, right, epsilon, error_kind)` is equivalent to
+/// `track_assert!((left - right).abs() <= epsilon, error_kind)`, but the values of both operands,
+/// the actual delta and the allowed tolerance are included in the cause message of the resulting error.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// # fn main() {
+/// use trackable::error::{Failed, Failure};
+///
+/// fn check(a: f64, b: f64) -> Result<(), Failure> {
+///     track_assert_almost_eq!(a, b, 0.01, Failed);
+///     Ok(())
+/// }
+///
+/// assert!(check(1.0, 1.005).is_ok());
+///
+/// let e = check(1.0, 1.5).err().unwrap();
+/// assert_eq!(format!("\n{}", e).replace('\\', "/"), r#"
+/// Failed (cause; assertion failed: `(left - right).abs() <= epsilon` (left: `1.0`, right: `1.5`, delta: `0.5`, epsilon: `0.01`))
+/// HISTORY:
+///   [0] at src/macros.rs:8:5
+/// "#);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! track_assert_almost_eq {
+    ($left:expr, $right:expr, $epsilon:expr, $error_kind:expr) => {
+        track_assert_almost_eq!($left, $right, $epsilon, $error_kind, "")
+    };
+    ($left:expr, $right:expr, $epsilon:expr, $error_kind:expr; $($value:expr),+) => {
+        track_assert_almost_eq!($left, $right, $epsilon, $error_kind,
+                                $crate::trackable_prepare_values_fmt!($($value),+), $($crate::error::TruncatedDebug(&$value)),+)
+    };
+    ($left:expr, $right:expr, $epsilon:expr, $error_kind:expr, $message:expr) => {
+        track_assert_almost_eq!($left, $right, $epsilon, $error_kind, $message,)
+    };
+    ($left:expr, $right:expr, $epsilon:expr, $error_kind:expr, $message:expr; $($value:expr),+) => {
+        track_assert_almost_eq!($left, $right, $epsilon, $error_kind,
+                                concat!($message, "; ", $crate::trackable_prepare_values_fmt!($($value),+)), $($crate::error::TruncatedDebug(&$value)),+)
+    };
+    ($left:expr, $right:expr, $epsilon:expr, $error_kind:expr, $fmt:expr, $($arg:tt)*) => {
+        {
+            let left = $left;
+            let right = $right;
+            let epsilon = $epsilon;
+            let delta = (left - right).abs();
+            if !(delta <= epsilon) {
+                let message = format!($fmt, $($arg)*);
+                $crate::track_panic!(
+                    $error_kind,
+                    "assertion failed: `(left - right).abs() <= epsilon` \
+                     (left: `{:?}`, right: `{:?}`, delta: `{:?}`, epsilon: `{:?}`){}{}",
+                    $crate::error::TruncatedDebug(left), $crate::error::TruncatedDebug(right),
+                    $crate::error::TruncatedDebug(delta), $crate::error::TruncatedDebug(epsilon),
+                    if message.is_empty() { "" } else { ": " }, message)
+            }
+        }
+    };
+}
+
+/// Trackable assertion for `Option` values.
+///
+/// This is a simple wrapper of the `track_panic!` macro.
+/// It will call `track_panic!` if `$expr` is evaluated to `None`.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// # fn main() {
+/// use trackable::error::{Failed, Failure};
+///
+/// fn trackable_checked_sub(a: u32, b: u32) -> Result<u32, Failure> {
+///     let n = track_assert_some!(a.checked_sub(b), Failed);
+///     Ok(n)
+/// }
+///
+/// let r = trackable_checked_sub(10, 2); // Ok
+/// assert_eq!(r.ok(), Some(8));
+///
+/// let r = trackable_checked_sub(2, 10); // Err
+/// assert!(r.is_err());
+/// assert_eq!(format!("\n{}", r.err().unwrap()).replace('\\', "/"), r#"
+/// Failed (cause; assertion failed: `a.checked_sub(b).is_some()`)
+/// HISTORY:
+///   [0] at src/macros.rs:9:13
+/// "#);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! track_assert_some {
+    ($expr:expr, $error_kind:expr) => {
+        if let Some(v) = $expr {
+            v
+        } else {
+            $crate::track_panic!($error_kind, "assertion failed: `{}.is_some()`", stringify!($expr))
+        }
+    };
+    ($expr:expr, $error_kind:expr; $($value:expr),+) => {
+        track_assert_some!($expr, $error_kind,
+                           $crate::trackable_prepare_values_fmt!($($value),+), $($crate::error::TruncatedDebug(&$value)),+)
+    };
+    ($expr:expr, $error_kind:expr, $message:expr) => {
+        track_assert_some!($expr, $error_kind, $message,)
+    };
+    ($expr:expr, $error_kind:expr, $message:expr; $($value:expr),+) => {
+        track_assert_some!($expr, $error_kind,
+                           concat!($message, "; ", $crate::trackable_prepare_values_fmt!($($value),+)), $($crate::error::TruncatedDebug(&$value)),+)
+    };
+    ($expr:expr, $error_kind:expr, $fmt:expr, $($arg:tt)*) => {
+        if let Some(v) = $expr {
+            v
+        } else {
+            $crate::track_panic!($error_kind,
+                         concat!("assertion failed: `{}.is_some()`; ", $fmt),
+                         stringify!($expr), $($arg)*)
+        }
+    };
+}
+
+/// Trackable assertion for `Option` values that are expected to be `None`.
+///
+/// This is a simple wrapper of the `track_panic!` macro.
+/// It will call `track_panic!` if `$expr` is evaluated to `Some`.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// # fn main() {
+/// use trackable::error::{Failed, Failure};
+///
+/// fn fill(slot: &mut Option<String>, s: &str) -> Result<(), Failure> {
+///     track_assert_none!(*slot, Failed);
+///     *slot = Some(s.to_owned());
+///     Ok(())
+/// }
+///
+/// let mut slot = None;
+/// assert!(fill(&mut slot, "foo").is_ok());
+///
+/// let r = fill(&mut slot, "bar"); // Err
+/// assert!(r.is_err());
+/// assert_eq!(format!("\n{}", r.err().unwrap()).replace('\\', "/"), r#"
+/// Failed (cause; assertion failed: `*slot.is_none()` (value: `"foo"`))
+/// HISTORY:
+///   [0] at src/macros.rs:8:5
+/// "#);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! track_assert_none {
+    ($expr:expr, $error_kind:expr) => {
+        if let Some(ref v) = $expr {
+            $crate::track_panic!($error_kind, "assertion failed: `{}.is_none()` (value: `{:?}`)",
+                                 stringify!($expr), $crate::error::TruncatedDebug(v))
+        }
+    };
+    ($expr:expr, $error_kind:expr; $($value:expr),+) => {
+        track_assert_none!($expr, $error_kind,
+                           $crate::trackable_prepare_values_fmt!($($value),+), $($crate::error::TruncatedDebug(&$value)),+)
+    };
+    ($expr:expr, $error_kind:expr, $message:expr) => {
+        track_assert_none!($expr, $error_kind, $message,)
+    };
+    ($expr:expr, $error_kind:expr, $message:expr; $($value:expr),+) => {
+        track_assert_none!($expr, $error_kind,
+                           concat!($message, "; ", $crate::trackable_prepare_values_fmt!($($value),+)), $($crate::error::TruncatedDebug(&$value)),+)
+    };
+    ($expr:expr, $error_kind:expr, $fmt:expr, $($arg:tt)*) => {
+        if let Some(ref v) = $expr {
+            $crate::track_panic!($error_kind,
+                                 concat!("assertion failed: `{}.is_none()` (value: `{:?}`); ", $fmt),
+                                 stringify!($expr), $crate::error::TruncatedDebug(v), $($arg)*)
+        }
+    };
+}
+
+/// Trackable pattern matching.
+///
+/// If `$expr` matches `$pat`, this macro will be evaluated to `$value`
+/// (the variables bound by `$pat` can be used in `$value`).
+/// Otherwise, it will call `track_panic!` to return a tracked error from the calling function.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// # fn main() {
+/// use trackable::error::{Failed, Failure};
+///
+/// enum Message {
+///     Ping(u32),
+///     Quit,
+/// }
+///
+/// fn ping_seq(m: Message) -> Result<u32, Failure> {
+///     let seq = track_match!(m, Message::Ping(seq) => seq, Failed);
+///     Ok(seq)
+/// }
+///
+/// assert_eq!(ping_seq(Message::Ping(3)).ok(), Some(3));
+/// assert_eq!(format!("\n{}", ping_seq(Message::Quit).err().unwrap()).replace('\\', "/"), r#"
+/// Failed (cause; assertion failed: `m` does not match `Message::Ping(seq)`)
+/// HISTORY:
+///   [0] at src/macros.rs:13:15
+/// "#);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! track_match {
+    ($expr:expr, $pat:pat => $value:expr, $error_kind:expr) => {
+        match $expr {
+            $pat => $value,
+            _ => $crate::track_panic!($error_kind, "assertion failed: `{}` does not match `{}`",
+                                      stringify!($expr), stringify!($pat)),
+        }
+    };
+    ($expr:expr, $pat:pat => $value:expr, $error_kind:expr; $($value_:expr),+) => {
+        track_match!($expr, $pat => $value, $error_kind,
+                     $crate::trackable_prepare_values_fmt!($($value_),+), $($value_),+)
+    };
+    ($expr:expr, $pat:pat => $value:expr, $error_kind:expr, $message:expr) => {
+        track_match!($expr, $pat => $value, $error_kind, $message,)
+    };
+    ($expr:expr, $pat:pat => $value:expr, $error_kind:expr, $message:expr; $($value_:expr),+) => {
+        track_match!($expr, $pat => $value, $error_kind,
+                     concat!($message, "; ", $crate::trackable_prepare_values_fmt!($($value_),+)), $($value_),+)
+    };
+    ($expr:expr, $pat:pat => $value:expr, $error_kind:expr, $fmt:expr, $($arg:tt)*) => {
+        match $expr {
+            $pat => $value,
+            _ => $crate::track_panic!($error_kind,
+                                      concat!("assertion failed: `{}` does not match `{}`; ", $fmt),
+                                      stringify!($expr), stringify!($pat), $($arg)*),
+        }
+    };
+}
+
+/// Trackable assertion for `Result` values that are expected to be `Ok`.
+///
+/// This is a simple wrapper of the `track_panic!` macro.
+/// It will call `track_panic!` if `$expr` is evaluated to `Err`,
+/// otherwise it will be evaluated to the contained `Ok` value.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// # fn main() {
+/// use trackable::error::{Failed, Failure};
+///
+/// fn parse_u8(s: &str) -> Result<u8, Failure> {
+///     let n = track_assert_ok!(s.parse::<u8>(), Failed);
+///     Ok(n)
+/// }
+///
+/// let r = parse_u8("10"); // Ok
+/// assert_eq!(r.ok(), Some(10));
+///
+/// let r = parse_u8("foo"); // Err
+/// assert!(r.is_err());
+/// assert_eq!(format!("\n{}", r.err().unwrap()).replace('\\', "/"), r#"
+/// Failed (cause; assertion failed: `s.parse::<u8>().is_ok()` (error: `ParseIntError { kind: InvalidDigit }`))
+/// HISTORY:
+///   [0] at src/macros.rs:8:13
+/// "#);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! track_assert_ok {
+    ($expr:expr, $error_kind:expr) => {
+        match $expr {
+            Ok(v) => v,
+            Err(e) => {
+                $crate::track_panic!($error_kind, "assertion failed: `{}.is_ok()` (error: `{:?}`)",
+                                     stringify!($expr), $crate::error::TruncatedDebug(e))
+            }
+        }
+    };
+    ($expr:expr, $error_kind:expr; $($value:expr),+) => {
+        track_assert_ok!($expr, $error_kind,
+                         $crate::trackable_prepare_values_fmt!($($value),+), $($crate::error::TruncatedDebug(&$value)),+)
+    };
+    ($expr:expr, $error_kind:expr, $message:expr) => {
+        track_assert_ok!($expr, $error_kind, $message,)
+    };
+    ($expr:expr, $error_kind:expr, $message:expr; $($value:expr),+) => {
+        track_assert_ok!($expr, $error_kind,
+                         concat!($message, "; ", $crate::trackable_prepare_values_fmt!($($value),+)), $($crate::error::TruncatedDebug(&$value)),+)
+    };
+    ($expr:expr, $error_kind:expr, $fmt:expr, $($arg:tt)*) => {
+        match $expr {
+            Ok(v) => v,
+            Err(e) => {
+                $crate::track_panic!($error_kind,
+                                     concat!("assertion failed: `{}.is_ok()` (error: `{:?}`); ", $fmt),
+                                     stringify!($expr), $crate::error::TruncatedDebug(e), $($arg)*)
+            }
+        }
+    };
+}
+
+/// Trackable assertion for `Result` values that are expected to be `Err`.
+///
+/// This is a simple wrapper of the `track_panic!` macro.
+/// It will call `track_panic!` if `$expr` is evaluated to `Ok`,
+/// otherwise it will be evaluated to the contained `Err` value.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// # fn main() {
+/// use trackable::error::{Failed, Failure};
+///
+/// fn parse_error(s: &str) -> Result<std::num::ParseIntError, Failure> {
+///     let e = track_assert_err!(s.parse::<u8>(), Failed);
+///     Ok(e)
+/// }
+///
+/// let r = parse_error("foo"); // Ok
+/// assert!(r.is_ok());
+///
+/// let r = parse_error("10"); // Err
+/// assert!(r.is_err());
+/// assert_eq!(format!("\n{}", r.err().unwrap()).replace('\\', "/"), r#"
+/// Failed (cause; assertion failed: `s.parse::<u8>().is_err()` (value: `10`))
+/// HISTORY:
+///   [0] at src/macros.rs:8:13
+/// "#);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! track_assert_err {
+    ($expr:expr, $error_kind:expr) => {
+        match $expr {
+            Ok(v) => {
+                $crate::track_panic!($error_kind, "assertion failed: `{}.is_err()` (value: `{:?}`)",
+                                     stringify!($expr), $crate::error::TruncatedDebug(v))
+            }
+            Err(e) => e,
+        }
+    };
+    ($expr:expr, $error_kind:expr; $($value:expr),+) => {
+        track_assert_err!($expr, $error_kind,
+                          $crate::trackable_prepare_values_fmt!($($value),+), $($crate::error::TruncatedDebug(&$value)),+)
+    };
+    ($expr:expr, $error_kind:expr, $message:expr) => {
+        track_assert_err!($expr, $error_kind, $message,)
+    };
+    ($expr:expr, $error_kind:expr, $message:expr; $($value:expr),+) => {
+        track_assert_err!($expr, $error_kind,
+                          concat!($message, "; ", $crate::trackable_prepare_values_fmt!($($value),+)), $($crate::error::TruncatedDebug(&$value)),+)
+    };
+    ($expr:expr, $error_kind:expr, $fmt:expr, $($arg:tt)*) => {
+        match $expr {
+            Ok(v) => {
+                $crate::track_panic!($error_kind,
+                                     concat!("assertion failed: `{}.is_err()` (value: `{:?}`); ", $fmt),
+                                     stringify!($expr), $crate::error::TruncatedDebug(v), $($arg)*)
+            }
+            Err(e) => e,
+        }
+    };
+}
+
+/// Error trackable variant of the standard `unreachable!` macro.
+///
+/// This is a simple wrapper of the `track_panic!` macro.
+/// It will call `track_panic!($error_kind, "unreachable code reached: ..")`
+/// instead of aborting the current thread.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// # fn main() {
+/// use trackable::error::{Failed, Failure};
+///
+/// fn to_bool(n: u8) -> Result<bool, Failure> {
+///     match n {
+///         0 => Ok(false),
+///         1 => Ok(true),
+///         _ => track_assert_unreachable!(Failed, "n={}", n),
+///     }
+/// }
+///
+/// let r = to_bool(1); // Ok
+/// assert_eq!(r.ok(), Some(true));
+///
+/// let r = to_bool(2); // Err
+/// assert!(r.is_err());
+/// assert_eq!(format!("\n{}", r.err().unwrap()).replace('\\', "/"), r#"
+/// Failed (cause; unreachable code reached: n=2)
+/// HISTORY:
+///   [0] at src/macros.rs:11:14
+/// "#);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! track_assert_unreachable {
+    ($error_kind:expr) => {
+        $crate::track_panic!($error_kind, "unreachable code reached")
+    };
+    ($error_kind:expr; $($value:expr),+) => {
+        track_assert_unreachable!($error_kind,
+                                  $crate::trackable_prepare_values_fmt!($($value),+), $($crate::error::TruncatedDebug(&$value)),+)
+    };
+    ($error_kind:expr, $message:expr) => {
+        track_assert_unreachable!($error_kind, $message,)
+    };
+    ($error_kind:expr, $message:expr; $($value:expr),+) => {
+        track_assert_unreachable!($error_kind,
+                                  concat!($message, "; ", $crate::trackable_prepare_values_fmt!($($value),+)), $($crate::error::TruncatedDebug(&$value)),+)
+    };
+    ($error_kind:expr, $fmt:expr, $($arg:tt)*) => {
+        $crate::track_panic!($error_kind, concat!("unreachable code reached: ", $fmt), $($arg)*)
+    };
+}
+
+/// Error trackable variant of the standard `Option::expect` and `Result::expect` methods.
+///
+/// If `$expr` is evaluated to `Some(v)` or `Ok(v)`, this macro will be evaluated to `v`.
+/// Otherwise, it returns a tracked `TrackableError` of `$error_kind` from the calling function.
+///
+/// In the case of `Err(e)`, the original error `e` is kept as the cause of the resulting error.
+/// The optional message (compatible to `format!()`) is left at the tracked location.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// # fn main() {
+/// use trackable::error::{Failed, Failure};
+///
+/// fn parse(s: &str) -> Result<u8, Failure> {
+///     let n = track_expect!(s.parse::<u8>(), Failed, "parsing {:?}", s);
+///     let n = track_expect!(n.checked_mul(2), Failed);
+///     Ok(n)
+/// }
+///
+/// assert_eq!(parse("10").ok(), Some(20));
+///
+/// let e = parse("foo").err().unwrap();
+/// assert!(e.concrete_cause::<std::num::ParseIntError>().is_some());
+/// assert_eq!(format!("\n{}", e).replace('\\', "/"), r#"
+/// Failed (cause; invalid digit found in string)
+/// HISTORY:
+///   [0] at src/macros.rs:8:13 -- parsing "foo"
+/// "#);
+///
+/// let e = parse("200").err().unwrap();
+/// assert_eq!(format!("\n{}", e).replace('\\', "/"), r#"
+/// Failed (cause; `n.checked_mul(2)` is `None`)
+/// HISTORY:
+///   [0] at src/macros.rs:9:13
+/// "#);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! track_expect {
+    ($expr:expr, $error_kind:expr) => {
+        match $crate::error::TrackExpect::into_trackable_result($expr, $error_kind, stringify!($expr)) {
+            Ok(v) => v,
+            Err(e) => return Err(From::from($crate::track!(e))),
+        }
+    };
+    ($expr:expr, $error_kind:expr; $($arg:tt)*) => {
+        match $crate::error::TrackExpect::into_trackable_result($expr, $error_kind, stringify!($expr)) {
+            Ok(v) => v,
+            Err(e) => return Err(From::from($crate::track!(e; $($arg)*))),
+        }
+    };
+    ($expr:expr, $error_kind:expr, $($arg:tt)*) => {
+        match $crate::error::TrackExpect::into_trackable_result($expr, $error_kind, stringify!($expr)) {
+            Ok(v) => v,
+            Err(e) => return Err(From::from($crate::track!(e, $($arg)*))),
+        }
+    };
+}
+
+/// Parses a string into a value of the specified type by using `FromStr` trait.
+///
+/// If the parsing succeeded, this macro will be evaluated to the resulting value.
+/// Otherwise, it returns a tracked `TrackableError` of `$error_kind` from the calling function.
+/// The cause message contains the (possibly truncated) input string, the target type and
+/// the description of the parse error.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// # fn main() {
+/// use trackable::error::{Failed, Failure};
+///
+/// fn parse_port(s: &str) -> Result<u16, Failure> {
+///     let port = track_parse!(s => u16, Failed);
+///     Ok(port)
+/// }
+///
+/// assert_eq!(parse_port("80").ok(), Some(80));
+/// assert_eq!(format!("\n{}", parse_port("foo").err().unwrap()).replace('\\', "/"), r#"
+/// Failed (cause; cannot parse "foo" as `u16`: invalid digit found in string)
+/// HISTORY:
+///   [0] at src/macros.rs:8:16
+/// "#);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! track_parse {
+    ($input:expr => $ty:ty, $error_kind:expr) => {
+        {
+            let input: &str = ::std::convert::AsRef::as_ref(&$input);
+            match input.parse::<$ty>() {
+                Ok(v) => v,
+                Err(e) => {
+                    $crate::track_panic!($error_kind, "cannot parse {} as `{}`: {}",
+                                         $crate::error::truncated_input(input), stringify!($ty), e)
+                }
+            }
+        }
+    };
+    ($input:expr => $ty:ty, $error_kind:expr; $($value:expr),+) => {
+        track_parse!($input => $ty, $error_kind,
+                     $crate::trackable_prepare_values_fmt!($($value),+), $($value),+)
+    };
+    ($input:expr => $ty:ty, $error_kind:expr, $message:expr) => {
+        track_parse!($input => $ty, $error_kind, $message,)
+    };
+    ($input:expr => $ty:ty, $error_kind:expr, $message:expr; $($value:expr),+) => {
+        track_parse!($input => $ty, $error_kind,
+                     concat!($message, "; ", $crate::trackable_prepare_values_fmt!($($value),+)), $($value),+)
+    };
+    ($input:expr => $ty:ty, $error_kind:expr, $fmt:expr, $($arg:tt)*) => {
+        {
+            let input: &str = ::std::convert::AsRef::as_ref(&$input);
+            match input.parse::<$ty>() {
+                Ok(v) => v,
+                Err(e) => {
+                    $crate::track_panic!($error_kind, concat!("cannot parse {} as `{}`: {}; ", $fmt),
+                                         $crate::error::truncated_input(input), stringify!($ty), e,
+                                         $($arg)*)
+                }
+            }
+        }
+    };
+}
+
+/// Error trackable variant of the standard `panic!` macro.
+///
+/// This returns an `TrackableError` object as the result value of the calling function,
+/// instead of aborting the current thread.
+///
+/// Conceptually, `track_panic!(error)` is equivalent to the following code:
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// # use trackable::error::{Failed, Failure};
+/// # fn main() { let _ = foo(); }
+/// # fn foo() -> Result<(), Failure> {
+/// use trackable::Trackable;
+/// use trackable::error::TrackableError;
+///
+/// # let error = Failed;
+/// let e = TrackableError::from(error); // Converts to `TrackableError`
+/// let e = track!(e);                   // Tracks this location
+/// Err(e)?;                             // Returns from the current function
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// # fn main() {
+/// use trackable::error::{Failed, Failure};
 ///
 /// fn foo<F>(f: F) -> Result<(), Failure> where F: FnOnce() -> Result<(), Failure> { f() }
 ///
@@ -359,44 +1856,210 @@ macro_rules! track_assert_some {
 /// assert_eq!(format!("\n{}", e).replace('\\', "/"), r#"
 /// Failed
 /// HISTORY:
-///   [0] at src/macros.rs:10
+///   [0] at src/macros.rs:10:16
 /// "#);
 ///
 /// let e = foo(|| track_panic!(Failed, "something {}", "wrong") ).err().unwrap();
 /// assert_eq!(format!("\n{}", e).replace('\\', "/"), r#"
 /// Failed (cause; something wrong)
 /// HISTORY:
-///   [0] at src/macros.rs:17
+///   [0] at src/macros.rs:17:16
 /// "#);
 /// # }
 /// ```
 #[macro_export]
 macro_rules! track_panic {
+    ($($arg:tt)+) => {
+        {
+            let e = $crate::track_error!($($arg)+);
+            return Err(From::from(e));
+        }
+    };
+}
+
+/// Makes a tracked `TrackableError` value.
+///
+/// This is similar to the `track_panic!` macro, but it does not return from the calling function.
+/// Instead, the resulting `TrackableError` is evaluated as the value of this macro.
+/// It is useful in closures or when you want to store the error somewhere.
+/// If the first argument is a literal message (e.g., `track_error!("something {}", "wrong")`),
+/// the default error kind declared by the [`trackable_default_kind!`](macro.trackable_default_kind.html)
+/// macro will be used.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// # fn main() {
+/// use trackable::error::{Failed, Failure};
+///
+/// let errors: Vec<Failure> = (0..2)
+///     .map(|i| track_error!(Failed, "something wrong: {}", i).into())
+///     .collect();
+/// assert_eq!(format!("\n{}", errors[1]).replace('\\', "/"), r#"
+/// Failed (cause; something wrong: 1)
+/// HISTORY:
+///   [0] at src/macros.rs:8:14
+/// "#);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! track_error {
+    ($message:literal $($rest:tt)*) => {
+        track_error!(trackable_default_error_kind(), $message $($rest)*)
+    };
     ($error:expr) => {
         {
             let e = $crate::error::TrackableError::from($error);
-            let e = $crate::track!(e);
-            return Err(From::from(e));
+            $crate::track!(e)
         }
     };
     ($error:expr; $($value:expr),+) => {
-        track_panic!($error, $crate::trackable_prepare_values_fmt!($($value),+), $($value),+)
+        track_error!($error, $crate::trackable_prepare_values_fmt!($($value),+), $($value),+)
     };
     ($error_kind:expr, $message:expr) => {
         {
             use $crate::error::ErrorKindExt;
-            track_panic!($error_kind.cause($message))
+            track_error!($error_kind.cause($message))
         }
     };
     ($error:expr, $message:expr; $($value:expr),+) => {
-        track_panic!($error,
+        track_error!($error,
                      concat!($message, "; ", $crate::trackable_prepare_values_fmt!($($value),+)), $($value),+)
     };
     ($error_kind:expr, $($format_arg:tt)+) => {
         {
-            track_panic!($error_kind, format!($($format_arg)+))
+            track_error!($error_kind, format!($($format_arg)+))
+        }
+    };
+}
+
+/// Declares the default error kind used by tracking macros in the current module.
+///
+/// After the declaration, the error kind can be omitted from
+/// `track_panic!`, `track_error!` and `track_assert!` in the module
+/// (e.g., `track_panic!("something wrong")` or `track_assert!(a < b)`).
+///
+/// If the kind type implements `Default`, `trackable_default_kind!($kind)` can be used.
+/// Otherwise, the value should be specified explicitly as `trackable_default_kind!($kind = $value)`.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// use trackable::error::{Failed, Failure};
+///
+/// trackable_default_kind!(Failed);
+///
+/// fn check(n: u32) -> Result<u32, Failure> {
+///     track_assert!(n > 0);
+///     if n > 10 {
+///         track_panic!("too large: {}", n);
+///     }
+///     Ok(n)
+/// }
+///
+/// # fn main() {
+/// assert!(check(1).is_ok());
+/// assert_eq!(check(0).err().unwrap().to_string().lines().next(),
+///            Some("Failed (cause; assertion failed: `n > 0`)"));
+/// assert_eq!(check(11).err().unwrap().to_string().lines().next(),
+///            Some("Failed (cause; too large: 11)"));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! trackable_default_kind {
+    ($kind:ty = $value:expr) => {
+        #[doc(hidden)]
+        #[allow(dead_code)]
+        #[inline]
+        fn trackable_default_error_kind() -> $kind {
+            $value
         }
     };
+    ($kind:ty) => {
+        trackable_default_kind!($kind = <$kind as Default>::default());
+    };
+}
+
+/// Error trackable variant of the standard `todo!` macro.
+///
+/// This returns a tracked `Failed` error as the result value of the calling function,
+/// instead of aborting the current thread.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// # fn main() {
+/// use trackable::error::Failure;
+///
+/// fn foo(n: u32) -> Result<u32, Failure> {
+///     if n == 0 {
+///         return Ok(0);
+///     }
+///     track_todo!("n={}", n)
+/// }
+///
+/// assert_eq!(foo(0).ok(), Some(0));
+/// assert_eq!(format!("\n{}", foo(3).err().unwrap()).replace('\\', "/"), r#"
+/// Failed (cause; not yet implemented: n=3)
+/// HISTORY:
+///   [0] at src/macros.rs:11:5
+/// "#);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! track_todo {
+    () => {
+        $crate::track_panic!($crate::error::Failed, "not yet implemented")
+    };
+    ($($format_arg:tt)+) => {
+        $crate::track_panic!($crate::error::Failed, "not yet implemented: {}",
+                             format_args!($($format_arg)+))
+    };
+}
+
+/// Error trackable variant of the standard `unimplemented!` macro.
+///
+/// This returns a tracked `Failed` error as the result value of the calling function,
+/// instead of aborting the current thread.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// #
+/// # fn main() {
+/// use trackable::error::Failure;
+///
+/// fn foo() -> Result<(), Failure> {
+///     track_unimplemented!()
+/// }
+///
+/// assert_eq!(format!("\n{}", foo().err().unwrap()).replace('\\', "/"), r#"
+/// Failed (cause; not implemented)
+/// HISTORY:
+///   [0] at src/macros.rs:8:5
+/// "#);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! track_unimplemented {
+    () => {
+        $crate::track_panic!($crate::error::Failed, "not implemented")
+    };
+    ($($format_arg:tt)+) => {
+        $crate::track_panic!($crate::error::Failed, "not implemented: {}",
+                             format_args!($($format_arg)+))
+    };
 }
 
 /// More human readable variant of the standard `Result::unwrap` method.
@@ -422,25 +2085,148 @@ macro_rules! track_panic {
 /// }
 /// ```
 #[macro_export]
-macro_rules! track_try_unwrap {
-    ($expr:expr) => {
-        match $crate::track!($expr) {
-            Err(e) => { panic!("\nEXPRESSION: {}\nERROR: {}\n", stringify!($expr), e) }
-            Ok(v) => { v }
+macro_rules! track_try_unwrap {
+    ($expr:expr) => {
+        match $crate::track!($expr) {
+            Err(e) => { panic!("\nEXPRESSION: {}\nERROR: {:#}\n", stringify!($expr), e) }
+            Ok(v) => { v }
+        }
+    };
+    ($expr:expr, $($format_arg:tt)*) => {
+        match $crate::track!($expr, $($format_arg)*) {
+            Err(e) => { panic!("\nEXPRESSION: {}\nERROR: {:#}\n", stringify!($expr), e) }
+            Ok(v) => { v }
+        }
+    };
+}
+
+/// Variant of the `track_try_unwrap!` macro which exits the process instead of panicking.
+///
+/// If `$expr` is evaluated to an `Err` value, this macro prints the extended report of the error
+/// (i.e., `{:#}`) to the standard error and
+/// terminates the current process with the specified status code (the default is `1`).
+///
+/// This is useful for the top-level code of binaries,
+/// because the output does not interleave with the messages of the panic hook.
+///
+/// # Examples
+///
+/// ```no_run
+/// #[macro_use]
+/// extern crate trackable;
+///
+/// use trackable::error::{Failed, Failure, ErrorKindExt};
+///
+/// fn main() {
+///    let result: Result<(), Failure> = Err(Failed.error().into());
+///
+///    // Exits with the status code `1`.
+///    track_expect_or_exit!(result.clone());
+///
+///    // Exits with the status code `2`.
+///    track_expect_or_exit!(status = 2, result.clone());
+///
+///    // Additional arguments compatible to `format!()` can be specified.
+///    track_expect_or_exit!(status = 2, result.clone(), "Additional information: {}", "foo");
+/// }
+/// ```
+#[macro_export]
+macro_rules! track_expect_or_exit {
+    (status = $status:expr, $expr:expr) => {
+        match $crate::track!($expr) {
+            Err(e) => {
+                eprintln!("\nEXPRESSION: {}\nERROR: {:#}", stringify!($expr), e);
+                ::std::process::exit($status)
+            }
+            Ok(v) => { v }
+        }
+    };
+    (status = $status:expr, $expr:expr, $($format_arg:tt)*) => {
+        match $crate::track!($expr, $($format_arg)*) {
+            Err(e) => {
+                eprintln!("\nEXPRESSION: {}\nERROR: {:#}", stringify!($expr), e);
+                ::std::process::exit($status)
+            }
+            Ok(v) => { v }
+        }
+    };
+    ($expr:expr) => {
+        track_expect_or_exit!(status = 1, $expr)
+    };
+    ($expr:expr, $($format_arg:tt)*) => {
+        track_expect_or_exit!(status = 1, $expr, $($format_arg)*)
+    };
+}
+
+/// Implements `ErrorKind` and `Display` traits for an enum that consists of unit variants.
+///
+/// The description and the displayed form of each variant are the name of the variant by default.
+/// They can be overridden by specifying a string as `Variant => "description"`.
+///
+/// If you also want per-variant constructors or variants with fields,
+/// consider using `#[derive(ErrorKind)]` instead (see the [`error`] module).
+///
+/// [`error`]: error/index.html
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// use trackable::error::{ErrorKind as TrackableErrorKind, ErrorKindExt};
+///
+/// #[derive(Debug, Clone, PartialEq, Eq)]
+/// pub enum ErrorKind {
+///     NotFound,
+///     InvalidInput,
+///     Other,
+/// }
+/// impl_error_kind!(ErrorKind {
+///     NotFound => "not found",
+///     InvalidInput => "invalid input",
+///     Other,
+/// });
+///
+/// # fn main() {
+/// assert_eq!(ErrorKind::NotFound.to_string(), "not found");
+/// assert_eq!(ErrorKind::Other.description(), "Other");
+///
+/// let e = ErrorKind::InvalidInput.cause("something wrong");
+/// assert_eq!(e.to_string().lines().next(), Some("invalid input (cause; something wrong)"));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! impl_error_kind {
+    ($kind:ident { $($variant:ident $(=> $description:expr)*),* $(,)* }) => {
+        impl $crate::error::ErrorKind for $kind {
+            fn description(&self) -> &str {
+                match *self {
+                    $($kind::$variant => $crate::impl_error_kind!(@description $variant $(, $description)*),)*
+                }
+            }
+
+            fn display(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                f.write_str($crate::error::ErrorKind::description(self))
+            }
         }
-    };
-    ($expr:expr, $($format_arg:tt)*) => {
-        match $crate::track!($expr, $($format_arg)*) {
-            Err(e) => { panic!("\nEXPRESSION: {}\nERROR: {}\n", stringify!($expr), e) }
-            Ok(v) => { v }
+        impl ::std::fmt::Display for $kind {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                $crate::error::ErrorKind::display(self, f)
+            }
         }
     };
+    (@description $variant:ident) => {
+        stringify!($variant)
+    };
+    (@description $variant:ident, $description:expr) => {
+        $description
+    };
 }
 
 /// Implements the typical traits for a newtype $error of `TrackableError<$kind>`.
 ///
 /// The automatically implemented traits are `Deref`, `From`, `Display`, `Error`,
-/// `Trackable` and `From`.
+/// `Trackable`, `ContextualError` and `From`.
 ///
 /// This macro is useful to reduce the boilerplate code when
 /// you define a your own trackable error type.
@@ -467,6 +2253,109 @@ macro_rules! track_try_unwrap {
 ///
 /// # fn main() {}
 /// ```
+///
+/// In addition, inherent `kind()`, `into_kind()`, `history()` and `insert_ext()` methods are implemented
+/// for the newtype, so that it can be used without knowing that it dereferences to `TrackableError`.
+///
+/// `From` implementations for foreign error types can be generated by specifying those as
+/// `from: [$foreign_error_type, ..]`.
+/// The foreign error is kept as the cause of the resulting error.
+/// The kind of the error is `$kind::default()` by default,
+/// and it can be specified explicitly as `$foreign_error_type => $kind_value`.
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// use trackable::error::{TrackableError, ErrorKind as TrackableErrorKind};
+///
+/// #[derive(Debug, Default, Clone, PartialEq, Eq)]
+/// pub enum ErrorKind {
+///    Io,
+///    #[default]
+///    Other,
+/// }
+/// impl TrackableErrorKind for ErrorKind {}
+///
+/// #[derive(Debug, Clone)]
+/// pub struct Error(TrackableError<ErrorKind>);
+/// derive_traits_for_trackable_error_newtype!(Error, ErrorKind, from: [
+///     std::io::Error => ErrorKind::Io,
+///     std::num::ParseIntError,
+/// ]);
+///
+/// # fn main() {
+/// let e = Error::from(std::io::Error::new(std::io::ErrorKind::Other, "oops"));
+/// assert_eq!(*e.kind(), ErrorKind::Io);
+/// assert!(e.concrete_cause::<std::io::Error>().is_some());
+///
+/// let e = Error::from("foo".parse::<u8>().unwrap_err());
+/// assert_eq!(*e.kind(), ErrorKind::Other);
+/// assert_eq!(e.into_kind(), ErrorKind::Other);
+/// # }
+/// ```
+///
+/// Constructors for each kind can be generated by specifying those as
+/// `constructors: [$function_name => $kind_value, ..]`.
+/// Each constructor takes a cause and returns an error which records the location of the caller.
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// use trackable::error::{TrackableError, ErrorKind as TrackableErrorKind};
+///
+/// #[derive(Debug, Default, Clone, PartialEq, Eq)]
+/// pub enum ErrorKind {
+///    InvalidInput,
+///    #[default]
+///    NotFound,
+/// }
+/// impl TrackableErrorKind for ErrorKind {}
+///
+/// #[derive(Debug, Clone)]
+/// pub struct Error(TrackableError<ErrorKind>);
+/// derive_traits_for_trackable_error_newtype!(Error, ErrorKind, constructors: [
+///     invalid_input => ErrorKind::InvalidInput,
+///     not_found => ErrorKind::NotFound,
+/// ]);
+///
+/// # fn main() {
+/// let e = Error::not_found("no such entry");
+/// assert_eq!(*e.kind(), ErrorKind::NotFound);
+/// assert_eq!(e.history().map(|h| h.events().len()), Some(1));
+/// # }
+/// ```
+///
+/// If the `serde` feature of this crate is enabled, `serde` can be specified as an option
+/// to implement `Serialize` and `Deserialize` traits that delegate to the inner `TrackableError`
+/// (e.g., `derive_traits_for_trackable_error_newtype!(Error, ErrorKind, serde, from: [..])`).
+/// The kind type must also implement these traits.
+///
+/// Generic newtypes are also supported.
+/// The generic parameters of the implementations are specified in brackets,
+/// and an optional `where` clause can follow the kind type.
+/// The options above can also be specified after the kind type (or the `where` clause),
+/// e.g., `derive_traits_for_trackable_error_newtype!(impl[T] Error<T>, ErrorKind<T> where T: Debug, serde)`.
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate trackable;
+/// use std::fmt::Debug;
+/// use trackable::error::{TrackableError, ErrorKind as TrackableErrorKind, ErrorKindExt};
+///
+/// #[derive(Debug, Clone)]
+/// pub struct ErrorKind<T>(T);
+/// impl<T: Debug> TrackableErrorKind for ErrorKind<T> {}
+///
+/// #[derive(Debug, Clone)]
+/// pub struct Error<'a, T>(TrackableError<ErrorKind<&'a T>>);
+/// derive_traits_for_trackable_error_newtype!(impl['a, T] Error<'a, T>, ErrorKind<&'a T> where T: Debug);
+///
+/// # fn main() {
+/// let e: Error<u8> = ErrorKind(&1).cause("something wrong").into();
+/// let e = track!(e);
+/// assert_eq!(e.to_string().lines().next(), Some("ErrorKind(1) (cause; something wrong)"));
+/// # }
+/// ```
 #[deprecated(
     since = "0.2.19",
     note = "please use `#[derive(TrackableError)]` instead"
@@ -474,7 +2363,232 @@ macro_rules! track_try_unwrap {
 #[macro_export]
 macro_rules! derive_traits_for_trackable_error_newtype {
     ($error:ident, $kind:ty) => {
-        impl ::std::ops::Deref for $error {
+        $crate::trackable_impl_newtype_traits!($error, $kind);
+    };
+    (impl [$($generics:tt)*] $error:ident <$($error_args:tt),*>, $kind:ty) => {
+        $crate::trackable_impl_newtype_traits!(impl [$($generics)*] $error [$($error_args),*], $kind, []);
+    };
+    (impl [$($generics:tt)*] $error:ident <$($error_args:tt),*>, $kind:ty where $($rest:tt)*) => {
+        $crate::trackable_impl_newtype_traits!(
+            @bounds [$($generics)*] $error [$($error_args),*], $kind, [] $($rest)*);
+    };
+    (impl [$($generics:tt)*] $error:ident <$($error_args:tt),*>, $kind:ty, $($options:tt)+) => {
+        $crate::trackable_impl_newtype_traits!(
+            @bounds [$($generics)*] $error [$($error_args),*], $kind, [], $($options)+);
+    };
+    ($error:ident, $kind:ty, $($options:tt)+) => {
+        $crate::trackable_impl_newtype_traits!(@bounds [] $error [], $kind, [], $($options)+);
+    };
+}
+
+/// Defines a trackable error type and its kind enum in one invocation.
+///
+/// This macro emits the following items:
+/// - The kind enum that consists of the specified unit variants
+///   (`Debug`, `Clone`, `Copy`, `PartialEq`, `Eq` and `Hash` are derived)
+/// - The implementations of `ErrorKind` and `Display` for the kind (see [`impl_error_kind!`])
+/// - The newtype of `TrackableError<$kind>`
+/// - The implementations of the same traits as `#[derive(TrackableError)]` for the newtype
+///
+/// [`impl_error_kind!`]: macro.impl_error_kind.html
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate trackable;
+///
+/// use trackable::error::ErrorKindExt;
+///
+/// define_trackable_error! {
+///     /// The error type of this crate.
+///     pub Error(pub ErrorKind) {
+///         NotFound => "not found",
+///         InvalidInput => "invalid input",
+///         Other,
+///     }
+/// }
+///
+/// fn main() {
+///     let e: Error = ErrorKind::NotFound.cause("no such user").into();
+///     let e = track!(e);
+///     assert_eq!(*e.kind(), ErrorKind::NotFound);
+///     assert_eq!(e.to_string().lines().next(), Some("not found (cause; no such user)"));
+/// }
+/// ```
+#[macro_export]
+macro_rules! define_trackable_error {
+    (
+        $(#[$error_attr:meta])*
+        $error_vis:vis $error:ident($kind_vis:vis $kind:ident) {
+            $($variant:ident $(=> $description:expr)*),* $(,)*
+        }
+    ) => {
+        $(#[$error_attr])*
+        #[derive(Debug, Clone)]
+        $error_vis struct $error($crate::error::TrackableError<$kind>);
+        $crate::trackable_impl_newtype_traits!($error, $kind);
+
+        #[allow(missing_docs)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        $kind_vis enum $kind {
+            $($variant),*
+        }
+        $crate::impl_error_kind!($kind { $($variant $(=> $description)*),* });
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! trackable_impl_newtype_traits {
+    (@bounds [$($generics:tt)*] $error:ident [$($error_args:tt)*], $kind:ty, [$($bounds:tt)*]) => {
+        $crate::trackable_impl_newtype_traits!(impl [$($generics)*] $error [$($error_args)*], $kind, [$($bounds)*]);
+    };
+    (@bounds [$($generics:tt)*] $error:ident [$($error_args:tt)*], $kind:ty, [$($bounds:tt)*], serde $($rest:tt)*) => {
+        $crate::trackable_impl_newtype_traits!(@bounds [$($generics)*] $error [$($error_args)*], $kind, [$($bounds)*]);
+        $crate::trackable_impl_newtype_traits!(
+            @options [$($generics)*] $error [$($error_args)*], $kind, [$($bounds)*], serde $($rest)*);
+    };
+    (@bounds [$($generics:tt)*] $error:ident [$($error_args:tt)*], $kind:ty, [$($bounds:tt)*], from: $($rest:tt)*) => {
+        $crate::trackable_impl_newtype_traits!(@bounds [$($generics)*] $error [$($error_args)*], $kind, [$($bounds)*]);
+        $crate::trackable_impl_newtype_traits!(
+            @options [$($generics)*] $error [$($error_args)*], $kind, [$($bounds)*], from: $($rest)*);
+    };
+    (@bounds [$($generics:tt)*] $error:ident [$($error_args:tt)*], $kind:ty, [$($bounds:tt)*], constructors: $($rest:tt)*) => {
+        $crate::trackable_impl_newtype_traits!(@bounds [$($generics)*] $error [$($error_args)*], $kind, [$($bounds)*]);
+        $crate::trackable_impl_newtype_traits!(
+            @options [$($generics)*] $error [$($error_args)*], $kind, [$($bounds)*], constructors: $($rest)*);
+    };
+    (@bounds [$($generics:tt)*] $error:ident [$($error_args:tt)*], $kind:ty, [$($bounds:tt)*] $bound:tt $($rest:tt)*) => {
+        $crate::trackable_impl_newtype_traits!(
+            @bounds [$($generics)*] $error [$($error_args)*], $kind, [$($bounds)* $bound] $($rest)*);
+    };
+    (@options [$($generics:tt)*] $error:ident [$($error_args:tt)*], $kind:ty, [$($bounds:tt)*] $(,)*) => {};
+    (@options [$($generics:tt)*] $error:ident [$($error_args:tt)*], $kind:ty, [$($bounds:tt)*], serde $($rest:tt)*) => {
+        $crate::trackable_impl_newtype_traits!(@serde [$($generics)*] $error [$($error_args)*], $kind, [$($bounds)*]);
+        $crate::trackable_impl_newtype_traits!(
+            @options [$($generics)*] $error [$($error_args)*], $kind, [$($bounds)*] $($rest)*);
+    };
+    (@options $generics:tt $error:ident $error_args:tt, $kind:ty, $bounds:tt,
+     from: [$($from:ty $(=> $from_kind:expr)*),* $(,)*] $($rest:tt)*) => {
+        $($crate::trackable_impl_newtype_traits!(
+            @from $generics $error $error_args, $kind, $bounds, $from $(=> $from_kind)*);)*
+        $crate::trackable_impl_newtype_traits!(@options $generics $error $error_args, $kind, $bounds $($rest)*);
+    };
+    (@options [$($generics:tt)*] $error:ident [$($error_args:tt)*], $kind:ty, [$($bounds:tt)*],
+     constructors: [$($name:ident => $ctor_kind:expr),* $(,)*] $($rest:tt)*) => {
+        impl<$($generics)*> $error<$($error_args)*>
+        where
+            $kind: $crate::error::ErrorKind,
+            $($bounds)*
+        {
+            $(
+                #[allow(dead_code)]
+                #[track_caller]
+                pub fn $name<Cause>(cause: Cause) -> Self
+                where
+                    Cause: Into<$crate::error::BoxError>,
+                {
+                    use $crate::error::ErrorKindExt;
+                    use $crate::Trackable;
+                    let kind: $kind = $ctor_kind;
+                    let mut e: Self = kind.cause(cause).into();
+                    e.track_here();
+                    e
+                }
+            )*
+        }
+        $crate::trackable_impl_newtype_traits!(
+            @options [$($generics)*] $error [$($error_args)*], $kind, [$($bounds)*] $($rest)*);
+    };
+    (@serde [$($generics:tt)*] $error:ident [$($error_args:tt)*], $kind:ty, [$($bounds:tt)*]) => {
+        impl<$($generics)*> $crate::serde::Serialize for $error<$($error_args)*>
+        where
+            $crate::error::TrackableError<$kind>: $crate::serde::Serialize,
+            $($bounds)*
+        {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: $crate::serde::Serializer,
+            {
+                $crate::serde::Serialize::serialize(&self.0, serializer)
+            }
+        }
+        impl<'de, $($generics)*> $crate::serde::Deserialize<'de> for $error<$($error_args)*>
+        where
+            $crate::error::TrackableError<$kind>: $crate::serde::Deserialize<'de>,
+            $($bounds)*
+        {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: $crate::serde::Deserializer<'de>,
+            {
+                let inner: $crate::error::TrackableError<$kind> =
+                    $crate::serde::Deserialize::deserialize(deserializer)?;
+                Ok($error(inner))
+            }
+        }
+    };
+    (@from [$($generics:tt)*] $error:ident [$($error_args:tt)*], $kind:ty, [$($bounds:tt)*], $from:ty) => {
+        $crate::trackable_impl_newtype_traits!(
+            @from [$($generics)*] $error [$($error_args)*], $kind, [$($bounds)*],
+            $from => <$kind as Default>::default());
+    };
+    (@from [$($generics:tt)*] $error:ident [$($error_args:tt)*], $kind:ty, [$($bounds:tt)*],
+     $from:ty => $from_kind:expr) => {
+        impl<$($generics)*> From<$from> for $error<$($error_args)*>
+        where
+            $kind: $crate::error::ErrorKind,
+            $($bounds)*
+        {
+            fn from(f: $from) -> Self {
+                use $crate::error::ErrorKindExt;
+                let kind: $kind = $from_kind;
+                kind.cause(f).into()
+            }
+        }
+    };
+    ($error:ident, $kind:ty) => {
+        $crate::trackable_impl_newtype_traits!(impl [] $error [], $kind, []);
+    };
+    (impl [$($generics:tt)*] $error:ident [$($error_args:tt)*], $kind:ty, [$($bounds:tt)*]) => {
+        #[allow(dead_code)]
+        impl<$($generics)*> $error<$($error_args)*>
+        where
+            $kind: $crate::error::ErrorKind,
+            $($bounds)*
+        {
+            /// Returns the kind of this error.
+            #[inline]
+            pub fn kind(&self) -> &$kind {
+                self.0.kind()
+            }
+
+            /// Converts this error into its kind.
+            #[inline]
+            pub fn into_kind(self) -> $kind {
+                self.0.into_kind()
+            }
+
+            /// Returns the tracking history of this error.
+            #[inline]
+            pub fn history(&self) -> Option<&$crate::History<$crate::Location>> {
+                $crate::Trackable::history(&self.0)
+            }
+
+            /// Attaches a typed value to this error.
+            #[inline]
+            pub fn insert_ext<Ext>(&mut self, value: Ext)
+            where
+                Ext: ::std::any::Any + Send + Sync,
+            {
+                self.0.insert_ext(value)
+            }
+        }
+        impl<$($generics)*> ::std::ops::Deref for $error<$($error_args)*>
+        where
+            $($bounds)*
+        {
             type Target = $crate::error::TrackableError<$kind>;
 
             #[inline]
@@ -482,17 +2596,29 @@ macro_rules! derive_traits_for_trackable_error_newtype {
                 &self.0
             }
         }
-        impl ::std::fmt::Display for $error {
+        impl<$($generics)*> ::std::fmt::Display for $error<$($error_args)*>
+        where
+            $kind: $crate::error::ErrorKind,
+            $($bounds)*
+        {
             fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
-                self.0.fmt(f)
+                ::std::fmt::Display::fmt(&self.0, f)
             }
         }
-        impl ::std::error::Error for $error {
-            fn source(&self) -> Option<&(::std::error::Error + 'static)> {
+        impl<$($generics)*> ::std::error::Error for $error<$($error_args)*>
+        where
+            $kind: $crate::error::ErrorKind,
+            $error<$($error_args)*>: ::std::fmt::Debug,
+            $($bounds)*
+        {
+            fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
                 self.0.source()
             }
         }
-        impl $crate::Trackable for $error {
+        impl<$($generics)*> $crate::Trackable for $error<$($error_args)*>
+        where
+            $($bounds)*
+        {
             type Event = $crate::Location;
 
             #[inline]
@@ -505,19 +2631,41 @@ macro_rules! derive_traits_for_trackable_error_newtype {
                 self.0.history_mut()
             }
         }
-        impl From<$crate::error::TrackableError<$kind>> for $error {
+        impl<$($generics)*> $crate::error::ContextualError for $error<$($error_args)*>
+        where
+            $($bounds)*
+        {
+            #[inline]
+            fn add_context<TrackableContext>(&mut self, context: TrackableContext)
+            where
+                TrackableContext: ::std::fmt::Display,
+            {
+                $crate::error::ContextualError::add_context(&mut self.0, context)
+            }
+        }
+        impl<$($generics)*> From<$crate::error::TrackableError<$kind>> for $error<$($error_args)*>
+        where
+            $($bounds)*
+        {
             #[inline]
             fn from(f: $crate::error::TrackableError<$kind>) -> Self {
                 $error(f)
             }
         }
-        impl From<$error> for $crate::error::TrackableError<$kind> {
+        impl<$($generics)*> From<$error<$($error_args)*>> for $crate::error::TrackableError<$kind>
+        where
+            $($bounds)*
+        {
             #[inline]
-            fn from(f: $error) -> Self {
+            fn from(f: $error<$($error_args)*>) -> Self {
                 f.0
             }
         }
-        impl From<$kind> for $error {
+        impl<$($generics)*> From<$kind> for $error<$($error_args)*>
+        where
+            $kind: $crate::error::ErrorKind,
+            $($bounds)*
+        {
             #[inline]
             fn from(f: $kind) -> Self {
                 use $crate::error::ErrorKindExt;
@@ -527,6 +2675,36 @@ macro_rules! derive_traits_for_trackable_error_newtype {
     };
 }
 
+#[doc(hidden)]
+#[macro_export]
+macro_rules! trackable_function_name {
+    () => {{
+        fn __trackable_function() {}
+        fn type_name_of<T>(_: T) -> &'static str {
+            ::std::any::type_name::<T>()
+        }
+        $crate::strip_function_name(type_name_of(__trackable_function))
+    }};
+}
+
+#[cfg(feature = "source-snippets")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! trackable_source_snippet {
+    ($($tokens:tt)*) => {
+        $crate::trackable_snippet::source_line!($($tokens)*)
+    };
+}
+
+#[cfg(not(feature = "source-snippets"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! trackable_source_snippet {
+    ($($tokens:tt)*) => {
+        ""
+    };
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! trackable_prepare_values_fmt {
@@ -539,9 +2717,28 @@ macro_rules! trackable_prepare_values_fmt {
     };
 }
 
+/// Tracks `$result` if it is `Trackable` (used by `#[trackable]` attribute).
+#[doc(hidden)]
+#[macro_export]
+macro_rules! trackable_track_try {
+    ($result:expr, $($snippet:tt)*) => {{
+        #[allow(unused_imports)]
+        use $crate::{TrackDbgOther as _, TrackDbgTrackable as _};
+        let result = $result;
+        (&result).trackable_dbg_tag().track(result, || {
+            $crate::Location::new(module_path!(), file!(), line!(), String::new())
+                .with_column(column!())
+                .with_function($crate::trackable_function_name!())
+                .with_crate_version(option_env!("CARGO_PKG_VERSION").unwrap_or(""))
+                .with_snippet($crate::trackable_source_snippet!($($snippet)*))
+        })
+    }};
+}
+
 #[cfg(test)]
 mod test {
     use error::{ErrorKindExt, Failed, Failure};
+    use Trackable;
 
     #[test]
     fn track_works() {
@@ -558,6 +2755,210 @@ mod test {
         assert!(foo(Ok(())).is_ok());
     }
 
+    #[test]
+    fn track_captures_function_name() {
+        fn flush_buffers() -> Result<(), Failure> {
+            let f = || -> Result<(), Failure> { track!(Err(Failed.error().into())) };
+            track!(f())
+        }
+        let e = flush_buffers().err().unwrap();
+        let events = e.history().unwrap().events();
+        assert_eq!(
+            events[0].function(),
+            "trackable::macros::test::track_captures_function_name::flush_buffers"
+        );
+        assert_eq!(events[1].function(), events[0].function());
+    }
+
+    #[test]
+    fn track_captures_crate_name_and_version() {
+        let e: Failure = track!(Failure::from(Failed.error()));
+        let location = &e.history().unwrap().events()[0];
+        assert_eq!(location.crate_name(), "trackable");
+        assert_eq!(location.crate_version(), env!("CARGO_PKG_VERSION"));
+    }
+
+    #[test]
+    fn track_captures_source_snippet() {
+        let e: Failure = Failed.error().into();
+        let e = track!(e, "first");
+        let expected = if cfg!(feature = "source-snippets") {
+            r#"let e = track!(e, "first");"#
+        } else {
+            ""
+        };
+        assert_eq!(e.history().unwrap().events()[0].snippet(), expected);
+    }
+
+    #[test]
+    fn track_in_loop_is_deduplicated() {
+        let mut e: Failure = Failed.error().into();
+        for _ in 0..214 {
+            e = track!(e);
+        }
+        e = track!(e, "done");
+        let events = e.history().unwrap().events();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].repeat_count(), 214);
+        assert!(events[0].to_string().ends_with(" (x214)"));
+        assert_eq!(events[1].repeat_count(), 1);
+    }
+
+    #[test]
+    fn track_message_is_lazily_evaluated() {
+        use std::cell::Cell;
+
+        let count = Cell::new(0);
+        let message = || {
+            count.set(count.get() + 1);
+            "hello"
+        };
+
+        let ok: Result<(), Failure> = Ok(());
+        let _ = track!(ok.clone(), "{}", message());
+        let _ = track!(ok; message());
+        assert_eq!(count.get(), 0);
+
+        let err: Result<(), Failure> = Err(Failed.error().into());
+        let _ = track!(err, "{}", message());
+        assert_eq!(count.get(), 1);
+    }
+
+    #[test]
+    fn track_with_fields_works() {
+        use Trackable;
+
+        let user_id = 42;
+        let e = track!(Failure::from(Failed.error()), { user_id = user_id, name = "foo" });
+        let e = track!(e, { retries = 3 }, "retry {}", "exhausted");
+
+        let events = e.history().unwrap().events();
+        assert_eq!(events[0].field("user_id"), Some("42"));
+        assert_eq!(events[0].field("name"), Some("\"foo\""));
+        assert_eq!(events[1].fields().len(), 1);
+        assert_eq!(events[1].message(), "retry exhausted");
+        assert_eq!(
+            events[0].to_string().split(" -- ").nth(1),
+            Some("user_id=42, name=\"foo\"")
+        );
+        assert_eq!(
+            events[1].to_string().split(" -- ").nth(1),
+            Some("retry exhausted; retries=3")
+        );
+    }
+
+    #[test]
+    fn debug_track_assert_works() {
+        fn check(n: u32) -> Result<u32, Failure> {
+            debug_track_assert!(n > 0, Failed; n);
+            Ok(n)
+        }
+
+        assert!(check(1).is_ok());
+        assert_eq!(check(0).is_err(), cfg!(debug_assertions));
+    }
+
+    #[test]
+    fn track_parse_works() {
+        fn parse(s: &str) -> Result<u8, Failure> {
+            let n = track_parse!(s => u8, Failed, "n={}", 1);
+            Ok(n)
+        }
+
+        assert_eq!(parse("10").ok(), Some(10));
+
+        let input = "1".repeat(100);
+        let e = parse(&input).err().unwrap();
+        assert_eq!(
+            e.to_string().lines().next(),
+            Some(
+                format!(
+                    "Failed (cause; cannot parse {:?}... (100 bytes) as `u8`: \
+                     number too large to fit in target type; n=1)",
+                    "1".repeat(64)
+                )
+                .as_str()
+            )
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    #[allow(deprecated)]
+    fn newtype_serde_option_works() {
+        use error::TrackableError;
+        use serde::de::DeserializeOwned;
+        use serde::Serialize;
+
+        #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+        struct Kind;
+        impl ::error::ErrorKind for Kind {}
+
+        #[derive(Debug, Clone)]
+        struct Error(TrackableError<Kind>);
+        derive_traits_for_trackable_error_newtype!(Error, Kind, serde, from: [::std::io::Error]);
+
+        fn assert_serde<T: Serialize + DeserializeOwned>() {}
+        assert_serde::<Error>();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    #[allow(deprecated)]
+    fn generic_newtype_serde_option_works() {
+        use error::TrackableError;
+        use serde::de::DeserializeOwned;
+        use serde::Serialize;
+        use std::fmt::Debug;
+
+        #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+        struct Kind<T>(T);
+        impl<T: Debug> ::error::ErrorKind for Kind<T> {}
+
+        #[derive(Debug, Clone)]
+        struct Error<T>(TrackableError<Kind<T>>);
+        derive_traits_for_trackable_error_newtype!(impl[T] Error<T>, Kind<T> where T: Debug, serde);
+
+        fn assert_serde<T: Serialize + DeserializeOwned>() {}
+        assert_serde::<Error<u8>>();
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn generic_newtype_options_work() {
+        use error::TrackableError;
+        use std::fmt::Debug;
+
+        #[derive(Debug, Clone, Default, PartialEq, Eq)]
+        struct Kind<T>(T);
+        impl<T: Debug> ::error::ErrorKind for Kind<T> {}
+
+        #[derive(Debug, Clone)]
+        struct Error<T>(TrackableError<Kind<T>>);
+        derive_traits_for_trackable_error_newtype!(
+            impl[T] Error<T>, Kind<T> where T: Debug + Default,
+            from: [::std::io::Error],
+            constructors: [invalid => Kind(T::default())]
+        );
+
+        #[derive(Debug, Clone)]
+        struct Plain<T>(TrackableError<Kind<T>>);
+        derive_traits_for_trackable_error_newtype!(
+            impl[T: Default] Plain<T>, Kind<T>,
+            constructors: [invalid => Kind(T::default())]
+        );
+
+        let e = Error::<u8>::invalid("oops");
+        assert_eq!(*e.kind(), Kind(0));
+        assert_eq!(e.history().map(|h| h.events().len()), Some(1));
+
+        let e = Error::<u8>::from(::std::io::Error::other("oops"));
+        assert!(e.concrete_cause::<::std::io::Error>().is_some());
+
+        let e = Plain::<String>::invalid("oops");
+        assert_eq!(*e.kind(), Kind(String::new()));
+    }
+
     #[test]
     fn track_assert_works() {
         fn add_positive_f32(a: f32, b: f32) -> Result<f32, Failure> {
@@ -575,11 +2976,52 @@ mod test {
             r#"
 Failed (cause; assertion failed: `a > 0.0 && b > 0.0`)
 HISTORY:
-  [0] at src/macros.rs:564
+  [0] at src/macros.rs:2965:13
 "#
         );
     }
 
+    #[test]
+    fn track_assert_values_are_truncated() {
+        fn check(buf: &[u8]) -> Result<(), Failure> {
+            track_assert!(buf.is_empty(), Failed; buf.len(), buf);
+            Ok(())
+        }
+
+        let e = check(&[0; 10_000]).err().unwrap();
+        let message = e.to_string();
+        let message = message.lines().next().unwrap();
+        assert!(message.starts_with(
+            "Failed (cause; assertion failed: `buf.is_empty()`; buf.len()=10000, buf=[0, 0, "
+        ));
+        let len = format!("{:?}", &[0u8; 10_000][..]).len();
+        assert!(message.ends_with(&format!("...({} bytes))", len)));
+        assert!(message.len() < 4200);
+    }
+
+    #[test]
+    fn track_assert_ordering_works() {
+        fn check_range(n: u32) -> Result<u32, Failure> {
+            track_assert_ge!(n, 10, Failed);
+            track_assert_lt!(n, 20, Failed, "n is too large");
+            Ok(n)
+        }
+
+        assert_eq!(check_range(15).ok(), Some(15));
+
+        let e = check_range(3).err().unwrap();
+        assert_eq!(
+            e.to_string().lines().next(),
+            Some("Failed (cause; assertion failed: `(left >= right)` (left: `3`, right: `10`))")
+        );
+
+        let e = check_range(20).err().unwrap();
+        assert_eq!(
+            e.to_string().lines().next(),
+            Some("Failed (cause; assertion failed: `(left < right)` (left: `20`, right: `20`): n is too large)")
+        );
+    }
+
     #[test]
     #[should_panic]
     fn track_try_unwrap_works() {
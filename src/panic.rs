@@ -0,0 +1,231 @@
+//! Conversion from panics into trackable errors.
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::panic::{self, UnwindSafe};
+use std::sync::Once;
+use std::thread;
+
+use error::{ErrorKindExt, Failed, Failure};
+use {Location, Trackable};
+
+thread_local! {
+    static PANIC_LOCATION: RefCell<Option<(String, u32, u32)>> = const { RefCell::new(None) };
+    static CATCHING: Cell<usize> = const { Cell::new(0) };
+}
+
+static INSTALL_LOCATION_RECORDER: Once = Once::new();
+
+/// Invokes `f`, converting a panic (if any) into a tracked `Failure`.
+///
+/// If `f` panics, the resulting error has the panic message as its cause.
+/// The history of the error consists of the location where the panic occurred
+/// (if it could be obtained) and the location of the caller of this function.
+///
+/// In order to obtain the location of a panic, this function chains a panic hook
+/// that records the location to the current hook at the first call.
+///
+/// Note that this is a process-wide side effect which outlives the call:
+/// the hook stays installed until the process exits,
+/// and it also runs (and then delegates to the previous hook) for panics outside of `catch`.
+/// If a hook is set by `std::panic::set_hook` afterwards, it replaces the recorder,
+/// and the locations of panics are no longer included in the histories.
+///
+/// # Examples
+///
+/// ```
+/// use trackable::panic;
+///
+/// assert_eq!(panic::catch(|| 1 + 2).ok(), Some(3));
+///
+/// let e = panic::catch(|| -> u32 { panic!("oops") }).err().unwrap();
+/// assert_eq!(e.to_string().lines().next(), Some("Failed (cause; panicked: oops)"));
+/// ```
+#[track_caller]
+pub fn catch<F, T>(f: F) -> Result<T, Failure>
+where
+    F: FnOnce() -> T + UnwindSafe,
+{
+    let caller = panic::Location::caller();
+    install_location_recorder();
+    PANIC_LOCATION.with(|l| l.borrow_mut().take());
+
+    CATCHING.with(|c| c.set(c.get() + 1));
+    let result = panic::catch_unwind(f);
+    CATCHING.with(|c| c.set(c.get() - 1));
+    let payload = match result {
+        Ok(v) => return Ok(v),
+        Err(payload) => payload,
+    };
+    let message = format!("panicked: {}", payload_message(&*payload));
+    let mut e: Failure = Failed.cause(message).into();
+    if let Some((file, line, column)) = PANIC_LOCATION.with(|l| l.borrow_mut().take()) {
+        e.track(|| Location::new("", file, line, "panicked here").with_column(column));
+    }
+    e.track(|| Location::new("", caller.file(), caller.line(), "").with_column(caller.column()));
+    Err(e)
+}
+
+/// Installs a panic hook that prints panics as reports in the style of `TrackableError`.
+///
+/// If the payload of a panic is a `Failure`, the report contains its history
+/// followed by the location of the panic.
+/// If the panic message is already a report of a trackable error
+/// (e.g., a panic caused by [track_try_unwrap!](../macro.track_try_unwrap.html)),
+/// it is printed as is.
+///
+/// The hook is chained to the current one, which still handles the panics caught by
+/// [catch](fn.catch.html), so this function and `catch` can be used together in any order.
+///
+/// # Examples
+///
+/// ```no_run
+/// #[macro_use]
+/// extern crate trackable;
+///
+/// use trackable::error::Failed;
+///
+/// fn main() {
+///     trackable::panic::install_hook();
+///
+///     // Prints a report like the following:
+///     //
+///     // thread 'main' panicked
+///     // ERROR: Failed (cause; oops)
+///     // HISTORY:
+///     //   [0] at src/main.rs:11:5 -- panicked
+///     panic!("oops");
+/// }
+/// ```
+pub fn install_hook() {
+    let prev = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if CATCHING.with(|c| c.get()) > 0 {
+            prev(info);
+            return;
+        }
+        let thread = thread::current();
+        let location = info.location().map(|l| (l.file(), l.line(), l.column()));
+        let report = render_report(
+            thread.name().unwrap_or("<unnamed>"),
+            info.payload(),
+            location,
+        );
+        eprint!("{}", report);
+    }));
+}
+
+fn render_report(
+    thread: &str,
+    payload: &(dyn Any + Send),
+    location: Option<(&str, u32, u32)>,
+) -> String {
+    let mut e = if let Some(e) = payload.downcast_ref::<Failure>() {
+        e.clone()
+    } else {
+        let message = payload_message(payload);
+        if message.contains("\nHISTORY:\n") {
+            let header = match location {
+                Some((file, line, column)) => format!(
+                    "thread '{}' panicked at {}:{}:{}",
+                    thread, file, line, column
+                ),
+                None => format!("thread '{}' panicked", thread),
+            };
+            return format!("{}\n{}\n", header, message.trim());
+        }
+        Failure::from(Failed.cause(message.to_owned()))
+    };
+    if let Some((file, line, column)) = location {
+        e.track(|| Location::new("", file.to_owned(), line, "panicked").with_column(column));
+    }
+    format!("thread '{}' panicked\nERROR: {:#}", thread, e)
+}
+
+fn install_location_recorder() {
+    INSTALL_LOCATION_RECORDER.call_once(|| {
+        let prev = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if let Some(location) = info.location() {
+                let location = (
+                    location.file().to_owned(),
+                    location.line(),
+                    location.column(),
+                );
+                PANIC_LOCATION.with(|l| *l.borrow_mut() = Some(location));
+            }
+            prev(info);
+        }));
+    });
+}
+
+fn payload_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(s) = payload.downcast_ref::<&'static str>() {
+        s
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s
+    } else {
+        "Box<dyn Any>"
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Strips the backtrace which is captured if `RUST_BACKTRACE` is set.
+    fn report(
+        thread: &str,
+        payload: &(dyn Any + Send),
+        location: Option<(&str, u32, u32)>,
+    ) -> String {
+        let report = render_report(thread, payload, location);
+        report.split("BACKTRACE:\n").next().unwrap().to_owned()
+    }
+
+    #[test]
+    fn render_report_works() {
+        let payload: Box<dyn Any + Send> = Box::new("oops");
+        assert_eq!(
+            report("main", &*payload, Some(("src/main.rs", 10, 5))),
+            "thread 'main' panicked\nERROR: Failed (cause; oops)\nHISTORY:\n  [0] at src/main.rs:10:5 -- panicked\n"
+        );
+
+        let payload: Box<dyn Any + Send> = Box::new(Failure::from(Failed.error()));
+        assert_eq!(
+            report("main", &*payload, None),
+            "thread 'main' panicked\nERROR: Failed\nHISTORY:\n"
+        );
+
+        let payload: Box<dyn Any + Send> = Box::new(String::from("\nERROR: Failed\nHISTORY:\n"));
+        assert_eq!(
+            report("foo", &*payload, Some(("src/main.rs", 10, 5))),
+            "thread 'foo' panicked at src/main.rs:10:5\nERROR: Failed\nHISTORY:\n"
+        );
+
+        // The report contains the full history
+        let mut e = Failure::from(Failed.error());
+        for line in 1..4 {
+            e.track(|| Location::new("", "src/main.rs", line, ""));
+        }
+        let payload: Box<dyn Any + Send> = Box::new(e);
+        assert_eq!(
+            report("main", &*payload, Some(("src/main.rs", 10, 5))),
+            "thread 'main' panicked\nERROR: Failed\nHISTORY:\n  [0] at src/main.rs:1\n  \
+             [1] at src/main.rs:2\n  [2] at src/main.rs:3\n  [3] at src/main.rs:10:5 -- panicked\n"
+        );
+    }
+
+    #[test]
+    fn catch_works() {
+        let e = catch(|| -> u32 { panic!("oops: {}", 10) }).err().unwrap();
+        assert_eq!(
+            format!("\n{}", e).replace('\\', "/"),
+            r#"
+Failed (cause; panicked: oops: 10)
+HISTORY:
+  [0] at src/panic.rs:220:35 -- panicked here
+  [1] at src/panic.rs:220:17
+"#
+        );
+    }
+}
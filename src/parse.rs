@@ -0,0 +1,267 @@
+//! Parsing of the textual representation of histories.
+use std::iter::Peekable;
+use std::str::{FromStr, Lines};
+
+use error::{Failed, Failure};
+use {Cycle, Events, History, HistoryState, Location};
+
+/// Parses a history from the format produced by the `Display` implementation of `History<Location>`.
+///
+/// The events, the repeat counts, the threads, the timestamps, the function names and the source snippets
+/// are restored.
+/// The key-value fields of a location are restored as a part of its message.
+/// Note that the events hidden by `set_display_frame_limit` cannot be restored,
+/// and the history must be rendered oldest-first (see `set_newest_first_enabled`).
+///
+/// # Examples
+///
+/// ```
+/// use trackable::{History, Location};
+///
+/// let history: History<Location> = r#"
+/// HISTORY:
+///   [0] at src/foo.rs:10:5 -- connecting (x3)
+///   [1] at src/bar.rs:20 in app::bar::run
+/// "#.trim_start().parse().unwrap();
+///
+/// let events = history.events();
+/// assert_eq!(events.len(), 2);
+/// assert_eq!((events[0].file(), events[0].line(), events[0].column()), ("src/foo.rs", 10, 5));
+/// assert_eq!(events[0].message(), "connecting");
+/// assert_eq!(events[0].repeat_count(), 3);
+/// assert_eq!(events[1].function(), "app::bar::run");
+/// ```
+impl FromStr for History<Location> {
+    type Err = Failure;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines().peekable();
+        let history = track!(parse_history(&mut lines))?;
+        if let Some(line) = lines.find(|l| !l.trim().is_empty()) {
+            track_panic!(Failed, "Unexpected line: {:?}", line);
+        }
+        Ok(history)
+    }
+}
+
+/// Parses a history section (i.e., the `HISTORY:` line and the following indented lines).
+pub(crate) fn parse_history(lines: &mut Peekable<Lines>) -> Result<History<Location>, Failure> {
+    let header = lines.next().map(str::trim);
+    track_assert_eq!(header, Some("HISTORY:"), Failed);
+    track!(parse_events(lines, 2))
+}
+
+fn parse_events(lines: &mut Peekable<Lines>, indent: usize) -> Result<History<Location>, Failure> {
+    let mut history: History<Location> = History {
+        events: Events::new(),
+        state: None,
+        branches: Vec::new(),
+    };
+    let mut positions = Vec::new();
+    while let Some(&line) = lines.peek() {
+        let body = line.trim_start();
+        let depth = line.len() - body.len();
+        if depth < indent || body.is_empty() {
+            break;
+        }
+        lines.next();
+
+        if depth > indent {
+            let snippet = body.strip_prefix("| ");
+            let last = history.events.last_mut();
+            match (snippet, last) {
+                (Some(snippet), Some(last)) => last.snippet = snippet.to_owned().into(),
+                _ => track_panic!(Failed, "Unexpected line: {:?}", line),
+            }
+        } else if body.starts_with('[') {
+            let (index, location) = track!(parse_event(body))?;
+            positions.push((index, history.events.len()));
+            history.events.push(location);
+        } else if let Some(label) = body
+            .strip_prefix("branch '")
+            .and_then(|s| s.strip_suffix("':"))
+        {
+            let branch = track!(parse_events(lines, indent + 2))?;
+            history.merge_branch(label.to_owned(), branch);
+        } else if let Some(s) = body.strip_prefix("... [") {
+            let (range, iterations) = track_assert_some!(s.split_once("] repeated (x"), Failed);
+            let (a, b) = track_assert_some!(range.split_once("] to ["), Failed);
+            let iterations = track_assert_some!(iterations.strip_suffix(") ..."), Failed);
+            let position = |index: &str| {
+                let index = index.parse::<usize>().ok()?;
+                positions.iter().find(|p| p.0 == index).map(|p| p.1)
+            };
+            let start = track_assert_some!(position(a), Failed, "line={:?}", line);
+            let end = track_assert_some!(position(b), Failed, "line={:?}", line);
+            let iterations = track!(iterations.parse().map_err(Failure::from_error))?;
+            state(&mut history).cycles.push(Cycle {
+                start,
+                len: end + 1 - start,
+                iterations,
+            });
+        } else if let Some(s) = body.strip_prefix("... and ") {
+            // The events hidden by the display frame limit cannot be restored.
+            track_assert!(
+                s.ends_with(" more frames") || s.ends_with(" more frame"),
+                Failed,
+                "line={:?}",
+                line
+            );
+        } else if let Some(s) = body.strip_prefix("... ") {
+            let omitted = track_assert_some!(s.strip_suffix(" events omitted ..."), Failed);
+            let omitted = track!(omitted.parse().map_err(Failure::from_error))?;
+            let omitted_at = history.events.len();
+            let state = state(&mut history);
+            state.omitted = omitted;
+            state.omitted_at = omitted_at;
+        } else {
+            track_panic!(Failed, "Unexpected line: {:?}", line);
+        }
+    }
+    Ok(history)
+}
+
+fn parse_event(body: &str) -> Result<(usize, Location), Failure> {
+    let body = &strip_hyperlinks(body);
+    let (index, mut rest) = track_assert_some!(body[1..].split_once("] at "), Failed);
+    let index = track!(index.parse().map_err(Failure::from_error))?;
+
+    let mut thread = None;
+    if let Some(s) = rest.strip_suffix("']") {
+        if let Some((s, name)) = s.rsplit_once(" [thread '") {
+            thread = Some(name.to_owned());
+            rest = s;
+        }
+    }
+    let mut timestamp = None;
+    if let Some(s) = rest.strip_suffix(']') {
+        if let Some((s, t)) = s.rsplit_once(" [") {
+            if let Some(t) = ::fmt::parse_timestamp(t) {
+                timestamp = Some(t);
+                rest = s;
+            }
+        }
+    }
+    let mut repeated = 0;
+    if let Some(s) = rest.strip_suffix(')') {
+        if let Some((s, n)) = s.rsplit_once(" (x") {
+            if let Ok(n) = n.parse::<u32>() {
+                repeated = n.saturating_sub(1);
+                rest = s;
+            }
+        }
+    }
+
+    let (position, message) = rest.split_once(" -- ").unwrap_or((rest, ""));
+    let (position, function) = position.split_once(" in ").unwrap_or((position, ""));
+    let (head, last) = track_assert_some!(rsplit_number(position), Failed, "body={:?}", body);
+    let (file, line, column) = match rsplit_number(head) {
+        Some((file, line)) => (file, line, last),
+        None => (head, last, 0),
+    };
+
+    let mut location = Location::new("", file.to_owned(), line, message.to_owned())
+        .with_column(column)
+        .with_function(function.to_owned());
+    location.thread = thread;
+    location.timestamp = timestamp;
+    location.repeated = repeated;
+    Ok((index, location))
+}
+
+// Removes the OSC 8 escape sequences (see `set_hyperlinks_enabled`).
+fn strip_hyperlinks(s: &str) -> String {
+    let mut stripped = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find("\x1b]8;") {
+        stripped.push_str(&rest[..start]);
+        rest = &rest[start..];
+        rest = rest.find("\x1b\\").map_or("", |end| &rest[end + 2..]);
+    }
+    stripped.push_str(rest);
+    stripped
+}
+
+fn rsplit_number(s: &str) -> Option<(&str, u32)> {
+    let (head, n) = s.rsplit_once(':')?;
+    n.parse().ok().map(|n| (head, n))
+}
+
+fn state(history: &mut History<Location>) -> &mut HistoryState {
+    history.state.get_or_insert_with(Default::default)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn parse_is_inverse_of_display() {
+        let location = |line| Location::new("foo", "src/foo.rs", line, "");
+        let mut branch = History::new();
+        branch.add(
+            location(100)
+                .with_thread("worker")
+                .with_snippet("let x = y?;"),
+        );
+
+        let mut history = History::with_capacity_limit(4);
+        for _ in 0..2 {
+            history.add_location(location(1));
+            history.add_location(location(2));
+        }
+        history.add_location(location(3).with_column(7));
+        history.add_location(location(3).with_column(7));
+        history.merge_branch("b", branch);
+        for line in 4..8 {
+            history.add_location(Location::new("foo", "C:/foo.rs", line, "a -- b"));
+        }
+
+        for alternate in [false, true] {
+            let text = if alternate {
+                format!("{:#}", history)
+            } else {
+                history.to_string()
+            };
+            let parsed: History<Location> = text.parse().unwrap();
+            let reparsed = if alternate {
+                format!("{:#}", parsed)
+            } else {
+                parsed.to_string()
+            };
+            assert_eq!(reparsed, text);
+        }
+    }
+
+    #[test]
+    fn parse_ignores_hyperlinks() {
+        let text = "HISTORY:\n  [0] at \x1b]8;;file:///src/foo.rs\x1b\\src/foo.rs:10:5\x1b]8;;\x1b\\ -- hi\n";
+        let history: History<Location> = text.parse().unwrap();
+        assert_eq!(history.events()[0].to_string(), "at src/foo.rs:10:5 -- hi");
+    }
+
+    #[test]
+    fn parse_restores_timestamps() {
+        let text =
+            "HISTORY:\n  [0] at src/foo.rs:10 -- hi [2024-01-02T03:04:05.678Z] [thread 'w']\n";
+        let history: History<Location> = text.parse().unwrap();
+        let event = &history.events()[0];
+        assert_eq!(event.message(), "hi");
+        assert_eq!(event.thread(), Some("w"));
+        assert_eq!(
+            event.timestamp(),
+            Some(UNIX_EPOCH + Duration::from_millis(1_704_164_645_678))
+        );
+    }
+
+    #[test]
+    fn parse_rejects_malformed_lines() {
+        assert!("".parse::<History<Location>>().is_err());
+        assert!("HISTORY:\n  [0] at src/foo.rs"
+            .parse::<History<Location>>()
+            .is_err());
+        assert!("HISTORY:\n  foo".parse::<History<Location>>().is_err());
+        assert!("HISTORY:\nfoo".parse::<History<Location>>().is_err());
+    }
+}
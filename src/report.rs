@@ -0,0 +1,481 @@
+//! Markdown and HTML rendering of errors.
+//!
+//! The rendered reports are suitable for attaching to bug trackers (e.g., GitHub issues)
+//! and incident management tools which do not preserve the layout of plain text.
+//!
+//! # Examples
+//!
+//! ```
+//! use trackable::Location;
+//! use trackable::error::{Failed, TrackableError};
+//! use trackable::report;
+//!
+//! let e = TrackableError::builder(Failed)
+//!     .cause("no such user")
+//!     .location(Location::new("foo", "src/db.rs", 10, "user_id=42").with_column(5))
+//!     .build();
+//!
+//! let markdown = report::to_markdown(&e);
+//! assert!(markdown.starts_with("## Failed\n\n### Cause\n\nno such user\n"));
+//! assert!(markdown.contains(r#"
+//! <details>
+//! <summary>History (1 event)</summary>
+//!
+//! - `[0]` `src/db.rs:10:5` — user\_id=42
+//!
+//! </details>
+//! "#));
+//!
+//! // The backtrace follows the history if the `backtrace` feature is enabled
+//! assert!(report::to_html(&e).starts_with(r#"<section class="trackable-error">
+//! <h2>Failed</h2>
+//! <h3>Cause</h3>
+//! <p>no such user</p>
+//! <details>
+//! <summary>History (1 event)</summary>
+//! <ul>
+//! <li><code>[0]</code> <code>src/db.rs:10:5</code> — user_id=42</li>
+//! </ul>
+//! </details>
+//! "#));
+//! ```
+use std::fmt::{self, Write};
+
+use error::{ErrorKind, History, TrackableError};
+use {strip_path_prefix, Branch, Location};
+
+/// Renders `error` as a Markdown document.
+///
+/// The document consists of a heading with the kind of the error,
+/// the sections of the cause, the source chain and the contexts,
+/// and a collapsible (`<details>`) list of the history events.
+/// The linked errors and the backtrace (if captured) follow them.
+///
+/// The texts taken from the error are escaped, so they are rendered literally.
+pub fn to_markdown<K: ErrorKind>(error: &TrackableError<K>) -> String {
+    let mut s = String::new();
+    write_markdown(&mut s, &Report::new(error)).expect("Never fails");
+    s
+}
+
+/// Renders `error` as an HTML fragment.
+///
+/// The fragment is a `<section class="trackable-error">` element
+/// which has the same structure as the document produced by `to_markdown`.
+///
+/// The texts taken from the error are escaped, so they are rendered literally.
+pub fn to_html<K: ErrorKind>(error: &TrackableError<K>) -> String {
+    let mut s = String::new();
+    write_html(&mut s, &Report::new(error)).expect("Never fails");
+    s
+}
+
+struct Report<'a> {
+    title: String,
+    cause: Option<String>,
+    sources: Vec<String>,
+    contexts: &'a [String],
+    history: &'a History,
+    linked: Vec<String>,
+    backtrace: Option<String>,
+}
+impl<'a> Report<'a> {
+    fn new<K: ErrorKind>(error: &'a TrackableError<K>) -> Self {
+        let parts = error.parts();
+        let mut title = KindText(parts.kind()).to_string();
+        if let Some(code) = parts.kind().code() {
+            title.push_str(&format!(" [{}]", code));
+        }
+        let cause = parts.cause_text().map(|c| c.to_string());
+        #[cfg(feature = "backtrace")]
+        let backtrace = parts.backtrace().map(|b| b.to_string());
+        #[cfg(not(feature = "backtrace"))]
+        let backtrace = None;
+        Report {
+            title,
+            cause,
+            sources: parts.sources().map(|e| e.to_string()).collect(),
+            contexts: parts.contexts(),
+            history: parts.history(),
+            linked: parts.linked_errors().map(|e| e.to_string()).collect(),
+            backtrace,
+        }
+    }
+}
+
+struct KindText<'a>(&'a dyn ErrorKind);
+impl<'a> fmt::Display for KindText<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.display(f)
+    }
+}
+
+// An entry of the rendered history.
+enum Item<'a> {
+    Event(usize, &'a Location),
+    Cycle(usize, usize, usize),
+    Branch(&'a str, Vec<Item<'a>>),
+    Omitted(usize),
+}
+
+fn items<'a>(history: &'a History) -> Vec<Item<'a>> {
+    let omitted = history.omitted();
+    let head = history.state.as_ref().map_or(0, |s| s.omitted_at);
+    let index = |i: usize| if i >= head { i + omitted } else { i };
+    let branch = |b: &'a Branch<Location>| Item::Branch(b.label(), items(b.history()));
+
+    let mut list = Vec::new();
+    let mut cycles = history.cycles().iter().peekable();
+    let mut branches = history.branches().iter().peekable();
+    for (i, e) in history.events().iter().enumerate() {
+        while let Some(b) = branches.next_if(|b| b.position() == i) {
+            list.push(branch(b));
+        }
+        if omitted > 0 && i == head {
+            list.push(Item::Omitted(omitted));
+        }
+        list.push(Item::Event(index(i), e));
+        if let Some(c) = cycles.next_if(|c| c.start() + c.len() == i + 1) {
+            list.push(Item::Cycle(index(c.start()), index(i), c.iterations()));
+        }
+    }
+    list.extend(branches.map(branch));
+    if omitted > 0 && history.len() <= head {
+        list.push(Item::Omitted(omitted));
+    }
+    list
+}
+
+fn position(location: &Location) -> String {
+    let mut s = format!("{}:{}", strip_path_prefix(location.file()), location.line());
+    if location.column() != 0 {
+        s.push_str(&format!(":{}", location.column()));
+    }
+    s
+}
+
+fn note(location: &Location) -> String {
+    let mut s = location.message().to_owned();
+    for (i, field) in location.fields().iter().enumerate() {
+        s.push_str(match (i, s.is_empty()) {
+            (0, true) => "",
+            (0, false) => "; ",
+            _ => ", ",
+        });
+        s.push_str(&field.to_string());
+    }
+    s
+}
+
+fn summary(history: &History) -> String {
+    match history.len() {
+        1 => "History (1 event)".to_owned(),
+        n => format!("History ({} events)", n),
+    }
+}
+
+fn write_markdown(out: &mut String, report: &Report) -> fmt::Result {
+    writeln!(out, "## {}", md_text(&report.title))?;
+    if let Some(ref cause) = report.cause {
+        writeln!(out, "\n### Cause\n")?;
+        md_block(out, cause)?;
+    }
+    if !report.sources.is_empty() {
+        writeln!(out, "\n### Caused by\n")?;
+        for (i, source) in report.sources.iter().enumerate() {
+            writeln!(out, "{}. {}", i + 1, md_text(source))?;
+        }
+    }
+    if !report.contexts.is_empty() {
+        writeln!(out, "\n### Context\n")?;
+        for context in report.contexts {
+            writeln!(out, "- {}", md_text(context))?;
+        }
+    }
+    writeln!(
+        out,
+        "\n<details>\n<summary>{}</summary>\n",
+        summary(report.history)
+    )?;
+    md_items(out, &items(report.history), "")?;
+    writeln!(out, "\n</details>")?;
+    for (i, linked) in report.linked.iter().enumerate() {
+        writeln!(out, "\n### Linked error [{}]\n", i)?;
+        md_fence(out, linked)?;
+    }
+    if let Some(ref backtrace) = report.backtrace {
+        writeln!(out, "\n<details>\n<summary>Backtrace</summary>\n")?;
+        md_fence(out, backtrace)?;
+        writeln!(out, "\n</details>")?;
+    }
+    Ok(())
+}
+
+fn md_items(out: &mut String, items: &[Item], indent: &str) -> fmt::Result {
+    for item in items {
+        match *item {
+            Item::Event(i, location) => {
+                write!(
+                    out,
+                    "{}- `[{}]` {}",
+                    indent,
+                    i,
+                    md_code(&position(location))
+                )?;
+                if !location.function().is_empty() {
+                    write!(out, " in {}", md_code(location.function()))?;
+                }
+                if let Some(thread) = location.thread() {
+                    write!(out, " on thread {}", md_code(thread))?;
+                }
+                let note = note(location);
+                if !note.is_empty() {
+                    write!(out, " — {}", md_text(&note))?;
+                }
+                if location.repeat_count() > 1 {
+                    write!(out, " (x{})", location.repeat_count())?;
+                }
+                writeln!(out)?;
+            }
+            Item::Cycle(start, end, iterations) => writeln!(
+                out,
+                "{}- *`[{}]` to `[{}]` repeated (x{})*",
+                indent, start, end, iterations
+            )?,
+            Item::Branch(label, ref items) => {
+                writeln!(out, "{}- branch {}:", indent, md_code(label))?;
+                md_items(out, items, &format!("{}  ", indent))?;
+            }
+            Item::Omitted(n) => writeln!(out, "{}- *{} events omitted*", indent, n)?,
+        }
+    }
+    Ok(())
+}
+
+fn md_text(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if "\\`*_[]<>|~#".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn md_code(s: &str) -> String {
+    if s.contains('`') {
+        format!("`` {} ``", s)
+    } else {
+        format!("`{}`", s)
+    }
+}
+
+fn md_block(out: &mut String, s: &str) -> fmt::Result {
+    if s.contains('\n') {
+        md_fence(out, s)
+    } else {
+        writeln!(out, "{}", md_text(s))
+    }
+}
+
+fn md_fence(out: &mut String, s: &str) -> fmt::Result {
+    // The fence must be longer than any run of backticks in the text
+    let longest = s.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest.max(2) + 1);
+    writeln!(out, "{}text\n{}\n{}", fence, s.trim_end(), fence)
+}
+
+fn write_html(out: &mut String, report: &Report) -> fmt::Result {
+    writeln!(out, "<section class=\"trackable-error\">")?;
+    writeln!(out, "<h2>{}</h2>", html_text(&report.title))?;
+    if let Some(ref cause) = report.cause {
+        writeln!(out, "<h3>Cause</h3>")?;
+        if cause.contains('\n') {
+            writeln!(out, "<pre>{}</pre>", html_text(cause))?;
+        } else {
+            writeln!(out, "<p>{}</p>", html_text(cause))?;
+        }
+    }
+    if !report.sources.is_empty() {
+        writeln!(out, "<h3>Caused by</h3>\n<ol>")?;
+        for source in &report.sources {
+            writeln!(out, "<li>{}</li>", html_text(source))?;
+        }
+        writeln!(out, "</ol>")?;
+    }
+    if !report.contexts.is_empty() {
+        writeln!(out, "<h3>Context</h3>\n<ul>")?;
+        for context in report.contexts {
+            writeln!(out, "<li>{}</li>", html_text(context))?;
+        }
+        writeln!(out, "</ul>")?;
+    }
+    writeln!(
+        out,
+        "<details>\n<summary>{}</summary>",
+        summary(report.history)
+    )?;
+    html_items(out, &items(report.history))?;
+    writeln!(out, "</details>")?;
+    for (i, linked) in report.linked.iter().enumerate() {
+        writeln!(out, "<h3>Linked error [{}]</h3>", i)?;
+        writeln!(out, "<pre>{}</pre>", html_text(linked.trim_end()))?;
+    }
+    if let Some(ref backtrace) = report.backtrace {
+        writeln!(out, "<details>\n<summary>Backtrace</summary>")?;
+        writeln!(out, "<pre>{}</pre>", html_text(backtrace.trim_end()))?;
+        writeln!(out, "</details>")?;
+    }
+    writeln!(out, "</section>")
+}
+
+fn html_items(out: &mut String, items: &[Item]) -> fmt::Result {
+    writeln!(out, "<ul>")?;
+    for item in items {
+        match *item {
+            Item::Event(i, location) => {
+                write!(
+                    out,
+                    "<li><code>[{}]</code> <code>{}</code>",
+                    i,
+                    html_text(&position(location))
+                )?;
+                if !location.function().is_empty() {
+                    write!(out, " in <code>{}</code>", html_text(location.function()))?;
+                }
+                if let Some(thread) = location.thread() {
+                    write!(out, " on thread <code>{}</code>", html_text(thread))?;
+                }
+                let note = note(location);
+                if !note.is_empty() {
+                    write!(out, " — {}", html_text(&note))?;
+                }
+                if location.repeat_count() > 1 {
+                    write!(out, " (x{})", location.repeat_count())?;
+                }
+                writeln!(out, "</li>")?;
+            }
+            Item::Cycle(start, end, iterations) => writeln!(
+                out,
+                "<li><em><code>[{}]</code> to <code>[{}]</code> repeated (x{})</em></li>",
+                start, end, iterations
+            )?,
+            Item::Branch(label, ref items) => {
+                writeln!(out, "<li>branch <code>{}</code>:", html_text(label))?;
+                html_items(out, items)?;
+                writeln!(out, "</li>")?;
+            }
+            Item::Omitted(n) => writeln!(out, "<li><em>{} events omitted</em></li>", n)?,
+        }
+    }
+    writeln!(out, "</ul>")
+}
+
+fn html_text(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use error::{ErrorKindExt, Failed, Failure};
+    use Trackable;
+
+    fn without_backtrace(report: String) -> String {
+        match report.find("<details>\n<summary>Backtrace") {
+            None => report,
+            Some(start) => {
+                let end = start + report[start..].find("</details>\n").unwrap();
+                let end = end + "</details>\n".len();
+                let start = start - usize::from(report[..start].ends_with("\n\n"));
+                format!("{}{}", &report[..start], &report[end..])
+            }
+        }
+    }
+
+    #[test]
+    fn reports_escape_error_texts() {
+        let inner: Failure = Failed.cause("<disk> *full*").into();
+        let mut branch = History::new();
+        branch.add(Location::new("foo", "src/worker.rs", 5, "").with_thread("w`1"));
+
+        let mut e = Failed.absorb(inner).context("ctx #1");
+        let history = e.history_mut().unwrap();
+        history.add(Location::new("foo", "src/foo.rs", 10, "").with_function("foo::run"));
+        history.merge_branch("worker", branch);
+
+        assert_eq!(
+            without_backtrace(to_markdown(&e)),
+            r#"## Failed
+
+### Cause
+
+Failed (cause; \<disk\> \*full\*)
+
+### Caused by
+
+1. \<disk\> \*full\*
+
+### Context
+
+- ctx \#1
+
+<details>
+<summary>History (1 event)</summary>
+
+- `[0]` `src/foo.rs:10` in `foo::run`
+- branch `worker`:
+  - `[0]` `src/worker.rs:5` on thread `` w`1 ``
+
+</details>
+"#
+        );
+        assert_eq!(
+            without_backtrace(to_html(&e)),
+            r#"<section class="trackable-error">
+<h2>Failed</h2>
+<h3>Cause</h3>
+<p>Failed (cause; &lt;disk&gt; *full*)</p>
+<h3>Caused by</h3>
+<ol>
+<li>&lt;disk&gt; *full*</li>
+</ol>
+<h3>Context</h3>
+<ul>
+<li>ctx #1</li>
+</ul>
+<details>
+<summary>History (1 event)</summary>
+<ul>
+<li><code>[0]</code> <code>src/foo.rs:10</code> in <code>foo::run</code></li>
+<li>branch <code>worker</code>:
+<ul>
+<li><code>[0]</code> <code>src/worker.rs:5</code> on thread <code>w`1</code></li>
+</ul>
+</li>
+</ul>
+</details>
+</section>
+"#
+        );
+    }
+
+    #[test]
+    fn markdown_fence_is_longer_than_backtick_runs() {
+        let mut s = String::new();
+        md_fence(&mut s, "a ```` b\n").unwrap();
+        assert_eq!(s, "`````text\na ```` b\n`````\n");
+    }
+}
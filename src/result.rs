@@ -1,7 +1,44 @@
 //! Trackable [`Result`] types for main and test functions.
 //!
+//! If a `main` function that returns [`MainResult`] fails,
+//! the process prints a report of the error (including the history) to the standard error
+//! and exits with a non-zero status code.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! #[macro_use]
+//! extern crate trackable;
+//!
+//! use trackable::result::MainResult;
+//!
+//! fn main() -> MainResult {
+//!     track_any_err!(std::fs::File::open("/path/to/non_existent_file"))?;
+//!     Ok(())
+//! }
+//! ```
+//!
+//! The above program will print a report like the following:
+//!
+//! ```text
+//! Error: Failed (cause; No such file or directory (os error 2))
+//! HISTORY:
+//!   [0] at src/main.rs:7:5
+//! ```
+//!
+//! The same report can be produced by annotating `main` with [`#[trackable::main]`](../attr.main.html)
+//! instead of changing the return type.
+//!
+//! If you want to map error kinds to exit codes, use [`MainReport`] instead.
+//!
 //! [`Result`]: https://doc.rust-lang.org/std/result/enum.Result.html
-use error::TopLevelError;
+//! [`MainResult`]: type.MainResult.html
+//! [`MainReport`]: struct.MainReport.html
+use std::fmt;
+use std::io;
+use std::process::{ExitCode, Termination};
+
+use error::{CommonErrorKind, ErrorKind, Failed, TopLevelError, TrackableError};
 
 /// A variant of [`Result`] for top-level functions that return a trackable error on failure.
 ///
@@ -17,3 +54,124 @@ pub type MainResult = TopLevelResult;
 ///
 /// [`Result`]: https://doc.rust-lang.org/std/result/enum.Result.html
 pub type TestResult = TopLevelResult;
+
+/// An extension of `ErrorKind` trait for mapping error kinds to exit codes of processes.
+pub trait ExitCodeKind: ErrorKind {
+    /// Returns the exit code corresponding to this kind.
+    ///
+    /// The default implementation always returns `1`.
+    fn exit_code(&self) -> u8 {
+        1
+    }
+}
+impl ExitCodeKind for Failed {}
+impl ExitCodeKind for CommonErrorKind {}
+impl ExitCodeKind for io::ErrorKind {}
+
+/// A value returned by `main` functions which maps the kind of the resulting error to an exit code.
+///
+/// If the result is an error, this prints the report of the error (including the history)
+/// to the standard error and exits with the code returned by [`ExitCodeKind::exit_code`].
+///
+/// # Examples
+///
+/// ```no_run
+/// #[macro_use]
+/// extern crate trackable;
+///
+/// use trackable::error::{ErrorKind, ErrorKindExt, TrackableError};
+/// use trackable::result::{ExitCodeKind, MainReport};
+///
+/// #[derive(Debug)]
+/// enum CliErrorKind {
+///     Usage,
+/// }
+/// impl ErrorKind for CliErrorKind {}
+/// impl ExitCodeKind for CliErrorKind {
+///     fn exit_code(&self) -> u8 {
+///         2
+///     }
+/// }
+///
+/// fn run() -> Result<(), TrackableError<CliErrorKind>> {
+///     track_panic!(CliErrorKind::Usage, "no arguments");
+/// }
+///
+/// fn main() -> MainReport<CliErrorKind> {
+///     // Exits with the code `2`.
+///     MainReport::from(run())
+/// }
+/// ```
+///
+/// [`ExitCodeKind::exit_code`]: trait.ExitCodeKind.html#method.exit_code
+#[derive(Debug)]
+pub struct MainReport<K>(Result<(), TrackableError<K>>);
+impl<K, E> From<Result<(), E>> for MainReport<K>
+where
+    E: Into<TrackableError<K>>,
+{
+    fn from(f: Result<(), E>) -> Self {
+        MainReport(f.map_err(Into::into))
+    }
+}
+impl<K: ExitCodeKind> Termination for MainReport<K> {
+    fn report(self) -> ExitCode {
+        match self.0 {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("Error: {:#}", e);
+                ExitCode::from(e.kind().exit_code())
+            }
+        }
+    }
+}
+
+/// Reports the result of a `main` function.
+///
+/// If `result` is an error, this prints the extended report of the error (i.e., `{:#}`)
+/// to the standard error and returns `ExitCode::FAILURE`.
+/// Otherwise, the `Ok` value is reported as is.
+///
+/// This is used by [`#[trackable::main]`](../attr.main.html) attribute.
+///
+/// # Examples
+///
+/// ```
+/// use std::process::ExitCode;
+/// use trackable::error::{ErrorKindExt, Failed, Failure};
+/// use trackable::result;
+///
+/// let ok: Result<(), Failure> = Ok(());
+/// assert_eq!(result::report(ok), ExitCode::SUCCESS);
+///
+/// let err: Result<(), Failure> = Err(Failed.cause("something wrong").into());
+/// assert_eq!(result::report(err), ExitCode::FAILURE);
+/// ```
+pub fn report<T, E>(result: Result<T, E>) -> ExitCode
+where
+    T: Termination,
+    E: fmt::Display,
+{
+    match result {
+        Ok(v) => v.report(),
+        Err(e) => {
+            eprintln!("Error: {:#}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use error::ErrorKindExt;
+
+    #[test]
+    fn main_report_works() {
+        let result: Result<(), TrackableError<Failed>> = Ok(());
+        assert_eq!(MainReport::from(result).report(), ExitCode::SUCCESS);
+
+        let result: Result<(), _> = Err(io::ErrorKind::NotFound.error());
+        assert_eq!(MainReport::from(result).report(), ExitCode::from(1));
+    }
+}
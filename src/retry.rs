@@ -0,0 +1,199 @@
+//! Retrying operations that fail with retriable errors.
+use std::panic;
+use std::thread;
+use std::time::Duration;
+
+use error::{ErrorKind, TrackableError};
+use {Location, Trackable};
+
+/// A policy that decides how many times and how long to wait before retrying an operation.
+///
+/// The delay before the `n`-th retry is `initial_delay * 2^(n - 1)`, and is capped by `max_delay`.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: usize,
+    initial_delay: Duration,
+    max_delay: Duration,
+}
+impl RetryPolicy {
+    /// The default value of `max_attempts`.
+    pub const DEFAULT_MAX_ATTEMPTS: usize = 3;
+
+    /// The default value of `initial_delay`.
+    pub const DEFAULT_INITIAL_DELAY: Duration = Duration::from_millis(100);
+
+    /// The default value of `max_delay`.
+    pub const DEFAULT_MAX_DELAY: Duration = Duration::from_secs(10);
+
+    /// Makes a new `RetryPolicy` instance with the default settings.
+    pub fn new() -> Self {
+        RetryPolicy {
+            max_attempts: Self::DEFAULT_MAX_ATTEMPTS,
+            initial_delay: Self::DEFAULT_INITIAL_DELAY,
+            max_delay: Self::DEFAULT_MAX_DELAY,
+        }
+    }
+
+    /// Sets the maximum number of attempts (including the first one).
+    pub fn max_attempts(mut self, n: usize) -> Self {
+        self.max_attempts = n;
+        self
+    }
+
+    /// Sets the delay before the first retry.
+    pub fn initial_delay(mut self, delay: Duration) -> Self {
+        self.initial_delay = delay;
+        self
+    }
+
+    /// Sets the upper bound of delays.
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    fn delay(&self, retry: usize) -> Duration {
+        let factor = 1u32.checked_shl(retry as u32 - 1).unwrap_or(u32::MAX);
+        self.initial_delay
+            .checked_mul(factor)
+            .map_or(self.max_delay, |d| d.min(self.max_delay))
+    }
+}
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Executes `f` repeatedly until it succeeds, fails with a non-retriable error,
+/// or the number of attempts reaches the limit of `policy`.
+///
+/// Whether an error is retriable is decided by `ErrorKind::is_retriable` method.
+///
+/// If all the attempts fail, the error of the last attempt is returned.
+/// The outcome of each attempt (the attempt number and the backoff delay)
+/// is appended to the history of the error.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use trackable::Trackable;
+/// use trackable::error::{ErrorKind, ErrorKindExt, TrackableError};
+/// use trackable::retry::{self, RetryPolicy};
+///
+/// #[derive(Debug)]
+/// struct Unavailable;
+/// impl ErrorKind for Unavailable {
+///     fn is_retriable(&self) -> bool {
+///         true
+///     }
+/// }
+///
+/// let policy = RetryPolicy::new().max_attempts(3).initial_delay(Duration::from_millis(1));
+/// let result: Result<(), _> = retry::retry_with(&policy, || Err(Unavailable.error()));
+///
+/// let error: TrackableError<Unavailable> = result.err().unwrap();
+/// let messages = error
+///     .history()
+///     .unwrap()
+///     .events()
+///     .iter()
+///     .map(|e| e.to_string())
+///     .collect::<Vec<_>>();
+/// assert!(messages[0].ends_with("-- retrying; attempt=1, delay=1ms"));
+/// assert!(messages[1].ends_with("-- retrying; attempt=2, delay=2ms"));
+/// assert!(messages[2].ends_with("-- giving up; attempt=3"));
+/// ```
+#[track_caller]
+pub fn retry_with<T, E, K, F>(policy: &RetryPolicy, mut f: F) -> Result<T, TrackableError<K>>
+where
+    F: FnMut() -> Result<T, E>,
+    E: Into<TrackableError<K>>,
+    K: ErrorKind,
+{
+    let caller = panic::Location::caller();
+    let mut attempts = Vec::new();
+    let mut attempt = 1;
+    loop {
+        let mut e = match f() {
+            Ok(v) => return Ok(v),
+            Err(e) => e.into(),
+        };
+        if !e.kind().is_retriable() || attempt >= policy.max_attempts {
+            let reason = if e.kind().is_retriable() {
+                "giving up"
+            } else {
+                "not retriable"
+            };
+            let mut location = Location::new("", caller.file(), caller.line(), reason)
+                .with_column(caller.column());
+            location.add_field("attempt", attempt.to_string());
+            attempts.push(location);
+            for location in attempts {
+                e.track(|| location);
+            }
+            return Err(e);
+        }
+
+        let delay = policy.delay(attempt);
+        let mut location = Location::new("", caller.file(), caller.line(), "retrying")
+            .with_column(caller.column());
+        location.add_field("attempt", attempt.to_string());
+        location.add_field("delay", format!("{:?}", delay));
+        attempts.push(location);
+
+        thread::sleep(delay);
+        attempt += 1;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use error::{ErrorKindExt, Failed};
+
+    #[test]
+    fn retry_with_works() {
+        #[derive(Debug)]
+        struct Retriable;
+        impl ErrorKind for Retriable {
+            fn is_retriable(&self) -> bool {
+                true
+            }
+        }
+
+        let policy = RetryPolicy::new().initial_delay(Duration::from_millis(0));
+        let mut count = 0;
+        let result = retry_with(&policy, || {
+            count += 1;
+            if count < 3 {
+                Err(Retriable.error())
+            } else {
+                Ok(count)
+            }
+        });
+        assert_eq!(result.ok(), Some(3));
+
+        let mut count = 0;
+        let result: Result<(), _> = retry_with(&policy, || {
+            count += 1;
+            Err(Failed.error())
+        });
+        assert_eq!(count, 1);
+        let history = result.err().unwrap().history().unwrap().events().to_owned();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].message(), "not retriable");
+    }
+
+    #[test]
+    fn retry_delay_works() {
+        let policy = RetryPolicy::new()
+            .initial_delay(Duration::from_millis(100))
+            .max_delay(Duration::from_millis(300));
+        assert_eq!(policy.delay(1), Duration::from_millis(100));
+        assert_eq!(policy.delay(2), Duration::from_millis(200));
+        assert_eq!(policy.delay(3), Duration::from_millis(300));
+        assert_eq!(policy.delay(100), Duration::from_millis(300));
+    }
+}
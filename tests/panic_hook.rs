@@ -0,0 +1,13 @@
+//! Tests of the panic hooks (in a dedicated binary since they are process-wide).
+extern crate trackable;
+
+use trackable::panic;
+use trackable::Trackable;
+
+#[test]
+fn catch_works_with_install_hook() {
+    panic::install_hook();
+    let e = panic::catch(|| -> u32 { panic!("oops") }).err().unwrap();
+    assert_eq!(e.history().map(|h| h.events().len()), Some(2));
+    assert_eq!(e.history().unwrap().events()[0].message(), "panicked here");
+}
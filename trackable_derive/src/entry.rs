@@ -0,0 +1,65 @@
+//! Implementation of `#[trackable::main]` attribute.
+use proc_macro2::TokenStream;
+use syn::parse::Parser;
+use syn::punctuated::Punctuated;
+use syn::{Attribute, Error, Ident, ItemFn, Meta, Result, ReturnType};
+
+pub fn expand(args: TokenStream, item: ItemFn) -> Result<TokenStream> {
+    let mut color = false;
+    for arg in Punctuated::<Ident, Token![,]>::parse_terminated.parse2(args)? {
+        if arg == "color" {
+            color = true;
+        } else {
+            return Err(Error::new_spanned(arg, "unsupported option"));
+        }
+    }
+    if let Some(asyncness) = item.sig.asyncness {
+        return Err(Error::new_spanned(
+            asyncness,
+            "`#[trackable::main]` cannot be applied to async functions",
+        ));
+    }
+    let ret = match item.sig.output {
+        ReturnType::Type(_, ref ty) => ty.clone(),
+        ReturnType::Default => {
+            return Err(Error::new_spanned(
+                &item.sig,
+                "`#[trackable::main]` can only be applied to functions that return `Result`",
+            ))
+        }
+    };
+
+    let ItemFn {
+        attrs,
+        vis,
+        sig,
+        block,
+    } = item;
+
+    // `#[trackable]` following this attribute is moved to the inner function which has the original body
+    let (inner_attrs, attrs): (Vec<_>, Vec<_>) = attrs.into_iter().partition(is_trackable_attr);
+    let ident = &sig.ident;
+    let setup = if color {
+        quote!(::trackable::fmt::set_config(::trackable::fmt::config().with_color(true));)
+    } else {
+        quote!()
+    };
+    Ok(quote! {
+        #(#attrs)*
+        #vis fn #ident() -> ::std::process::ExitCode {
+            #(#inner_attrs)*
+            fn #ident() -> #ret #block
+
+            #setup
+            ::trackable::result::report(#ident())
+        }
+    })
+}
+
+/// Returns `true` if `attr` is `#[trackable]` (or `#[trackable::trackable]`).
+fn is_trackable_attr(attr: &Attribute) -> bool {
+    match attr.meta {
+        Meta::Path(ref path) => path.segments.last().is_some_and(|s| s.ident == "trackable"),
+        _ => false,
+    }
+}
@@ -0,0 +1,228 @@
+//! Implementation of `#[derive(TrackableError)]`.
+use proc_macro2::TokenStream;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{Attribute, Data, DeriveInput, Error, Expr, Member, Result, Type};
+
+pub fn derive(input: &DeriveInput) -> Result<TokenStream> {
+    let options = Options::parse(&input.attrs)?;
+    let fields = match input.data {
+        Data::Struct(ref s) => &s.fields,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "`TrackableError` can only be derived for structs",
+            ))
+        }
+    };
+    let members = fields
+        .iter()
+        .enumerate()
+        .map(|(i, f)| match f.ident {
+            Some(ref ident) => (Member::Named(ident.clone()), &f.ty),
+            None => (Member::Unnamed(i.into()), &f.ty),
+        })
+        .collect::<Vec<_>>();
+    let candidates = members
+        .iter()
+        .filter(|&&(_, ty)| members.len() == 1 || trackable_error_path(ty).is_some())
+        .collect::<Vec<_>>();
+    let &(ref field, field_ty) = match candidates[..] {
+        [field] => field,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "cannot determine the `TrackableError` field \
+                 (the struct must have exactly one field of type `TrackableError<_>`)",
+            ))
+        }
+    };
+    let others = members
+        .iter()
+        .map(|m| &m.0)
+        .filter(|&m| m != field)
+        .collect::<Vec<_>>();
+
+    let kind = match options.error_kind {
+        Some(kind) => kind,
+        None => error_kind_of(field_ty).unwrap_or_else(|| parse_quote!(ErrorKind)),
+    };
+    let error = &input.ident;
+    let mut generics = input.generics.clone();
+    if !generics.params.is_empty() {
+        let predicates = &mut generics.make_where_clause().predicates;
+        predicates.push(parse_quote!(#kind: ::trackable::error::ErrorKind));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let mut error_generics = generics.clone();
+    if !error_generics.params.is_empty() {
+        let predicates = &mut error_generics.make_where_clause().predicates;
+        predicates.push(parse_quote!(#error #ty_generics: ::std::fmt::Debug));
+    }
+    let error_where_clause = &error_generics.where_clause;
+
+    let from_impls = options.from.iter().map(|from| {
+        let ty = &from.ty;
+        let from_kind = match from.kind {
+            Some(ref kind) => quote!(#kind),
+            None => quote!(<#kind as ::std::default::Default>::default()),
+        };
+        quote! {
+            impl #impl_generics ::std::convert::From<#ty> for #error #ty_generics #where_clause {
+                fn from(f: #ty) -> Self {
+                    let kind: #kind = #from_kind;
+                    ::trackable::error::ErrorKindExt::cause(kind, f).into()
+                }
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::std::ops::Deref for #error #ty_generics #where_clause {
+            type Target = ::trackable::error::TrackableError<#kind>;
+
+            #[inline]
+            fn deref(&self) -> &Self::Target {
+                &self.#field
+            }
+        }
+        impl #impl_generics ::std::fmt::Display for #error #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                ::std::fmt::Display::fmt(&self.#field, f)
+            }
+        }
+        impl #impl_generics ::std::error::Error for #error #ty_generics #error_where_clause {
+            fn source(&self) -> ::std::option::Option<&(dyn std::error::Error + 'static)> {
+                ::std::error::Error::source(&self.#field)
+            }
+        }
+        impl #impl_generics ::trackable::Trackable for #error #ty_generics #where_clause {
+            type Event = ::trackable::Location;
+
+            #[inline]
+            fn history(&self) -> ::std::option::Option<&::trackable::History<Self::Event>> {
+                ::trackable::Trackable::history(&self.#field)
+            }
+
+            #[inline]
+            fn history_mut(&mut self) -> ::std::option::Option<&mut ::trackable::History<Self::Event>> {
+                ::trackable::Trackable::history_mut(&mut self.#field)
+            }
+        }
+        impl #impl_generics ::trackable::error::ContextualError for #error #ty_generics #where_clause {
+            #[inline]
+            fn add_context<TrackableContext>(&mut self, context: TrackableContext)
+            where
+                TrackableContext: ::std::fmt::Display,
+            {
+                ::trackable::error::ContextualError::add_context(&mut self.#field, context)
+            }
+        }
+        impl #impl_generics ::std::convert::From<::trackable::error::TrackableError<#kind>>
+            for #error #ty_generics #where_clause
+        {
+            #[inline]
+            fn from(f: ::trackable::error::TrackableError<#kind>) -> Self {
+                #error {
+                    #field: f,
+                    #(#others: ::std::default::Default::default(),)*
+                }
+            }
+        }
+        impl #impl_generics ::std::convert::From<#error #ty_generics>
+            for ::trackable::error::TrackableError<#kind> #where_clause
+        {
+            #[inline]
+            fn from(f: #error #ty_generics) -> Self {
+                f.#field
+            }
+        }
+        impl #impl_generics ::std::convert::From<#kind> for #error #ty_generics #where_clause {
+            #[inline]
+            fn from(f: #kind) -> Self {
+                ::trackable::error::ErrorKindExt::error(f).into()
+            }
+        }
+        #(#from_impls)*
+    })
+}
+
+/// Options specified by `#[trackable(..)]` attributes.
+struct Options {
+    error_kind: Option<Type>,
+    from: Vec<FromOption>,
+}
+impl Options {
+    fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut options = Options {
+            error_kind: None,
+            from: Vec::new(),
+        };
+        for attr in attrs.iter().filter(|a| a.path().is_ident("trackable")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("error_kind") {
+                    let value = meta.value()?;
+                    options.error_kind = Some(if value.peek(syn::LitStr) {
+                        value.parse::<syn::LitStr>()?.parse()?
+                    } else {
+                        value.parse()?
+                    });
+                    Ok(())
+                } else if meta.path.is_ident("from") {
+                    let content;
+                    parenthesized!(content in meta.input);
+                    options
+                        .from
+                        .extend(Punctuated::<FromOption, Token![,]>::parse_terminated(
+                            &content,
+                        )?);
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported option"))
+                }
+            })?;
+        }
+        Ok(options)
+    }
+}
+
+/// `$from_type [=> $kind_expr]`
+struct FromOption {
+    ty: Type,
+    kind: Option<Expr>,
+}
+impl Parse for FromOption {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let ty = input.parse()?;
+        let kind = if input.peek(Token![=>]) {
+            input.parse::<Token![=>]>()?;
+            Some(input.parse()?)
+        } else {
+            None
+        };
+        Ok(FromOption { ty, kind })
+    }
+}
+
+fn trackable_error_path(ty: &Type) -> Option<&syn::PathSegment> {
+    match *ty {
+        Type::Path(ref ty) => ty
+            .path
+            .segments
+            .last()
+            .filter(|s| s.ident == "TrackableError"),
+        _ => None,
+    }
+}
+
+fn error_kind_of(ty: &Type) -> Option<Type> {
+    match trackable_error_path(ty)?.arguments {
+        syn::PathArguments::AngleBracketed(ref args) if args.args.len() == 1 => {
+            match args.args[0] {
+                syn::GenericArgument::Type(ref kind) => Some(kind.clone()),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,141 @@
+//! Implementation of `#[derive(ErrorKind)]`.
+use proc_macro2::{Span, TokenStream};
+use syn::{Attribute, Data, DeriveInput, Error, Fields, Ident, LitStr, Result};
+
+pub fn derive(input: &DeriveInput) -> Result<TokenStream> {
+    let variants = match input.data {
+        Data::Enum(ref e) => &e.variants,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "`ErrorKind` can only be derived for enums",
+            ))
+        }
+    };
+    let kind = &input.ident;
+    let vis = &input.vis;
+    let mut generics = input.generics.clone();
+    if !generics.params.is_empty() {
+        let (_, ty_generics, _) = input.generics.split_for_impl();
+        let predicates = &mut generics.make_where_clause().predicates;
+        predicates.push(parse_quote!(#kind #ty_generics: ::std::fmt::Debug));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let mut descriptions = Vec::new();
+    let mut constructors = Vec::new();
+    for variant in variants {
+        let name = &variant.ident;
+        let description = match display_option(&variant.attrs)? {
+            Some(display) => display,
+            None => LitStr::new(&name.to_string(), name.span()),
+        };
+        let (pattern, params, value) = match variant.fields {
+            Fields::Unit => (quote!(#kind::#name), quote!(), quote!(#kind::#name)),
+            Fields::Unnamed(ref fields) => {
+                let args = (0..fields.unnamed.len())
+                    .map(|i| Ident::new(&format!("f{}", i), Span::call_site()))
+                    .collect::<Vec<_>>();
+                let types = fields.unnamed.iter().map(|f| &f.ty);
+                (
+                    quote!(#kind::#name(..)),
+                    quote!(#(#args: #types),*),
+                    quote!(#kind::#name(#(#args),*)),
+                )
+            }
+            Fields::Named(ref fields) => {
+                let args = fields.named.iter().map(|f| &f.ident).collect::<Vec<_>>();
+                let types = fields.named.iter().map(|f| &f.ty);
+                (
+                    quote!(#kind::#name { .. }),
+                    quote!(#(#args: #types),*),
+                    quote!(#kind::#name { #(#args),* }),
+                )
+            }
+        };
+        descriptions.push(quote!(#pattern => #description));
+
+        let constructor = constructor_name(name);
+        let doc = format!("Makes a `{}::{}` instance.", kind, name);
+        constructors.push(quote! {
+            #[doc = #doc]
+            #[allow(dead_code, clippy::should_implement_trait)]
+            #[inline]
+            #vis fn #constructor(#params) -> Self {
+                #value
+            }
+        });
+    }
+    let description = if descriptions.is_empty() {
+        quote!(match *self {})
+    } else {
+        quote!(match *self { #(#descriptions,)* })
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::trackable::error::ErrorKind for #kind #ty_generics #where_clause {
+            fn description(&self) -> &str {
+                #description
+            }
+
+            fn display(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                f.write_str(::trackable::error::ErrorKind::description(self))
+            }
+        }
+        impl #impl_generics ::std::fmt::Display for #kind #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                ::trackable::error::ErrorKind::display(self, f)
+            }
+        }
+        impl #impl_generics #kind #ty_generics #where_clause {
+            #(#constructors)*
+        }
+    })
+}
+
+/// Returns the value of `#[trackable(display = "..")]` attribute if it is specified.
+fn display_option(attrs: &[Attribute]) -> Result<Option<LitStr>> {
+    let mut display = None;
+    for attr in attrs.iter().filter(|a| a.path().is_ident("trackable")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("display") {
+                display = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported option"))
+            }
+        })?;
+    }
+    Ok(display)
+}
+
+/// Converts a variant name into the snake case constructor name (e.g., `NotFound` => `not_found`).
+///
+/// If the resulting name is a keyword, it is escaped as a raw identifier (e.g., `r#type`).
+/// Keywords that cannot be raw identifiers are suffixed by an underscore instead (e.g., `super_`).
+fn constructor_name(variant: &Ident) -> Ident {
+    let mut name = String::new();
+    let variant = variant.to_string();
+    let mut chars = variant.trim_start_matches("r#").chars().peekable();
+    let mut prev_lower = false;
+    while let Some(c) = chars.next() {
+        if c.is_uppercase() {
+            let next_lower = chars.peek().is_some_and(|c| c.is_lowercase());
+            if !name.is_empty() && !name.ends_with('_') && (prev_lower || next_lower) {
+                name.push('_');
+            }
+            name.extend(c.to_lowercase());
+            prev_lower = false;
+        } else {
+            name.push(c);
+            prev_lower = c.is_lowercase() || c.is_ascii_digit();
+        }
+    }
+    if syn::parse_str::<Ident>(&name).is_ok() {
+        Ident::new(&name, Span::call_site())
+    } else if ["crate", "self", "super"].contains(&name.as_str()) {
+        Ident::new(&format!("{}_", name), Span::call_site())
+    } else {
+        Ident::new_raw(&name, Span::call_site())
+    }
+}
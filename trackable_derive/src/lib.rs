@@ -0,0 +1,64 @@
+//! Procedural macros for the [trackable] crate.
+//!
+//! This crate should not be used directly.
+//! The macros are re-exported from `trackable`, and are documented there.
+//!
+//! [trackable]: https://docs.rs/trackable
+#![warn(missing_docs)]
+extern crate proc_macro;
+extern crate proc_macro2;
+#[macro_use]
+extern crate quote;
+#[macro_use]
+extern crate syn;
+
+use proc_macro::TokenStream;
+
+mod entry;
+mod error;
+mod kind;
+mod track;
+
+/// Implements the typical traits for a newtype of `TrackableError`.
+///
+/// See the documentation of the `trackable::error` module for the usage.
+#[proc_macro_derive(TrackableError, attributes(trackable))]
+pub fn derive_trackable_error(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as syn::DeriveInput);
+    error::derive(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implements `ErrorKind` and `Display` traits and per-variant constructors for an enum.
+///
+/// See the documentation of the `trackable::error` module for the usage.
+#[proc_macro_derive(ErrorKind, attributes(trackable))]
+pub fn derive_error_kind(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as syn::DeriveInput);
+    kind::derive(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Tracks every error propagated by `?` in the annotated function.
+///
+/// See the documentation of `trackable::trackable` for the usage.
+#[proc_macro_attribute]
+pub fn trackable(args: TokenStream, input: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(input as syn::ItemFn);
+    track::expand(args.into(), item)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Reports the error returned by the annotated `main` function.
+///
+/// See the documentation of `trackable::main` for the usage.
+#[proc_macro_attribute]
+pub fn main(args: TokenStream, input: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(input as syn::ItemFn);
+    entry::expand(args.into(), item)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
@@ -0,0 +1,92 @@
+//! Implementation of `#[trackable]` attribute.
+use proc_macro2::{Span, TokenStream};
+use quote::ToTokens;
+use syn::visit_mut::{self, VisitMut};
+use syn::{Error, Expr, ExprTry, Ident, Item, ItemFn, Result, ReturnType, Type};
+
+pub fn expand(args: TokenStream, mut item: ItemFn) -> Result<TokenStream> {
+    if !args.is_empty() {
+        return Err(Error::new_spanned(
+            args,
+            "`#[trackable]` does not take any arguments",
+        ));
+    }
+    let ret = match item.sig.output {
+        ReturnType::Type(_, ref ty) => (**ty).clone(),
+        ReturnType::Default => {
+            return Err(Error::new_spanned(
+                &item.sig,
+                "`#[trackable]` can only be applied to functions that return `Result`",
+            ))
+        }
+    };
+    if let Type::ImplTrait(_) = ret {
+        return Err(Error::new_spanned(
+            ret,
+            "`#[trackable]` cannot be applied to functions that return `impl Trait`",
+        ));
+    }
+    if !is_option(&ret) {
+        TrackTry { ret }.visit_block_mut(&mut item.block);
+    }
+    Ok(item.into_token_stream())
+}
+
+/// Rewrites every `$expr?` that returns from the function to track the returned error.
+struct TrackTry {
+    ret: Type,
+}
+impl TrackTry {
+    fn rewrite(&self, expr: &ExprTry, snippet: TokenStream) -> Expr {
+        let ret = &self.ret;
+        let target = &expr.expr;
+        let value = Ident::new("__trackable_value", Span::mixed_site());
+        let error = Ident::new("__trackable_error", Span::mixed_site());
+        let result = Ident::new("__trackable_result", Span::mixed_site());
+
+        // The span of `?` is used so that `line!()` and `column!()` point to the propagation point.
+        let track = quote_spanned! {expr.question_token.span=>
+            ::trackable::trackable_track_try!(#result, #snippet)
+        };
+        parse_quote! {
+            match #target {
+                ::std::result::Result::Ok(#value) => #value,
+                ::std::result::Result::Err(#error) => {
+                    let #result: #ret =
+                        ::std::result::Result::Err(::std::convert::From::from(#error));
+                    return #track;
+                }
+            }
+        }
+    }
+}
+impl VisitMut for TrackTry {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        match *expr {
+            // `?` in these expressions does not return from the function
+            Expr::Closure(_) | Expr::Async(_) | Expr::TryBlock(_) => return,
+            _ => {}
+        }
+        let snippet = match *expr {
+            Expr::Try(ref e) => Some(e.expr.to_token_stream()),
+            _ => None,
+        };
+        visit_mut::visit_expr_mut(self, expr);
+        if let Some(snippet) = snippet {
+            if let Expr::Try(ref e) = *expr {
+                *expr = self.rewrite(e, snippet);
+            }
+        }
+    }
+
+    fn visit_item_mut(&mut self, _item: &mut Item) {
+        // Nested items are not the targets
+    }
+}
+
+fn is_option(ty: &Type) -> bool {
+    match *ty {
+        Type::Path(ref ty) => ty.path.segments.last().is_some_and(|s| s.ident == "Option"),
+        _ => false,
+    }
+}
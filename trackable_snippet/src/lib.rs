@@ -0,0 +1,33 @@
+//! A procedural macro which captures source line snippets for the [trackable] crate.
+//!
+//! This is an implementation detail of the `source-snippets` feature of `trackable`.
+//!
+//! [trackable]: https://crates.io/crates/trackable
+#![warn(missing_docs)]
+extern crate proc_macro;
+
+use proc_macro::{Delimiter, Literal, Span, TokenStream, TokenTree};
+use std::fs;
+
+/// Expands to a string literal of the source line where the given tokens start.
+///
+/// Leading and trailing whitespaces of the line are trimmed.
+/// If the source file cannot be read, this expands to an empty string.
+#[proc_macro]
+pub fn source_line(input: TokenStream) -> TokenStream {
+    let line = first_span(input).and_then(|span| {
+        let source = fs::read_to_string(span.local_file()?).ok()?;
+        let line = source.lines().nth(span.line().checked_sub(1)?)?;
+        Some(line.trim().to_owned())
+    });
+    TokenTree::from(Literal::string(line.as_deref().unwrap_or(""))).into()
+}
+
+fn first_span(input: TokenStream) -> Option<Span> {
+    match input.into_iter().next()? {
+        TokenTree::Group(g) if g.delimiter() == Delimiter::None => {
+            first_span(g.stream()).or_else(|| Some(g.span()))
+        }
+        t => Some(t.span()),
+    }
+}